@@ -1,5 +1,19 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Bytes, Env, String, Vec, Map, U256};
+use soroban_sdk::{contract, contractimpl, contracttype, token, xdr::ToXdr, Address, Bytes, BytesN, ConversionError, Env, String, Symbol, TryFromVal, Val, Vec, Map, U256};
+
+use crate::capacity;
+use crate::fees;
+
+/// Layout version of this contract's stored state
+const STATE_VERSION: u32 = 3;
+/// Ledgers an idempotency key is remembered for (about 1 day)
+const IDEMPOTENCY_KEY_TTL_LEDGERS: u32 = 17_280;
+/// Longest a swap may lock funds for, on any chain pair (7 days)
+const MAX_TIMELOCK_SECONDS: u64 = 604_800;
+/// Largest fee discount a reputation tier may grant (50%)
+const MAX_FEE_DISCOUNT_BPS: u32 = 5_000;
+/// Most swaps a single batch refund may cover
+const MAX_REFUND_BATCH: u32 = 50;
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -13,7 +27,37 @@ pub struct AtomicSwap {
     pub target_asset: Bytes,
     pub source_amount: U256,
     pub target_amount: U256,
-    pub secret_hash: Bytes,
+    pub fee: U256,
+    pub hash_lock: HashLock,
+    pub initiator_deposit: DepositState,
+    pub participant_deposit: DepositState,
+    pub deposit_order: DepositOrder,
+    pub fills: Vec<SwapFill>,
+    pub secret: Option<Bytes>,
+    pub status: SwapStatus,
+    pub redeem_deadline: u64, // redeem by this time
+    pub refund_deadline: u64, // refund only after this time, always later than `redeem_deadline`
+    pub created_at: u64,
+    pub completed_at: Option<u64>,
+}
+
+/// Swap layout stored under state version 2, before deposit ordering, fills and staggered deadlines
+/// Only ever read back, so it is decoded by hand from the stored field map rather than derived
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AtomicSwapV2 {
+    pub swap_id: u64,
+    pub initiator: Address,
+    pub participant: Address,
+    pub source_chain: u32,
+    pub target_chain: u32,
+    pub source_asset: Bytes,
+    pub target_asset: Bytes,
+    pub source_amount: U256,
+    pub target_amount: U256,
+    pub fee: U256,
+    pub hash_lock: HashLock,
+    pub initiator_deposit: DepositState,
+    pub participant_deposit: DepositState,
     pub secret: Option<Bytes>,
     pub status: SwapStatus,
     pub timeout: u64,
@@ -21,6 +65,58 @@ pub struct AtomicSwap {
     pub completed_at: Option<u64>,
 }
 
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum HashAlgorithm {
+    Sha256,
+    Keccak256,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HashLock {
+    pub secret_hash: Bytes,
+    pub algorithm: HashAlgorithm,
+    pub salt: Bytes,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SwapLeg {
+    pub chain_id: u32,
+    pub asset: Bytes,
+    pub amount: U256,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DepositOrder {
+    InitiatorFirst,
+    ParticipantFirst,
+    Either,
+    InitiatorOnly, // one-sided: only the initiator escrows, the participant claims with the secret
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DepositState {
+    NotDeposited,
+    Pending(u64),   // deposit block, awaiting confirmations
+    Confirmed(u64), // deposit block
+}
+
+/// A portion of the target leg deposited by one of several participants
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SwapFill {
+    pub participant: Address,
+    pub amount: U256,       // of the target asset
+    pub source_share: U256, // of the source asset, pro rata to `amount`
+    pub hash_lock: HashLock, // the swap's lock when the fill was made; fills redeem with the initiator's secret
+    pub deposit: DepositState,
+    pub redeemed: bool,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum SwapStatus {
@@ -41,6 +137,71 @@ pub struct SwapProposal {
     pub created_at: u64,
 }
 
+/// Proposal layout stored under state version 2, decoded by hand like `AtomicSwapV2`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SwapProposalV2 {
+    pub proposal_id: u64,
+    pub proposer: Address,
+    pub proposed_swap: AtomicSwapV2,
+    pub signature: Bytes,
+    pub created_at: u64,
+}
+
+/// Read one field of a struct stored as a `contracttype` field map
+fn legacy_field<T: TryFromVal<Env, Val>>(env: &Env, fields: &Map<Symbol, Val>, name: &str) -> Result<T, ConversionError> {
+    let value = fields.get(Symbol::new(env, name)).ok_or(ConversionError)?;
+    T::try_from_val(env, &value).map_err(|_| ConversionError)
+}
+
+impl TryFromVal<Env, Val> for AtomicSwapV2 {
+    type Error = ConversionError;
+
+    fn try_from_val(env: &Env, val: &Val) -> Result<Self, Self::Error> {
+        let fields = Map::<Symbol, Val>::try_from_val(env, val)?;
+        if fields.len() != 18 {
+            return Err(ConversionError);
+        }
+        Ok(AtomicSwapV2 {
+            swap_id: legacy_field(env, &fields, "swap_id")?,
+            initiator: legacy_field(env, &fields, "initiator")?,
+            participant: legacy_field(env, &fields, "participant")?,
+            source_chain: legacy_field(env, &fields, "source_chain")?,
+            target_chain: legacy_field(env, &fields, "target_chain")?,
+            source_asset: legacy_field(env, &fields, "source_asset")?,
+            target_asset: legacy_field(env, &fields, "target_asset")?,
+            source_amount: legacy_field(env, &fields, "source_amount")?,
+            target_amount: legacy_field(env, &fields, "target_amount")?,
+            fee: legacy_field(env, &fields, "fee")?,
+            hash_lock: legacy_field(env, &fields, "hash_lock")?,
+            initiator_deposit: legacy_field(env, &fields, "initiator_deposit")?,
+            participant_deposit: legacy_field(env, &fields, "participant_deposit")?,
+            secret: legacy_field(env, &fields, "secret")?,
+            status: legacy_field(env, &fields, "status")?,
+            timeout: legacy_field(env, &fields, "timeout")?,
+            created_at: legacy_field(env, &fields, "created_at")?,
+            completed_at: legacy_field(env, &fields, "completed_at")?,
+        })
+    }
+}
+
+impl TryFromVal<Env, Val> for SwapProposalV2 {
+    type Error = ConversionError;
+
+    fn try_from_val(env: &Env, val: &Val) -> Result<Self, Self::Error> {
+        let fields = Map::<Symbol, Val>::try_from_val(env, val)?;
+        if fields.len() != 5 {
+            return Err(ConversionError);
+        }
+        Ok(SwapProposalV2 {
+            proposal_id: legacy_field(env, &fields, "proposal_id")?,
+            proposer: legacy_field(env, &fields, "proposer")?,
+            proposed_swap: legacy_field(env, &fields, "proposed_swap")?,
+            signature: legacy_field(env, &fields, "signature")?,
+            created_at: legacy_field(env, &fields, "created_at")?,
+        })
+    }
+}
+
 #[contracttype]
 pub enum SwapDataKey {
     AtomicSwap(u64),
@@ -48,6 +209,21 @@ pub enum SwapDataKey {
     SwapCount,
     ProposalCount,
     ActiveSwaps,
+    FeeRate(u32), // chain_id -> basis points
+    FeeToken,
+    RequiredConfirmations(u32),
+    StateVersion,
+    StateCapacity,
+    MaxPending,
+    IdempotencyKey(Address, BytesN<32>), // initiator, key -> swap_id (temporary storage)
+    RevealedSecret(u64), // swap_id -> secret revealed before the redeem deadline
+    PairMaxTimelock(u32, u32), // lower chain_id, higher chain_id -> seconds
+    Committed(u64, Address), // swap_id, party that committed to the swap
+    Reputation(Address),
+    FeeDiscountTiers, // (min_reputation, discount_bps), ascending by reputation
+    EscrowToken(Bytes), // asset -> token contract its deposits are escrowed in on this chain
+    Escrowed(u64, Address), // swap_id, depositor -> (token, amount) held by the contract
+    MinReputationAmount, // smallest unescrowed source amount a redeemed swap earns reputation for
     Admin,
 }
 
@@ -66,41 +242,173 @@ impl AtomicSwapContract {
         env.storage().instance().set(&SwapDataKey::SwapCount, &0u64);
         env.storage().instance().set(&SwapDataKey::ProposalCount, &0u64);
         env.storage().instance().set(&SwapDataKey::ActiveSwaps, &Vec::new(&env));
+        env.storage().instance().set(&SwapDataKey::MaxPending, &capacity::DEFAULT_MAX_PENDING);
+        env.storage().instance().set(&SwapDataKey::StateVersion, &STATE_VERSION);
     }
 
-    /// Initiate atomic swap
-    pub fn initiate_swap(
+    /// Get the layout version of stored state (state predating versioning is v1)
+    pub fn get_state_version(env: Env) -> u32 {
+        env.storage().instance().get(&SwapDataKey::StateVersion).unwrap_or(1)
+    }
+
+    /// Upgrade state written by an older contract version to the current layout
+    pub fn migrate(env: Env, admin: Address) -> u32 {
+        let stored_admin: Address = env.storage().instance()
+            .get(&SwapDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let version = Self::get_state_version(env.clone());
+        if version > STATE_VERSION {
+            panic!("Stored state is newer than this contract");
+        }
+        
+        // v1 -> v2: the version is now tracked explicitly, existing entries are kept as-is
+        // v2 -> v3: swaps and proposals in the v2 layout are upgraded as they are read and rewritten on their next update
+        env.storage().instance().set(&SwapDataKey::StateVersion, &STATE_VERSION);
+        
+        STATE_VERSION
+    }
+
+    /// Get the byte capacity growing state lists may occupy
+    pub fn get_state_capacity(env: Env) -> u32 {
+        env.storage().instance()
+            .get(&SwapDataKey::StateCapacity)
+            .unwrap_or(capacity::DEFAULT_STATE_CAPACITY)
+    }
+
+    /// Set how many items may be pending at once before new submissions are rejected
+    pub fn set_max_pending(env: Env, admin: Address, max_pending: u32) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&SwapDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        if max_pending == 0 {
+            panic!("Max pending must be positive");
+        }
+        
+        env.storage().instance().set(&SwapDataKey::MaxPending, &max_pending);
+    }
+
+    /// Get how many items may be pending at once
+    pub fn get_max_pending(env: Env) -> u32 {
+        env.storage().instance()
+            .get(&SwapDataKey::MaxPending)
+            .unwrap_or(capacity::DEFAULT_MAX_PENDING)
+    }
+
+    /// Grow the state capacity ahead of demand, prepaying rent for the larger entry
+    pub fn realloc_state(env: Env, admin: Address, new_size: u32) -> u32 {
+        let stored_admin: Address = env.storage().instance()
+            .get(&SwapDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let current = Self::get_state_capacity(env.clone());
+        let new_size = capacity::realloc(&env, current, new_size);
+        env.storage().instance().set(&SwapDataKey::StateCapacity, &new_size);
+        
+        new_size
+    }
+
+    /// Derive a collision-resistant key identifying a swap from its defining terms
+    /// This is the recommended `idempotency_key` for `initiate_swap`: retries of the same swap map
+    /// to the same key, while any change to the terms yields a different one
+    pub fn derive_swap_id(
         env: Env,
-        participant: Address,
+        initiator: Address,
         source_chain: u32,
         target_chain: u32,
-        source_asset: Bytes,
-        target_asset: Bytes,
-        source_amount: U256,
-        target_amount: U256,
         secret_hash: Bytes,
         timeout: u64,
+    ) -> BytesN<32> {
+        let terms = (initiator, source_chain, target_chain, secret_hash, timeout).to_xdr(&env);
+        env.crypto().sha256(&terms)
+    }
+
+    /// Initiate atomic swap between the initiator's `source` leg and the participant's `target` leg
+    /// The participant redeems by `redeem_deadline`; the initiator may refund only after the later `refund_deadline`
+    /// Retrying with the same `idempotency_key` returns the swap created by the first call
+    pub fn initiate_swap(
+        env: Env,
+        participant: Address,
+        source: SwapLeg,
+        target: SwapLeg,
+        hash_lock: HashLock,
+        redeem_deadline: u64,
+        refund_deadline: u64,
+        deposit_order: DepositOrder,
+        idempotency_key: Option<BytesN<32>>,
         initiator: Address,
     ) -> u64 {
         initiator.require_auth();
         
+        // Keys live in temporary storage so they expire instead of accumulating
+        let idempotency_key = idempotency_key.map(|key| SwapDataKey::IdempotencyKey(initiator.clone(), key));
+        if let Some(key) = &idempotency_key {
+            if let Some(swap_id) = env.storage().temporary().get::<SwapDataKey, u64>(key) {
+                return swap_id;
+            }
+        }
+        
+        Self::check_hash_lock(&hash_lock);
+        
+        // Staggered deadlines leave the redeemer a window the refunder can never race
+        if refund_deadline <= redeem_deadline {
+            panic!("Refund deadline must follow redeem deadline");
+        }
+        
+        let max_timelock = Self::get_max_timelock(env.clone(), source.chain_id, target.chain_id);
+        if refund_deadline.saturating_sub(env.ledger().timestamp()) > max_timelock {
+            panic!("Timelock exceeds maximum for chain pair");
+        }
+        
         let count: u64 = env.storage().instance().get(&SwapDataKey::SwapCount).unwrap_or(0);
         let swap_id = count + 1;
         
+        // Reliable initiators pay a discounted share of the chain's rate; the fee is charged here rather than
+        // at redeem, so the discount follows the initiator's reputation instead of the participant's
+        let fee = Self::compute_fee(env.clone(), source.amount.clone(), source.chain_id);
+        let discount = fees::amount_fee(&env, &fee, Self::get_fee_discount_bps(env.clone(), initiator.clone()));
+        let fee = fee.sub(&discount);
+        Self::collect_fee(env.clone(), initiator.clone(), fee.clone());
+        
         let swap = AtomicSwap {
             swap_id,
             initiator: initiator.clone(),
             participant: participant.clone(),
-            source_chain,
-            target_chain,
-            source_asset: source_asset.clone(),
-            target_asset: target_asset.clone(),
-            source_amount,
-            target_amount,
-            secret_hash: secret_hash.clone(),
+            source_chain: source.chain_id,
+            target_chain: target.chain_id,
+            source_asset: source.asset,
+            target_asset: target.asset,
+            source_amount: source.amount,
+            target_amount: target.amount,
+            fee,
+            hash_lock: hash_lock.clone(),
+            initiator_deposit: DepositState::NotDeposited,
+            participant_deposit: DepositState::NotDeposited,
+            deposit_order,
+            fills: Vec::new(&env),
             secret: None,
             status: SwapStatus::Initiated,
-            timeout,
+            redeem_deadline,
+            refund_deadline,
             created_at: env.ledger().timestamp(),
             completed_at: None,
         };
@@ -112,47 +420,570 @@ impl AtomicSwapContract {
         let mut active_swaps: Vec<u64> = env.storage().instance()
             .get(&SwapDataKey::ActiveSwaps)
             .unwrap_or(Vec::new(&env));
+        capacity::ensure_below_max_pending(active_swaps.len() as u64, Self::get_max_pending(env.clone()));
         active_swaps.push_back(swap_id);
+        capacity::ensure_fits(&env, &active_swaps, Self::get_state_capacity(env.clone()));
         env.storage().instance().set(&SwapDataKey::ActiveSwaps, &active_swaps);
         
+        if let Some(key) = &idempotency_key {
+            env.storage().temporary().set(key, &swap_id);
+            env.storage().temporary().extend_ttl(key, IDEMPOTENCY_KEY_TTL_LEDGERS, IDEMPOTENCY_KEY_TTL_LEDGERS);
+        }
+        
         swap_id
     }
 
-    /// Fund atomic swap
-    pub fn fund_swap(env: Env, swap_id: u64, funder: Address) -> bool {
+    /// Set the swap fee rate in basis points for a source chain
+    pub fn set_fee_rate(env: Env, admin: Address, chain_id: u32, rate_bps: u32) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&SwapDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        if rate_bps > fees::FEE_DENOMINATOR {
+            panic!("Fee rate exceeds 100%");
+        }
+        
+        env.storage().instance().set(&SwapDataKey::FeeRate(chain_id), &rate_bps);
+    }
+
+    /// Get the swap fee rate in basis points for a source chain
+    pub fn get_fee_rate(env: Env, chain_id: u32) -> u32 {
+        env.storage().instance()
+            .get(&SwapDataKey::FeeRate(chain_id))
+            .unwrap_or(fees::DEFAULT_FEE_BPS)
+    }
+
+    /// Set the reputation tiers that discount swap fees, as (min_reputation, discount_bps) ascending by reputation
+    pub fn set_fee_discount_tiers(env: Env, admin: Address, tiers: Vec<(u32, u32)>) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&SwapDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let mut previous: Option<u32> = None;
+        for (min_reputation, discount_bps) in tiers.iter() {
+            if discount_bps > MAX_FEE_DISCOUNT_BPS {
+                panic!("Fee discount exceeds maximum");
+            }
+            if previous.map_or(false, |previous| min_reputation <= previous) {
+                panic!("Tiers must ascend by reputation");
+            }
+            previous = Some(min_reputation);
+        }
+        
+        env.storage().instance().set(&SwapDataKey::FeeDiscountTiers, &tiers);
+    }
+
+    /// Get the reputation tiers that discount swap fees
+    pub fn get_fee_discount_tiers(env: Env) -> Vec<(u32, u32)> {
+        env.storage().instance()
+            .get(&SwapDataKey::FeeDiscountTiers)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Set the smallest source amount an unescrowed swap must carry to earn its parties reputation
+    pub fn set_min_reputation_amount(env: Env, admin: Address, amount: U256) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&SwapDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&SwapDataKey::MinReputationAmount, &amount);
+    }
+
+    /// Get the smallest source amount an unescrowed swap must carry to earn reputation; until set, only escrowed swaps do
+    pub fn get_min_reputation_amount(env: Env) -> Option<U256> {
+        env.storage().instance().get(&SwapDataKey::MinReputationAmount)
+    }
+
+    /// Get a party's reputation: swaps redeemed, less refunds it forced on a counterparty
+    pub fn get_reputation(env: Env, party: Address) -> u32 {
+        env.storage().instance().get(&SwapDataKey::Reputation(party)).unwrap_or(0)
+    }
+
+    /// Get the fee discount (basis points) a party's reputation earns
+    pub fn get_fee_discount_bps(env: Env, party: Address) -> u32 {
+        let reputation = Self::get_reputation(env.clone(), party);
+        let mut discount_bps = 0;
+        for (min_reputation, tier_discount_bps) in Self::get_fee_discount_tiers(env).iter() {
+            if reputation < min_reputation {
+                break;
+            }
+            discount_bps = tier_discount_bps;
+        }
+        discount_bps
+    }
+
+    /// Set the token fees are collected in
+    pub fn set_fee_token(env: Env, admin: Address, fee_token: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&SwapDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&SwapDataKey::FeeToken, &fee_token);
+    }
+
+    /// Get the token fees are collected in
+    pub fn get_fee_token(env: Env) -> Option<Address> {
+        env.storage().instance().get(&SwapDataKey::FeeToken)
+    }
+
+    /// Set the token contract deposits of `asset` are escrowed in; `None` leaves the asset off-chain
+    /// Deposits of an escrowed asset move the leg amount into this contract instead of being taken on trust
+    pub fn set_escrow_token(env: Env, admin: Address, asset: Bytes, escrow_token: Option<Address>) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&SwapDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let key = SwapDataKey::EscrowToken(asset);
+        match escrow_token {
+            Some(escrow_token) => env.storage().instance().set(&key, &escrow_token),
+            None => env.storage().instance().remove(&key),
+        }
+    }
+
+    /// Get the token contract deposits of `asset` are escrowed in
+    pub fn get_escrow_token(env: Env, asset: Bytes) -> Option<Address> {
+        env.storage().instance().get(&SwapDataKey::EscrowToken(asset))
+    }
+
+    /// Get the token amount a party has escrowed in a swap
+    pub fn get_escrowed(env: Env, swap_id: u64, party: Address) -> i128 {
+        env.storage().instance()
+            .get::<SwapDataKey, (Address, i128)>(&SwapDataKey::Escrowed(swap_id, party))
+            .map_or(0, |(_, amount)| amount)
+    }
+
+    /// Get the fee vault collected fees are sent to
+    pub fn get_fee_vault(env: Env) -> Address {
+        fees::fee_vault(&env)
+    }
+
+    /// Get the fee a swap of `amount` from `chain_id` would be charged
+    pub fn get_fee_estimate(env: Env, amount: U256, chain_id: u32) -> U256 {
+        Self::compute_fee(env, amount, chain_id)
+    }
+
+    /// Check whether a chain has a fee rate or confirmation requirement configured
+    pub fn is_chain_supported(env: Env, chain_id: u32) -> bool {
+        env.storage().instance().has(&SwapDataKey::FeeRate(chain_id))
+            || env.storage().instance().has(&SwapDataKey::RequiredConfirmations(chain_id))
+    }
+
+    /// Cap how long swaps between two chains may lock funds, e.g. a few multiples of their finality time
+    /// The cap applies in both directions and can only tighten the global maximum
+    pub fn set_pair_max_timelock(env: Env, admin: Address, chain_a: u32, chain_b: u32, seconds: u64) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&SwapDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&Self::pair_key(chain_a, chain_b), &seconds);
+    }
+
+    /// Get the longest timelock allowed between two chains: the tighter of the pair and global maxima
+    pub fn get_max_timelock(env: Env, chain_a: u32, chain_b: u32) -> u64 {
+        env.storage().instance()
+            .get::<SwapDataKey, u64>(&Self::pair_key(chain_a, chain_b))
+            .map_or(MAX_TIMELOCK_SECONDS, |seconds| seconds.min(MAX_TIMELOCK_SECONDS))
+    }
+
+    fn pair_key(chain_a: u32, chain_b: u32) -> SwapDataKey {
+        if chain_a <= chain_b {
+            SwapDataKey::PairMaxTimelock(chain_a, chain_b)
+        } else {
+            SwapDataKey::PairMaxTimelock(chain_b, chain_a)
+        }
+    }
+
+    /// Set the confirmations a deposit on a chain needs before it counts
+    pub fn set_required_confirmations(env: Env, admin: Address, chain_id: u32, confirmations: u64) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&SwapDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&SwapDataKey::RequiredConfirmations(chain_id), &confirmations);
+    }
+
+    /// Get the confirmations a deposit on a chain needs before it counts
+    pub fn get_required_confirmations(env: Env, chain_id: u32) -> u64 {
+        env.storage().instance()
+            .get(&SwapDataKey::RequiredConfirmations(chain_id))
+            .unwrap_or(0)
+    }
+
+    /// Commit to a swap ahead of depositing; each party, and each filler, must commit before its own deposit is accepted
+    pub fn commit_swap(env: Env, swap_id: u64, party: Address) {
+        party.require_auth();
+        
+        let swap: AtomicSwap = Self::load_swap(env.clone(), swap_id)
+            .unwrap_or_else(|| panic!("Swap not found"));
+        
+        if swap.status != SwapStatus::Initiated {
+            panic!("Swap not in initiated state");
+        }
+        
+        if env.ledger().timestamp() > swap.redeem_deadline {
+            panic!("Swap has expired");
+        }
+        
+        // Anyone else commits as a prospective filler of the target leg
+        if party != swap.initiator && party != swap.participant && swap.deposit_order == DepositOrder::InitiatorOnly {
+            panic!("Only swap parties can commit");
+        }
+        
+        if Self::is_committed(env.clone(), swap_id, party.clone()) {
+            panic!("Already committed");
+        }
+        
+        env.storage().instance().set(&SwapDataKey::Committed(swap_id, party), &true);
+    }
+
+    /// Check whether a party has committed to a swap
+    pub fn is_committed(env: Env, swap_id: u64, party: Address) -> bool {
+        env.storage().instance().has(&SwapDataKey::Committed(swap_id, party))
+    }
+
+    /// Record a side's deposit made at `deposit_block`, given the chain's `head_block`
+    pub fn fund_swap(env: Env, swap_id: u64, funder: Address, deposit_block: u64, head_block: u64) -> bool {
         funder.require_auth();
         
-        let mut swap: AtomicSwap = env.storage().instance()
-            .get(&SwapDataKey::AtomicSwap(swap_id))
+        let mut swap: AtomicSwap = Self::load_swap(env.clone(), swap_id)
+            .unwrap_or_else(|| panic!("Swap not found"));
+        
+        if swap.status != SwapStatus::Initiated {
+            panic!("Swap not in initiated state");
+        }
+        
+        if env.ledger().timestamp() > swap.redeem_deadline {
+            panic!("Swap has expired");
+        }
+        
+        // The initiator deposits on the source chain, the participant on the target chain
+        let chain_id = if funder == swap.initiator {
+            swap.source_chain
+        } else if funder == swap.participant {
+            swap.target_chain
+        } else {
+            panic!("Only swap parties can fund swap");
+        };
+        
+        if Self::deposit_of(&swap, &funder) != DepositState::NotDeposited {
+            panic!("Deposit already recorded");
+        }
+        
+        if !Self::is_committed(env.clone(), swap_id, funder.clone()) {
+            panic!("Commit to the swap before depositing");
+        }
+        
+        if funder == swap.participant && !swap.fills.is_empty() {
+            panic!("Swap is being filled by multiple participants");
+        }
+        
+        if funder == swap.participant && swap.deposit_order == DepositOrder::InitiatorOnly {
+            panic!("Only the initiator deposits in a one-sided swap");
+        }
+        
+        // The party at greater risk can require the other side to be confirmed first
+        let out_of_order = match swap.deposit_order {
+            DepositOrder::InitiatorFirst => {
+                funder == swap.participant && !matches!(swap.initiator_deposit, DepositState::Confirmed(_))
+            }
+            DepositOrder::ParticipantFirst => {
+                funder == swap.initiator && !Self::counterparty_confirmed(&swap)
+            }
+            DepositOrder::Either | DepositOrder::InitiatorOnly => false,
+        };
+        if out_of_order {
+            panic!("Deposit out of order");
+        }
+        
+        let (asset, amount) = if funder == swap.initiator {
+            (swap.source_asset.clone(), swap.source_amount.clone())
+        } else {
+            (swap.target_asset.clone(), swap.target_amount.clone())
+        };
+        if let Some(escrow_token) = Self::get_escrow_token(env.clone(), asset) {
+            Self::escrow_deposit(env.clone(), swap_id, &escrow_token, &funder, amount);
+        }
+        
+        let deposit = if Self::is_confirmed(env.clone(), chain_id, deposit_block, head_block) {
+            DepositState::Confirmed(deposit_block)
+        } else {
+            DepositState::Pending(deposit_block)
+        };
+        let confirmed = matches!(deposit, DepositState::Confirmed(_));
+        
+        Self::set_deposit(&mut swap, &funder, deposit);
+        env.storage().instance().set(&SwapDataKey::AtomicSwap(swap_id), &swap);
+        
+        confirmed
+    }
+
+    /// Promote a pending deposit once it is buried under `head_block`
+    pub fn confirm_deposit(env: Env, swap_id: u64, funder: Address, head_block: u64) -> bool {
+        let mut swap: AtomicSwap = Self::load_swap(env.clone(), swap_id)
             .unwrap_or_else(|| panic!("Swap not found"));
         
         if swap.status != SwapStatus::Initiated {
             panic!("Swap not in initiated state");
         }
         
-        if funder != swap.initiator {
-            panic!("Only initiator can fund swap");
+        let chain_id = if funder == swap.initiator { swap.source_chain } else { swap.target_chain };
+        let deposit_block = match Self::deposit_of(&swap, &funder) {
+            DepositState::Pending(block) => block,
+            _ => panic!("No pending deposit"),
+        };
+        
+        if !Self::is_confirmed(env.clone(), chain_id, deposit_block, head_block) {
+            return false;
         }
         
-        if env.ledger().timestamp() > swap.timeout {
+        Self::set_deposit(&mut swap, &funder, DepositState::Confirmed(deposit_block));
+        env.storage().instance().set(&SwapDataKey::AtomicSwap(swap_id), &swap);
+        
+        true
+    }
+
+    /// Fill part of the target leg, depositing `amount` at `deposit_block` under the initiator's hash lock
+    /// Returns the index of the fill; the swap is funded once fills cover the whole target amount
+    pub fn fill_swap(
+        env: Env,
+        swap_id: u64,
+        filler: Address,
+        amount: U256,
+        deposit_block: u64,
+        head_block: u64,
+    ) -> u32 {
+        filler.require_auth();
+        
+        let mut swap: AtomicSwap = Self::load_swap(env.clone(), swap_id)
+            .unwrap_or_else(|| panic!("Swap not found"));
+        
+        if swap.status != SwapStatus::Initiated {
+            panic!("Swap not in initiated state");
+        }
+        
+        if env.ledger().timestamp() > swap.redeem_deadline {
             panic!("Swap has expired");
         }
         
-        swap.status = SwapStatus::Funded;
+        if filler == swap.initiator {
+            panic!("Initiator cannot fill own swap");
+        }
+        
+        if !Self::is_committed(env.clone(), swap_id, filler.clone()) {
+            panic!("Commit to the swap before depositing");
+        }
+        
+        if swap.deposit_order == DepositOrder::InitiatorOnly {
+            panic!("Only the initiator deposits in a one-sided swap");
+        }
+        
+        if swap.participant_deposit != DepositState::NotDeposited {
+            panic!("Swap already funded by participant");
+        }
+        
+        if Self::get_escrow_token(env.clone(), swap.target_asset.clone()).is_some() {
+            panic!("Escrowed assets must be deposited in full");
+        }
+        
+        if swap.deposit_order == DepositOrder::InitiatorFirst
+            && !matches!(swap.initiator_deposit, DepositState::Confirmed(_))
+        {
+            panic!("Deposit out of order");
+        }
+        
+        let zero = U256::from_u32(&env, 0);
+        if amount == zero {
+            panic!("Fill amount must be positive");
+        }
+        if Self::filled_amount(env.clone(), &swap).add(&amount) > swap.target_amount {
+            panic!("Fill exceeds remaining amount");
+        }
+        
+        let deposit = if Self::is_confirmed(env.clone(), swap.target_chain, deposit_block, head_block) {
+            DepositState::Confirmed(deposit_block)
+        } else {
+            DepositState::Pending(deposit_block)
+        };
+        
+        swap.fills.push_back(SwapFill {
+            participant: filler,
+            source_share: swap.source_amount.mul(&amount).div(&swap.target_amount),
+            amount,
+            hash_lock: swap.hash_lock.clone(),
+            deposit,
+            redeemed: false,
+        });
+        Self::update_funded(&mut swap);
         env.storage().instance().set(&SwapDataKey::AtomicSwap(swap_id), &swap);
         
+        swap.fills.len() - 1
+    }
+
+    /// Promote a pending fill deposit once it is buried under `head_block`
+    pub fn confirm_fill(env: Env, swap_id: u64, fill_index: u32, head_block: u64) -> bool {
+        let mut swap: AtomicSwap = Self::load_swap(env.clone(), swap_id)
+            .unwrap_or_else(|| panic!("Swap not found"));
+        
+        if swap.status != SwapStatus::Initiated {
+            panic!("Swap not in initiated state");
+        }
+        
+        let mut fill = swap.fills.get(fill_index)
+            .unwrap_or_else(|| panic!("Fill not found"));
+        let deposit_block = match fill.deposit {
+            DepositState::Pending(block) => block,
+            _ => panic!("No pending deposit"),
+        };
+        
+        if !Self::is_confirmed(env.clone(), swap.target_chain, deposit_block, head_block) {
+            return false;
+        }
+        
+        fill.deposit = DepositState::Confirmed(deposit_block);
+        swap.fills.set(fill_index, fill);
+        Self::update_funded(&mut swap);
+        env.storage().instance().set(&SwapDataKey::AtomicSwap(swap_id), &swap);
+        
+        true
+    }
+
+    /// Redeem one participant's fill with the initiator's secret, learned once the initiator claims the fills
+    /// The swap completes once every fill has been redeemed
+    pub fn redeem_fill(env: Env, swap_id: u64, fill_index: u32, secret: Bytes, redeemer: Address) -> bool {
+        redeemer.require_auth();
+        
+        let mut swap: AtomicSwap = Self::load_swap(env.clone(), swap_id)
+            .unwrap_or_else(|| panic!("Swap not found"));
+        
+        if swap.status != SwapStatus::Funded {
+            panic!("Swap not funded");
+        }
+        
+        if env.ledger().timestamp() > swap.redeem_deadline {
+            panic!("Swap has expired");
+        }
+        
+        let mut fill = swap.fills.get(fill_index)
+            .unwrap_or_else(|| panic!("Fill not found"));
+        
+        if redeemer != fill.participant {
+            panic!("Only the filling participant can redeem");
+        }
+        
+        if fill.redeemed {
+            panic!("Fill already redeemed");
+        }
+        
+        let computed_hash = Self::compute_secret_hash(env.clone(), secret.clone(), swap.hash_lock.clone());
+        if computed_hash != swap.hash_lock.secret_hash {
+            panic!("Invalid secret");
+        }
+        
+        let filler = fill.participant.clone();
+        let filler_earns = Self::earns_reputation(env.clone(), swap_id, &swap, fill.source_share.clone());
+        fill.redeemed = true;
+        swap.fills.set(fill_index, fill);
+        swap.secret = Some(secret);
+        
+        let completed = swap.fills.iter().all(|fill| fill.redeemed);
+        if completed {
+            swap.status = SwapStatus::Redeemed;
+            swap.completed_at = Some(env.ledger().timestamp());
+        }
+        
+        env.storage().instance().set(&SwapDataKey::AtomicSwap(swap_id), &swap);
+        if filler_earns {
+            Self::adjust_reputation(env.clone(), filler, true);
+        }
+        
+        if completed {
+            if Self::earns_reputation(env.clone(), swap_id, &swap, swap.source_amount.clone()) {
+                Self::adjust_reputation(env.clone(), swap.initiator.clone(), true);
+            }
+            Self::remove_from_active_swaps(env.clone(), swap_id);
+        }
+        
         true
     }
 
+    /// Publish a swap's secret before its redeem deadline; anyone who has seen it may reveal it
+    pub fn reveal_secret(env: Env, swap_id: u64, secret: Bytes) {
+        let swap: AtomicSwap = Self::load_swap(env.clone(), swap_id)
+            .unwrap_or_else(|| panic!("Swap not found"));
+        
+        if swap.status != SwapStatus::Funded {
+            panic!("Swap not funded");
+        }
+        
+        if env.ledger().timestamp() > swap.redeem_deadline {
+            panic!("Swap has expired");
+        }
+        
+        let computed_hash = Self::compute_secret_hash(env.clone(), secret.clone(), swap.hash_lock.clone());
+        if computed_hash != swap.hash_lock.secret_hash {
+            panic!("Invalid secret");
+        }
+        
+        env.storage().instance().set(&SwapDataKey::RevealedSecret(swap_id), &secret);
+    }
+
+    /// Get the secret revealed for a swap, if any
+    pub fn get_revealed_secret(env: Env, swap_id: u64) -> Option<Bytes> {
+        env.storage().instance().get(&SwapDataKey::RevealedSecret(swap_id))
+    }
+
     /// Redeem atomic swap with secret
     pub fn redeem_swap(env: Env, swap_id: u64, secret: Bytes, redeemer: Address) -> bool {
         redeemer.require_auth();
         
-        let mut swap: AtomicSwap = env.storage().instance()
-            .get(&SwapDataKey::AtomicSwap(swap_id))
+        let mut swap: AtomicSwap = Self::load_swap(env.clone(), swap_id)
             .unwrap_or_else(|| panic!("Swap not found"));
         
-        if swap.status != SwapStatus::Funded {
+        // A secret revealed in time settles the swap even if it was marked expired before expiry learned to skip it
+        let revealed = env.storage().instance().has(&SwapDataKey::RevealedSecret(swap_id));
+        if swap.status != SwapStatus::Funded && !(swap.status == SwapStatus::Expired && revealed) {
             panic!("Swap not funded");
         }
         
@@ -160,13 +991,18 @@ impl AtomicSwapContract {
             panic!("Only participant can redeem swap");
         }
         
-        if env.ledger().timestamp() > swap.timeout {
+        if !swap.fills.is_empty() {
+            panic!("Partially filled swaps are redeemed per fill");
+        }
+        
+        // A secret revealed in time keeps the redeem path open past the redeem deadline
+        if env.ledger().timestamp() > swap.redeem_deadline && !revealed {
             panic!("Swap has expired");
         }
         
         // Verify secret hash matches
-        let computed_hash = Self::compute_secret_hash(env.clone(), secret.clone());
-        if computed_hash != swap.secret_hash {
+        let computed_hash = Self::compute_secret_hash(env.clone(), secret.clone(), swap.hash_lock.clone());
+        if computed_hash != swap.hash_lock.secret_hash {
             panic!("Invalid secret");
         }
         
@@ -175,6 +1011,14 @@ impl AtomicSwapContract {
         swap.completed_at = Some(env.ledger().timestamp());
         
         env.storage().instance().set(&SwapDataKey::AtomicSwap(swap_id), &swap);
+        let earns = Self::earns_reputation(env.clone(), swap_id, &swap, swap.source_amount.clone());
+        // Each side receives what the other escrowed
+        Self::release_escrow(env.clone(), swap_id, &swap.initiator, &swap.participant);
+        Self::release_escrow(env.clone(), swap_id, &swap.participant, &swap.initiator);
+        if earns {
+            Self::adjust_reputation(env.clone(), swap.initiator.clone(), true);
+            Self::adjust_reputation(env.clone(), swap.participant.clone(), true);
+        }
         
         // Remove from active swaps
         Self::remove_from_active_swaps(env.clone(), swap_id);
@@ -182,30 +1026,53 @@ impl AtomicSwapContract {
         true
     }
 
-    /// Refund atomic swap after timeout
+    /// Refund atomic swap after its refund deadline
     pub fn refund_swap(env: Env, swap_id: u64, refunder: Address) -> bool {
         refunder.require_auth();
         
-        let mut swap: AtomicSwap = env.storage().instance()
-            .get(&SwapDataKey::AtomicSwap(swap_id))
+        let mut swap: AtomicSwap = Self::load_swap(env.clone(), swap_id)
             .unwrap_or_else(|| panic!("Swap not found"));
         
-        if swap.status != SwapStatus::Funded {
-            panic!("Swap not funded");
-        }
-        
-        if refunder != swap.initiator {
-            panic!("Only initiator can refund swap");
+        // A party that deposited while its counterparty never committed can reclaim at once
+        let uncommitted = Self::refundable_without_commit(env.clone(), swap_id, &swap, &refunder);
+        if !uncommitted {
+            if refunder != swap.initiator && refunder != swap.participant {
+                panic!("Only swap parties can refund swap");
+            }
+            
+            // Either side can reclaim its own deposit even if the other never matched it
+            if swap.status != SwapStatus::Funded
+                && !(swap.status == SwapStatus::Initiated && Self::deposit_of(&swap, &refunder) != DepositState::NotDeposited)
+            {
+                panic!("Swap not funded");
+            }
+            
+            if env.ledger().timestamp() <= swap.refund_deadline {
+                panic!("Refund deadline not reached");
+            }
+            
+            // Once the secret is out the participant is owed the funds, so only redeem may settle
+            if env.storage().instance().has(&SwapDataKey::RevealedSecret(swap_id)) {
+                panic!("Secret revealed; swap must be redeemed");
+            }
         }
         
-        if env.ledger().timestamp() <= swap.timeout {
-            panic!("Swap has not expired yet");
-        }
+        // The side that stalled the swap loses standing: an uncommitted counterparty, or whoever missed its step
+        let stalled = if uncommitted {
+            Some(if refunder == swap.initiator { swap.participant.clone() } else { swap.initiator.clone() })
+        } else {
+            Self::stalled_party(&swap)
+        };
         
         swap.status = SwapStatus::Refunded;
         swap.completed_at = Some(env.ledger().timestamp());
         
         env.storage().instance().set(&SwapDataKey::AtomicSwap(swap_id), &swap);
+        Self::refund_escrow(env.clone(), swap_id, &swap);
+        
+        if let Some(party) = stalled {
+            Self::adjust_reputation(env.clone(), party, false);
+        }
         
         // Remove from active swaps
         Self::remove_from_active_swaps(env.clone(), swap_id);
@@ -243,8 +1110,7 @@ impl AtomicSwapContract {
     pub fn accept_proposal(env: Env, proposal_id: u64, accepter: Address) -> u64 {
         accepter.require_auth();
         
-        let proposal: SwapProposal = env.storage().instance()
-            .get(&SwapDataKey::SwapProposal(proposal_id))
+        let proposal: SwapProposal = Self::load_proposal(env.clone(), proposal_id)
             .unwrap_or_else(|| panic!("Proposal not found"));
         
         if accepter != proposal.proposed_swap.participant {
@@ -252,18 +1118,26 @@ impl AtomicSwapContract {
         }
         
         // Create actual swap from proposal
+        let proposed = proposal.proposed_swap;
         let swap_id = Self::initiate_swap(
             env.clone(),
-            proposal.proposed_swap.participant,
-            proposal.proposed_swap.source_chain,
-            proposal.proposed_swap.target_chain,
-            proposal.proposed_swap.source_asset,
-            proposal.proposed_swap.target_asset,
-            proposal.proposed_swap.source_amount,
-            proposal.proposed_swap.target_amount,
-            proposal.proposed_swap.secret_hash,
-            proposal.proposed_swap.timeout,
-            proposal.proposed_swap.initiator,
+            proposed.participant,
+            SwapLeg {
+                chain_id: proposed.source_chain,
+                asset: proposed.source_asset,
+                amount: proposed.source_amount,
+            },
+            SwapLeg {
+                chain_id: proposed.target_chain,
+                asset: proposed.target_asset,
+                amount: proposed.target_amount,
+            },
+            proposed.hash_lock,
+            proposed.redeem_deadline,
+            proposed.refund_deadline,
+            proposed.deposit_order,
+            None,
+            proposed.initiator,
         );
         
         swap_id
@@ -271,15 +1145,24 @@ impl AtomicSwapContract {
 
     /// Get atomic swap details
     pub fn get_swap(env: Env, swap_id: u64) -> AtomicSwap {
-        env.storage().instance()
-            .get(&SwapDataKey::AtomicSwap(swap_id))
+        Self::load_swap(env.clone(), swap_id)
             .unwrap_or_else(|| panic!("Swap not found"))
     }
 
+    /// Get the hash a swap's secret must match
+    pub fn get_swap_secret_hash(env: Env, swap_id: u64) -> Bytes {
+        Self::get_swap(env, swap_id).hash_lock.secret_hash
+    }
+
+    /// Check a candidate secret against a swap's hash lock without redeeming
+    pub fn check_secret(env: Env, swap_id: u64, secret: Bytes) -> bool {
+        let swap = Self::get_swap(env.clone(), swap_id);
+        Self::compute_secret_hash(env, secret, swap.hash_lock.clone()) == swap.hash_lock.secret_hash
+    }
+
     /// Get swap proposal
     pub fn get_proposal(env: Env, proposal_id: u64) -> SwapProposal {
-        env.storage().instance()
-            .get(&SwapDataKey::SwapProposal(proposal_id))
+        Self::load_proposal(env.clone(), proposal_id)
             .unwrap_or_else(|| panic!("Proposal not found"))
     }
 
@@ -290,13 +1173,43 @@ impl AtomicSwapContract {
             .unwrap_or(Vec::new(&env))
     }
 
+    /// Get up to `limit` active swaps whose redeem deadline falls within `seconds` from now, soonest first
+    /// Swaps already past their redeem deadline are excluded: they can no longer be redeemed and are
+    /// left to `expire_swaps` and the refund path
+    pub fn get_swaps_expiring_within(env: Env, seconds: u64, limit: u32) -> Vec<AtomicSwap> {
+        let now = env.ledger().timestamp();
+        let horizon = now.saturating_add(seconds);
+        let mut expiring: Vec<AtomicSwap> = Vec::new(&env);
+        
+        for swap_id in Self::get_active_swaps(env.clone()).iter() {
+            if let Some(swap) = Self::load_swap(env.clone(), swap_id) {
+                if swap.redeem_deadline < now || swap.redeem_deadline > horizon {
+                    continue;
+                }
+                
+                // Insert in deadline order; active lists are small enough for an insertion sort
+                let mut index = expiring.len();
+                while index > 0 && expiring.get(index - 1).unwrap().redeem_deadline > swap.redeem_deadline {
+                    index -= 1;
+                }
+                expiring.insert(index, swap);
+            }
+        }
+        
+        while expiring.len() > limit {
+            expiring.pop_back();
+        }
+        
+        expiring
+    }
+
     /// Get swaps for user
     pub fn get_user_swaps(env: Env, user: Address) -> Vec<AtomicSwap> {
         let count: u64 = env.storage().instance().get(&SwapDataKey::SwapCount).unwrap_or(0);
         let mut user_swaps = Vec::new(&env);
         
         for i in 1..=count {
-            if let Some(swap) = env.storage().instance().get::<SwapDataKey, AtomicSwap>(&SwapDataKey::AtomicSwap(i)) {
+            if let Some(swap) = Self::load_swap(env.clone(), i) {
                 if swap.initiator == user || swap.participant == user {
                     user_swaps.push_back(swap);
                 }
@@ -306,15 +1219,63 @@ impl AtomicSwapContract {
         user_swaps
     }
 
+    /// Refund every listed swap past its refund deadline to its initiator, returning the ids refunded
+    /// Anyone may call this; missing, settled, not yet refundable or secret-revealed swaps are skipped
+    pub fn refund_expired_batch(env: Env, swap_ids: Vec<u64>) -> Vec<u64> {
+        if swap_ids.len() > MAX_REFUND_BATCH {
+            panic!("Batch too large");
+        }
+        
+        let current_time = env.ledger().timestamp();
+        let mut refunded = Vec::new(&env);
+        
+        for swap_id in swap_ids.iter() {
+            let mut swap: AtomicSwap = match Self::load_swap(env.clone(), swap_id) {
+                Some(swap) => swap,
+                None => continue,
+            };
+            
+            // Swaps already marked expired are still owed their refund
+            let holds_deposit = match swap.status {
+                SwapStatus::Funded | SwapStatus::Expired => true,
+                SwapStatus::Initiated => {
+                    swap.initiator_deposit != DepositState::NotDeposited
+                        || swap.participant_deposit != DepositState::NotDeposited
+                }
+                SwapStatus::Redeemed | SwapStatus::Refunded => false,
+            };
+            if !holds_deposit
+                || current_time <= swap.refund_deadline
+                || env.storage().instance().has(&SwapDataKey::RevealedSecret(swap_id))
+            {
+                continue;
+            }
+            
+            let stalled = Self::stalled_party(&swap);
+            swap.status = SwapStatus::Refunded;
+            swap.completed_at = Some(current_time);
+            env.storage().instance().set(&SwapDataKey::AtomicSwap(swap_id), &swap);
+            Self::refund_escrow(env.clone(), swap_id, &swap);
+            
+            if let Some(party) = stalled {
+                Self::adjust_reputation(env.clone(), party, false);
+            }
+            Self::remove_from_active_swaps(env.clone(), swap_id);
+            refunded.push_back(swap_id);
+        }
+        
+        refunded
+    }
+
     /// Check swap status
     pub fn get_swap_status(env: Env, swap_id: u64) -> SwapStatus {
-        let swap: AtomicSwap = env.storage().instance()
-            .get(&SwapDataKey::AtomicSwap(swap_id))
+        let swap: AtomicSwap = Self::load_swap(env.clone(), swap_id)
             .unwrap_or_else(|| panic!("Swap not found"));
         swap.status
     }
 
-    /// Expire swaps that have timed out
+    /// Expire funded swaps past their refund deadline
+    /// Swaps whose secret was revealed in time are left funded, as they can only be settled by redeeming
     pub fn expire_swaps(env: Env) -> Vec<u64> {
         let active_swaps = Self::get_active_swaps(env.clone());
         let mut expired_swaps = Vec::new(&env);
@@ -322,11 +1283,13 @@ impl AtomicSwapContract {
         
         for i in 0..active_swaps.len() {
             let swap_id = active_swaps.get(i).unwrap();
-            let swap: AtomicSwap = env.storage().instance()
-                .get(&SwapDataKey::AtomicSwap(*swap_id))
+            let swap: AtomicSwap = Self::load_swap(env.clone(), *swap_id)
                 .unwrap();
             
-            if current_time > swap.timeout && swap.status == SwapStatus::Funded {
+            if current_time > swap.refund_deadline
+                && swap.status == SwapStatus::Funded
+                && !env.storage().instance().has(&SwapDataKey::RevealedSecret(swap.swap_id))
+            {
                 let mut updated_swap = swap;
                 updated_swap.status = SwapStatus::Expired;
                 updated_swap.completed_at = Some(current_time);
@@ -344,15 +1307,246 @@ impl AtomicSwapContract {
         expired_swaps
     }
 
-    /// Compute secret hash (simplified)
-    fn compute_secret_hash(env: Env, secret: Bytes) -> Bytes {
-        // Simplified hash computation
-        // In practice, use proper cryptographic hash function like SHA256
-        let mut hash = Bytes::new(&env);
-        if secret.len() > 0 {
-            hash = secret.slice(0..min(secret.len(), 32));
+    /// Both supported algorithms produce 32-byte digests, so a lock of any other length can never be redeemed
+    fn check_hash_lock(hash_lock: &HashLock) {
+        if hash_lock.secret_hash.len() != 32 {
+            panic!("Secret hash must be 32 bytes");
+        }
+    }
+
+    /// Compute secret hash over `salt || secret` with the lock's algorithm
+    fn compute_secret_hash(env: Env, secret: Bytes, hash_lock: HashLock) -> Bytes {
+        let mut preimage = hash_lock.salt;
+        preimage.append(&secret);
+        
+        let hash = match hash_lock.algorithm {
+            HashAlgorithm::Sha256 => env.crypto().sha256(&preimage),
+            HashAlgorithm::Keccak256 => env.crypto().keccak256(&preimage),
+        };
+        hash.into()
+    }
+
+    /// Check a deposit block has the chain's required confirmations below the head
+    fn is_confirmed(env: Env, chain_id: u32, deposit_block: u64, head_block: u64) -> bool {
+        if head_block < deposit_block {
+            panic!("Head block is behind deposit block");
+        }
+        
+        head_block - deposit_block >= Self::get_required_confirmations(env, chain_id)
+    }
+
+    /// Fillers lock their shares against the initiator's deposit, so a swap with fills never refunds early
+    fn refundable_without_commit(env: Env, swap_id: u64, swap: &AtomicSwap, refunder: &Address) -> bool {
+        let counterparty = if *refunder == swap.initiator {
+            swap.participant.clone()
+        } else if *refunder == swap.participant {
+            swap.initiator.clone()
+        } else {
+            return false;
+        };
+        
+        swap.status == SwapStatus::Initiated
+            && swap.fills.is_empty()
+            && Self::deposit_of(swap, refunder) != DepositState::NotDeposited
+            && !Self::is_committed(env, swap_id, counterparty)
+    }
+
+    /// The party whose missed step left a swap to run past its refund deadline, if one can be singled out
+    /// Unfunded swaps stalled on the side that never deposited; funded ones on the initiator, who holds the secret
+    fn stalled_party(swap: &AtomicSwap) -> Option<Address> {
+        match swap.status {
+            SwapStatus::Funded | SwapStatus::Expired => Some(swap.initiator.clone()),
+            SwapStatus::Initiated if swap.initiator_deposit == DepositState::NotDeposited => Some(swap.initiator.clone()),
+            SwapStatus::Initiated if swap.participant_deposit == DepositState::NotDeposited && swap.fills.is_empty() => {
+                Some(swap.participant.clone())
+            }
+            _ => None,
+        }
+    }
+
+    fn deposit_of(swap: &AtomicSwap, funder: &Address) -> DepositState {
+        if *funder == swap.initiator {
+            swap.initiator_deposit.clone()
+        } else if *funder == swap.participant {
+            swap.participant_deposit.clone()
+        } else {
+            panic!("Only swap parties can fund swap");
+        }
+    }
+
+    /// Record a side's deposit and mark the swap funded once both sides are confirmed
+    fn set_deposit(swap: &mut AtomicSwap, funder: &Address, deposit: DepositState) {
+        if *funder == swap.initiator {
+            swap.initiator_deposit = deposit;
+        } else {
+            swap.participant_deposit = deposit;
+        }
+        
+        Self::update_funded(swap);
+    }
+
+    /// A one-sided swap is funded by the initiator's deposit alone
+    fn update_funded(swap: &mut AtomicSwap) {
+        let counterparty_funded = swap.deposit_order == DepositOrder::InitiatorOnly || Self::counterparty_confirmed(swap);
+        if matches!(swap.initiator_deposit, DepositState::Confirmed(_)) && counterparty_funded {
+            swap.status = SwapStatus::Funded;
+        }
+    }
+
+    /// The target leg is confirmed either by the participant or by fills covering the whole amount
+    fn counterparty_confirmed(swap: &AtomicSwap) -> bool {
+        if matches!(swap.participant_deposit, DepositState::Confirmed(_)) {
+            return true;
+        }
+        if swap.fills.is_empty() {
+            return false;
+        }
+        
+        let env = swap.target_amount.env();
+        let mut confirmed = U256::from_u32(env, 0);
+        for fill in swap.fills.iter() {
+            if !matches!(fill.deposit, DepositState::Confirmed(_)) {
+                return false;
+            }
+            confirmed = confirmed.add(&fill.amount);
+        }
+        confirmed == swap.target_amount
+    }
+
+    fn filled_amount(env: Env, swap: &AtomicSwap) -> U256 {
+        let mut filled = U256::from_u32(&env, 0);
+        for fill in swap.fills.iter() {
+            filled = filled.add(&fill.amount);
+        }
+        filled
+    }
+
+    /// Fee for a swap of `amount` at the source chain's rate
+    fn compute_fee(env: Env, amount: U256, chain_id: u32) -> U256 {
+        let rate_bps = Self::get_fee_rate(env.clone(), chain_id);
+        fees::amount_fee(&env, &amount, rate_bps)
+    }
+
+    /// Only swaps between distinct parties whose source leg is escrowed, or carries at least the minimum amount,
+    /// earn reputation, so it cannot be farmed with self-swaps or fee-free dust
+    fn earns_reputation(env: Env, swap_id: u64, swap: &AtomicSwap, amount: U256) -> bool {
+        if swap.initiator == swap.participant {
+            return false;
+        }
+        
+        env.storage().instance().has(&SwapDataKey::Escrowed(swap_id, swap.initiator.clone()))
+            || Self::get_min_reputation_amount(env).map_or(false, |min_amount| amount >= min_amount)
+    }
+
+    fn adjust_reputation(env: Env, party: Address, success: bool) {
+        let reputation = Self::get_reputation(env.clone(), party.clone());
+        let reputation = if success { reputation.saturating_add(1) } else { reputation.saturating_sub(1) };
+        env.storage().instance().set(&SwapDataKey::Reputation(party), &reputation);
+    }
+
+    /// Transfer a charged fee to the fee vault when a fee token is set
+    fn collect_fee(env: Env, payer: Address, fee: U256) {
+        if let Some(fee_token) = Self::get_fee_token(env.clone()) {
+            let amount = fee.to_u128()
+                .and_then(|amount| i128::try_from(amount).ok())
+                .unwrap_or_else(|| panic!("Fee exceeds token range"));
+            fees::collect_fee(&env, &fee_token, &payer, amount);
+        }
+    }
+
+    /// Move a deposit's full leg amount from `funder` into this contract
+    /// Panics unless the contract's balance grew by the whole amount, so fee-on-transfer tokens cannot short the escrow
+    fn escrow_deposit(env: Env, swap_id: u64, escrow_token: &Address, funder: &Address, amount: U256) {
+        let amount = amount.to_u128()
+            .and_then(|amount| i128::try_from(amount).ok())
+            .unwrap_or_else(|| panic!("Amount exceeds token range"));
+        
+        let token = token::Client::new(&env, escrow_token);
+        let escrow = env.current_contract_address();
+        let before = token.balance(&escrow);
+        token.transfer(funder, &escrow, &amount);
+        if token.balance(&escrow) - before < amount {
+            panic!("Deposit not fully escrowed");
+        }
+        
+        env.storage().instance().set(&SwapDataKey::Escrowed(swap_id, funder.clone()), &(escrow_token.clone(), amount));
+    }
+
+    /// Pay out whatever `depositor` escrowed in a swap to `recipient`
+    fn release_escrow(env: Env, swap_id: u64, depositor: &Address, recipient: &Address) {
+        let key = SwapDataKey::Escrowed(swap_id, depositor.clone());
+        // The token is kept with the amount so changing an asset's escrow token never strands a deposit
+        let (escrow_token, amount): (Address, i128) = match env.storage().instance().get(&key) {
+            Some(escrowed) => escrowed,
+            None => return,
+        };
+        env.storage().instance().remove(&key);
+        
+        token::Client::new(&env, &escrow_token).transfer(&env.current_contract_address(), recipient, &amount);
+    }
+
+    /// Return both parties' escrowed deposits to them
+    fn refund_escrow(env: Env, swap_id: u64, swap: &AtomicSwap) {
+        Self::release_escrow(env.clone(), swap_id, &swap.initiator, &swap.initiator);
+        Self::release_escrow(env, swap_id, &swap.participant, &swap.participant);
+    }
+
+    /// Load a swap, upgrading entries stored in an older layout
+    fn load_swap(env: Env, swap_id: u64) -> Option<AtomicSwap> {
+        let raw: Val = env.storage().instance().get(&SwapDataKey::AtomicSwap(swap_id))?;
+        
+        if let Ok(swap) = AtomicSwap::try_from_val(&env, &raw) {
+            return Some(swap);
+        }
+        
+        let v2 = AtomicSwapV2::try_from_val(&env, &raw).unwrap_or_else(|_| panic!("Unknown swap layout"));
+        Some(Self::upgrade_v2_swap(env, v2))
+    }
+
+    /// Load a proposal, upgrading entries stored in an older layout
+    fn load_proposal(env: Env, proposal_id: u64) -> Option<SwapProposal> {
+        let raw: Val = env.storage().instance().get(&SwapDataKey::SwapProposal(proposal_id))?;
+        
+        if let Ok(proposal) = SwapProposal::try_from_val(&env, &raw) {
+            return Some(proposal);
+        }
+        
+        let v2 = SwapProposalV2::try_from_val(&env, &raw).unwrap_or_else(|_| panic!("Unknown proposal layout"));
+        Some(SwapProposal {
+            proposal_id: v2.proposal_id,
+            proposer: v2.proposer,
+            proposed_swap: Self::upgrade_v2_swap(env, v2.proposed_swap),
+            signature: v2.signature,
+            created_at: v2.created_at,
+        })
+    }
+
+    /// A v2 swap's single timeout was both the last moment to redeem and the first to refund after,
+    /// so both deadlines take its value; v2 swaps had no ordering and no fills
+    fn upgrade_v2_swap(env: Env, v2: AtomicSwapV2) -> AtomicSwap {
+        AtomicSwap {
+            swap_id: v2.swap_id,
+            initiator: v2.initiator,
+            participant: v2.participant,
+            source_chain: v2.source_chain,
+            target_chain: v2.target_chain,
+            source_asset: v2.source_asset,
+            target_asset: v2.target_asset,
+            source_amount: v2.source_amount,
+            target_amount: v2.target_amount,
+            fee: v2.fee,
+            hash_lock: v2.hash_lock,
+            initiator_deposit: v2.initiator_deposit,
+            participant_deposit: v2.participant_deposit,
+            deposit_order: DepositOrder::Either,
+            fills: Vec::new(&env),
+            secret: v2.secret,
+            status: v2.status,
+            redeem_deadline: v2.timeout,
+            refund_deadline: v2.timeout,
+            created_at: v2.created_at,
+            completed_at: v2.completed_at,
         }
-        hash
     }
 
     /// Remove swap from active swaps list
@@ -387,7 +1581,3 @@ impl AtomicSwapContract {
         env.storage().instance().get(&SwapDataKey::ProposalCount).unwrap_or(0)
     }
 }
-
-fn min(a: u32, b: u32) -> u32 {
-    if a < b { a } else { b }
-}
@@ -0,0 +1,999 @@
+#![cfg(test)]
+use soroban_sdk::{testutils::{Address as _, Ledger as _}, token, Address, Bytes, BytesN, Env, IntoVal, Map, Symbol, U256, Val, Vec};
+use crate::atomicSwap::{AtomicSwapContract, AtomicSwapV2, AtomicSwapContractClient, DepositOrder, DepositState, HashAlgorithm, HashLock, SwapDataKey, SwapLeg, SwapStatus};
+
+fn setup(env: &Env) -> (AtomicSwapContractClient, Address) {
+    let contract_id = env.register_contract(None, AtomicSwapContract);
+    let client = AtomicSwapContractClient::new(env, &contract_id);
+    
+    let admin = Address::generate(env);
+    client.initialize(&admin);
+    
+    (client, admin)
+}
+
+fn hash_lock(env: &Env, secret: &Bytes, algorithm: HashAlgorithm, salt: &Bytes) -> HashLock {
+    let mut preimage = salt.clone();
+    preimage.append(secret);
+    
+    let secret_hash = match algorithm {
+        HashAlgorithm::Sha256 => env.crypto().sha256(&preimage),
+        HashAlgorithm::Keccak256 => env.crypto().keccak256(&preimage),
+    };
+    
+    HashLock {
+        secret_hash: secret_hash.into(),
+        algorithm,
+        salt: salt.clone(),
+    }
+}
+
+fn initiate(
+    env: &Env,
+    client: &AtomicSwapContractClient,
+    initiator: &Address,
+    participant: &Address,
+    hash_lock: &HashLock,
+) -> u64 {
+    initiate_ordered(env, client, initiator, participant, hash_lock, DepositOrder::Either)
+}
+
+fn initiate_ordered(
+    env: &Env,
+    client: &AtomicSwapContractClient,
+    initiator: &Address,
+    participant: &Address,
+    hash_lock: &HashLock,
+    deposit_order: DepositOrder,
+) -> u64 {
+    let (source, target) = legs(env);
+    let swap_id = client.initiate_swap(participant, &source, &target, hash_lock, &1000, &2000, &deposit_order, &None, initiator);
+    client.commit_swap(&swap_id, initiator);
+    client.commit_swap(&swap_id, participant);
+    swap_id
+}
+
+fn legs(env: &Env) -> (SwapLeg, SwapLeg) {
+    let source = SwapLeg {
+        chain_id: 1,
+        asset: Bytes::from_slice(env, b"XLM"),
+        amount: U256::from_u32(env, 1_000_000),
+    };
+    let target = SwapLeg {
+        chain_id: 137,
+        asset: Bytes::from_slice(env, b"MATIC"),
+        amount: U256::from_u32(env, 500),
+    };
+    (source, target)
+}
+
+fn fund_both(client: &AtomicSwapContractClient, swap_id: u64, initiator: &Address, participant: &Address) {
+    client.fund_swap(&swap_id, initiator, &100, &100);
+    client.fund_swap(&swap_id, participant, &200, &200);
+}
+
+#[test]
+fn test_fee_estimate_matches_charged_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let amount = U256::from_u32(&env, 1_000_000);
+    
+    // Default rate applies until a chain-specific rate is set
+    let default_estimate = client.get_fee_estimate(&amount, &1);
+    assert_eq!(default_estimate, U256::from_u32(&env, 3_000));
+    
+    client.set_fee_rate(&admin, &1, &50);
+    let estimate = client.get_fee_estimate(&amount, &1);
+    assert_eq!(estimate, U256::from_u32(&env, 5_000));
+    
+    let secret = Bytes::from_slice(&env, b"secret");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    let swap_id = initiate(&env, &client, &initiator, &participant, &lock);
+    
+    let swap = client.get_swap(&swap_id);
+    assert_eq!(swap.fee, estimate);
+}
+
+#[test]
+fn test_redeem_with_salted_keccak_secret() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, b"secret");
+    let salt = Bytes::from_slice(&env, b"evm-salt");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Keccak256, &salt);
+    
+    let swap_id = initiate(&env, &client, &initiator, &participant, &lock);
+    fund_both(&client, swap_id, &initiator, &participant);
+    
+    assert!(client.redeem_swap(&swap_id, &secret, &participant));
+    assert_eq!(client.get_swap_status(&swap_id), SwapStatus::Redeemed);
+}
+
+#[test]
+#[should_panic(expected = "Invalid secret")]
+fn test_redeem_rejects_hash_from_other_algorithm() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, b"secret");
+    let salt = Bytes::from_slice(&env, b"evm-salt");
+    
+    // Keccak digest locked under the SHA-256 algorithm
+    let mut lock = hash_lock(&env, &secret, HashAlgorithm::Keccak256, &salt);
+    lock.algorithm = HashAlgorithm::Sha256;
+    
+    let swap_id = initiate(&env, &client, &initiator, &participant, &lock);
+    fund_both(&client, swap_id, &initiator, &participant);
+    client.redeem_swap(&swap_id, &secret, &participant);
+}
+
+#[test]
+fn test_redeem_with_plain_sha256_preimage() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, &[7u8; 32]);
+    let (source, target) = legs(&env);
+    
+    // Locks must carry a full 32-byte digest
+    let short_lock = HashLock {
+        secret_hash: Bytes::from_slice(&env, &[1u8; 20]),
+        algorithm: HashAlgorithm::Sha256,
+        salt: Bytes::new(&env),
+    };
+    assert!(client.try_initiate_swap(&participant, &source, &target, &short_lock, &1000, &2000, &DepositOrder::Either, &None, &initiator).is_err());
+    
+    // The lock an HTLC on another chain uses: sha256(secret) with no salt
+    let lock = HashLock {
+        secret_hash: env.crypto().sha256(&secret).into(),
+        algorithm: HashAlgorithm::Sha256,
+        salt: Bytes::new(&env),
+    };
+    let swap_id = initiate(&env, &client, &initiator, &participant, &lock);
+    fund_both(&client, swap_id, &initiator, &participant);
+    assert!(client.redeem_swap(&swap_id, &secret, &participant));
+    assert_eq!(client.get_swap_status(&swap_id), SwapStatus::Redeemed);
+}
+
+#[test]
+fn test_fees_land_in_contract_fee_vault() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let token_admin = Address::generate(&env);
+    let fee_token = env.register_stellar_asset_contract(token_admin);
+    client.set_fee_token(&admin, &fee_token);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &fee_token).mint(&initiator, &10_000);
+    
+    // The contract custodies its own fees
+    let vault = client.get_fee_vault();
+    assert_eq!(vault, client.address);
+    
+    let secret = Bytes::from_slice(&env, b"secret");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    initiate(&env, &client, &initiator, &participant, &lock);
+    
+    let balances = token::Client::new(&env, &fee_token);
+    assert_eq!(balances.balance(&vault), 3_000);
+    assert_eq!(balances.balance(&initiator), 7_000);
+}
+
+#[test]
+fn test_unconfirmed_deposit_does_not_enable_redeem() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    client.set_required_confirmations(&admin, &137, &64);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, b"secret");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    let swap_id = initiate(&env, &client, &initiator, &participant, &lock);
+    
+    // Source chain has no depth requirement, target chain needs 64 blocks
+    assert!(client.fund_swap(&swap_id, &initiator, &100, &100));
+    assert!(!client.fund_swap(&swap_id, &participant, &500, &510));
+    
+    let swap = client.get_swap(&swap_id);
+    assert_eq!(swap.status, SwapStatus::Initiated);
+    assert_eq!(swap.participant_deposit, DepositState::Pending(500));
+    assert!(client.try_redeem_swap(&swap_id, &secret, &participant).is_err());
+    
+    // Still too shallow
+    assert!(!client.confirm_deposit(&swap_id, &participant, &563));
+    
+    assert!(client.confirm_deposit(&swap_id, &participant, &564));
+    assert_eq!(client.get_swap_status(&swap_id), SwapStatus::Funded);
+    assert!(client.redeem_swap(&swap_id, &secret, &participant));
+}
+
+/// A swap's field map as the v2 contract stored it
+fn v2_swap_fields(env: &Env, swap: &AtomicSwapV2) -> Map<Symbol, Val> {
+    let mut fields = Map::new(env);
+    fields.set(Symbol::new(env, "swap_id"), swap.swap_id.into_val(env));
+    fields.set(Symbol::new(env, "initiator"), swap.initiator.into_val(env));
+    fields.set(Symbol::new(env, "participant"), swap.participant.into_val(env));
+    fields.set(Symbol::new(env, "source_chain"), swap.source_chain.into_val(env));
+    fields.set(Symbol::new(env, "target_chain"), swap.target_chain.into_val(env));
+    fields.set(Symbol::new(env, "source_asset"), swap.source_asset.into_val(env));
+    fields.set(Symbol::new(env, "target_asset"), swap.target_asset.into_val(env));
+    fields.set(Symbol::new(env, "source_amount"), swap.source_amount.into_val(env));
+    fields.set(Symbol::new(env, "target_amount"), swap.target_amount.into_val(env));
+    fields.set(Symbol::new(env, "fee"), swap.fee.into_val(env));
+    fields.set(Symbol::new(env, "hash_lock"), swap.hash_lock.into_val(env));
+    fields.set(Symbol::new(env, "initiator_deposit"), swap.initiator_deposit.into_val(env));
+    fields.set(Symbol::new(env, "participant_deposit"), swap.participant_deposit.into_val(env));
+    fields.set(Symbol::new(env, "secret"), swap.secret.into_val(env));
+    fields.set(Symbol::new(env, "status"), swap.status.into_val(env));
+    fields.set(Symbol::new(env, "timeout"), swap.timeout.into_val(env));
+    fields.set(Symbol::new(env, "created_at"), swap.created_at.into_val(env));
+    fields.set(Symbol::new(env, "completed_at"), swap.completed_at.into_val(env));
+    fields
+}
+
+#[test]
+fn test_migrate_reads_v2_swap_layout() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, b"secret");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    let (source, target) = legs(&env);
+    
+    // A funded swap as the v2 contract stored it, with a single timeout
+    let legacy = AtomicSwapV2 {
+        swap_id: 1,
+        initiator: initiator.clone(),
+        participant: participant.clone(),
+        source_chain: source.chain_id,
+        target_chain: target.chain_id,
+        source_asset: source.asset,
+        target_asset: target.asset,
+        source_amount: source.amount,
+        target_amount: target.amount,
+        fee: U256::from_u32(&env, 0),
+        hash_lock: lock,
+        initiator_deposit: DepositState::Confirmed(100),
+        participant_deposit: DepositState::Confirmed(200),
+        secret: None,
+        status: SwapStatus::Funded,
+        timeout: 1_000,
+        created_at: 0,
+        completed_at: None,
+    };
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&SwapDataKey::AtomicSwap(1), &v2_swap_fields(&env, &legacy));
+        env.storage().instance().set(&SwapDataKey::SwapCount, &1u64);
+        env.storage().instance().set(&SwapDataKey::ActiveSwaps, &Vec::from_array(&env, [1u64]));
+        env.storage().instance().set(&SwapDataKey::StateVersion, &2u32);
+    });
+    
+    assert_eq!(client.migrate(&admin), 3);
+    assert_eq!(client.get_state_version(), 3);
+    
+    // The timeout becomes both deadlines, keeping the v2 redeem and refund windows
+    let swap = client.get_swap(&1);
+    assert_eq!((swap.redeem_deadline, swap.refund_deadline), (1_000, 1_000));
+    assert_eq!(swap.deposit_order, DepositOrder::Either);
+    assert!(swap.fills.is_empty());
+    
+    env.ledger().with_mut(|li| li.timestamp = 1_001);
+    assert!(client.refund_swap(&1, &initiator));
+    assert_eq!(client.get_swap_status(&1), SwapStatus::Refunded);
+}
+
+#[test]
+fn test_initiator_first_ordering() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let lock = hash_lock(&env, &Bytes::from_slice(&env, b"secret"), HashAlgorithm::Sha256, &Bytes::new(&env));
+    let swap_id = initiate_ordered(&env, &client, &initiator, &participant, &lock, DepositOrder::InitiatorFirst);
+    
+    assert!(client.try_fund_swap(&swap_id, &participant, &200, &200).is_err());
+    
+    client.fund_swap(&swap_id, &initiator, &100, &100);
+    client.fund_swap(&swap_id, &participant, &200, &200);
+    assert_eq!(client.get_swap_status(&swap_id), SwapStatus::Funded);
+}
+
+#[test]
+fn test_participant_first_ordering() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let lock = hash_lock(&env, &Bytes::from_slice(&env, b"secret"), HashAlgorithm::Sha256, &Bytes::new(&env));
+    let swap_id = initiate_ordered(&env, &client, &initiator, &participant, &lock, DepositOrder::ParticipantFirst);
+    
+    assert!(client.try_fund_swap(&swap_id, &initiator, &100, &100).is_err());
+    
+    client.fund_swap(&swap_id, &participant, &200, &200);
+    client.fund_swap(&swap_id, &initiator, &100, &100);
+    assert_eq!(client.get_swap_status(&swap_id), SwapStatus::Funded);
+}
+
+#[test]
+#[should_panic(expected = "Deposit out of order")]
+fn test_participant_first_rejects_unconfirmed_participant_deposit() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    client.set_required_confirmations(&admin, &137, &64);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let lock = hash_lock(&env, &Bytes::from_slice(&env, b"secret"), HashAlgorithm::Sha256, &Bytes::new(&env));
+    let swap_id = initiate_ordered(&env, &client, &initiator, &participant, &lock, DepositOrder::ParticipantFirst);
+    
+    // A pending participant deposit does not yet protect the initiator
+    client.fund_swap(&swap_id, &participant, &500, &510);
+    client.fund_swap(&swap_id, &initiator, &100, &100);
+}
+
+#[test]
+fn test_either_ordering() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let lock = hash_lock(&env, &Bytes::from_slice(&env, b"secret"), HashAlgorithm::Sha256, &Bytes::new(&env));
+    let swap_id = initiate(&env, &client, &initiator, &participant, &lock);
+    
+    client.fund_swap(&swap_id, &participant, &200, &200);
+    client.fund_swap(&swap_id, &initiator, &100, &100);
+    assert_eq!(client.get_swap_status(&swap_id), SwapStatus::Funded);
+}
+
+#[test]
+fn test_idempotency_key_deduplicates_initiation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, b"secret");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    let (source, target) = legs(&env);
+    let key = Some(BytesN::from_array(&env, &[5u8; 32]));
+    
+    let first = client.initiate_swap(&participant, &source, &target, &lock, &1000, &2000, &DepositOrder::Either, &key, &initiator);
+    let retry = client.initiate_swap(&participant, &source, &target, &lock, &1000, &2000, &DepositOrder::Either, &key, &initiator);
+    assert_eq!(first, retry);
+    assert_eq!(client.get_active_swaps().len(), 1);
+    
+    // A different key, or no key, creates a new swap
+    let other_key = Some(BytesN::from_array(&env, &[6u8; 32]));
+    let second = client.initiate_swap(&participant, &source, &target, &lock, &1000, &2000, &DepositOrder::Either, &other_key, &initiator);
+    assert_ne!(first, second);
+    assert_eq!(client.get_active_swaps().len(), 2);
+}
+
+#[test]
+fn test_two_participant_partial_fill_completes_swap() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, b"initiator secret");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    let swap_id = initiate(&env, &client, &initiator, &participant, &lock);
+    client.fund_swap(&swap_id, &initiator, &100, &100);
+    
+    client.set_min_reputation_amount(&admin, &U256::from_u32(&env, 400_000));
+    
+    // Every filler locks its portion under the initiator's hash lock
+    let filler_a = Address::generate(&env);
+    let filler_b = Address::generate(&env);
+    
+    // Fillers go through the commit phase like the named parties
+    assert!(client.try_fill_swap(&swap_id, &filler_a, &U256::from_u32(&env, 200), &200, &200).is_err());
+    client.commit_swap(&swap_id, &filler_a);
+    client.commit_swap(&swap_id, &filler_b);
+    
+    let fill_a = client.fill_swap(&swap_id, &filler_a, &U256::from_u32(&env, 200), &200, &200);
+    assert_eq!(client.get_swap_status(&swap_id), SwapStatus::Initiated);
+    
+    // Fills can't exceed what is left of the target amount
+    let oversized = client.try_fill_swap(&swap_id, &filler_b, &U256::from_u32(&env, 301), &200, &200);
+    assert!(oversized.is_err());
+    
+    let fill_b = client.fill_swap(&swap_id, &filler_b, &U256::from_u32(&env, 300), &200, &200);
+    assert_eq!(client.get_swap_status(&swap_id), SwapStatus::Funded);
+    
+    let swap = client.get_swap(&swap_id);
+    assert_eq!(swap.fills.get(fill_a).unwrap().source_share, U256::from_u32(&env, 400_000));
+    assert_eq!(swap.fills.get(fill_b).unwrap().source_share, U256::from_u32(&env, 600_000));
+    
+    // A filler cannot open its share with a secret of its own choosing
+    let own_secret = Bytes::from_slice(&env, b"filler secret");
+    assert!(client.try_redeem_fill(&swap_id, &fill_a, &own_secret, &filler_a).is_err());
+    assert!(client.redeem_fill(&swap_id, &fill_a, &secret, &filler_a));
+    assert_eq!(client.get_swap_status(&swap_id), SwapStatus::Funded);
+    assert_eq!(client.get_reputation(&filler_a), 1);
+    assert_eq!(client.get_reputation(&initiator), 0);
+    
+    assert!(client.redeem_fill(&swap_id, &fill_b, &secret, &filler_b));
+    assert_eq!(client.get_swap_status(&swap_id), SwapStatus::Redeemed);
+    assert!(!client.get_active_swaps().contains(&swap_id));
+    assert_eq!(client.get_reputation(&filler_b), 1);
+    assert_eq!(client.get_reputation(&initiator), 1);
+}
+
+#[test]
+fn test_derive_swap_id_is_stable_and_distinct() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let secret_hash = Bytes::from_slice(&env, &[9u8; 32]);
+    
+    let id = client.derive_swap_id(&initiator, &1, &137, &secret_hash, &1000);
+    assert_eq!(client.derive_swap_id(&initiator, &1, &137, &secret_hash, &1000), id);
+    
+    assert_ne!(client.derive_swap_id(&Address::generate(&env), &1, &137, &secret_hash, &1000), id);
+    assert_ne!(client.derive_swap_id(&initiator, &56, &137, &secret_hash, &1000), id);
+    assert_ne!(client.derive_swap_id(&initiator, &1, &56, &secret_hash, &1000), id);
+    assert_ne!(client.derive_swap_id(&initiator, &1, &137, &Bytes::from_slice(&env, &[8u8; 32]), &1000), id);
+    assert_ne!(client.derive_swap_id(&initiator, &1, &137, &secret_hash, &1001), id);
+}
+
+#[test]
+fn test_swaps_expiring_within() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, b"secret");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    let (source, target) = legs(&env);
+    
+    let mut ids = [0u64; 4];
+    for (i, redeem_deadline) in [500u64, 300, 5_000, 100].iter().enumerate() {
+        ids[i] = client.initiate_swap(&participant, &source, &target, &lock, redeem_deadline, &(redeem_deadline + 100), &DepositOrder::Either, &None, &initiator);
+    }
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    
+    // The already-expired swap and the distant one are left out, soonest first
+    let expiring = client.get_swaps_expiring_within(&400, &10);
+    assert_eq!(expiring.len(), 2);
+    assert_eq!(expiring.get(0).unwrap().swap_id, ids[1]);
+    assert_eq!(expiring.get(1).unwrap().swap_id, ids[0]);
+    
+    let soonest = client.get_swaps_expiring_within(&400, &1);
+    assert_eq!(soonest.len(), 1);
+    assert_eq!(soonest.get(0).unwrap().swap_id, ids[1]);
+}
+
+#[test]
+fn test_secret_revealed_near_expiry_blocks_refund() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, b"secret");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    let swap_id = initiate(&env, &client, &initiator, &participant, &lock);
+    fund_both(&client, swap_id, &initiator, &participant);
+    
+    // The secret surfaces just before the redeem deadline, but the redeem lands after it
+    env.ledger().with_mut(|li| li.timestamp = 999);
+    assert!(client.try_reveal_secret(&swap_id, &Bytes::from_slice(&env, b"wrong")).is_err());
+    client.reveal_secret(&swap_id, &secret);
+    assert_eq!(client.get_revealed_secret(&swap_id), Some(secret.clone()));
+    env.ledger().with_mut(|li| li.timestamp = 1_001);
+    
+    assert!(client.try_refund_swap(&swap_id, &initiator).is_err());
+    assert!(client.redeem_swap(&swap_id, &secret, &participant));
+    assert_eq!(client.get_swap_status(&swap_id), SwapStatus::Redeemed);
+}
+
+#[test]
+fn test_fast_chain_pair_caps_timelock_below_global_max() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, b"secret");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    let (source, target) = legs(&env);
+    
+    env.ledger().with_mut(|li| li.timestamp = 10_000);
+    let week = 604_800u64;
+    assert_eq!(client.get_max_timelock(&1, &137), week);
+    assert!(client.try_initiate_swap(&participant, &source, &target, &lock, &(10_000 + week), &(10_000 + week + 1), &DepositOrder::Either, &None, &initiator).is_err());
+    
+    // Both chains finalize within seconds, so an hour is plenty
+    client.set_pair_max_timelock(&admin, &137, &1, &3_600);
+    assert_eq!(client.get_max_timelock(&1, &137), 3_600);
+    assert!(client.try_initiate_swap(&participant, &source, &target, &lock, &(10_000 + 1_800), &(10_000 + 7_200), &DepositOrder::Either, &None, &initiator).is_err());
+    client.initiate_swap(&participant, &source, &target, &lock, &(10_000 + 1_800), &(10_000 + 3_600), &DepositOrder::Either, &None, &initiator);
+    
+    // Other pairs keep the global maximum
+    let mut slow_target = target.clone();
+    slow_target.chain_id = 56;
+    client.initiate_swap(&participant, &source, &slow_target, &lock, &(10_000 + 3_600), &(10_000 + week), &DepositOrder::Either, &None, &initiator);
+}
+
+#[test]
+fn test_is_chain_supported() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    assert!(!client.is_chain_supported(&137));
+    client.set_required_confirmations(&admin, &137, &12);
+    assert!(client.is_chain_supported(&137));
+    assert!(!client.is_chain_supported(&56));
+}
+
+#[test]
+fn test_deposit_requires_commit() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, b"secret");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    let (source, target) = legs(&env);
+    let swap_id = client.initiate_swap(&participant, &source, &target, &lock, &1000, &2000, &DepositOrder::Either, &None, &initiator);
+    
+    assert!(client.try_fund_swap(&swap_id, &initiator, &100, &100).is_err());
+    
+    // Outsiders commit only as fillers, so they still cannot fund either side
+    let outsider = Address::generate(&env);
+    client.commit_swap(&swap_id, &outsider);
+    assert!(client.try_fund_swap(&swap_id, &outsider, &200, &200).is_err());
+    let one_sided = client.initiate_swap(&participant, &source, &target, &lock, &1000, &2000, &DepositOrder::InitiatorOnly, &None, &initiator);
+    assert!(client.try_commit_swap(&one_sided, &outsider).is_err());
+    
+    client.commit_swap(&swap_id, &initiator);
+    assert!(client.is_committed(&swap_id, &initiator));
+    assert!(client.try_commit_swap(&swap_id, &initiator).is_err());
+    client.fund_swap(&swap_id, &initiator, &100, &100);
+    
+    assert!(client.try_fund_swap(&swap_id, &participant, &200, &200).is_err());
+    client.commit_swap(&swap_id, &participant);
+    client.fund_swap(&swap_id, &participant, &200, &200);
+    assert_eq!(client.get_swap_status(&swap_id), SwapStatus::Funded);
+}
+
+#[test]
+fn test_instant_refund_when_counterparty_never_commits() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, b"secret");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    let (source, target) = legs(&env);
+    
+    // The participant commits and deposits, the initiator never commits
+    let reneged = client.initiate_swap(&participant, &source, &target, &lock, &1000, &2000, &DepositOrder::Either, &None, &initiator);
+    client.commit_swap(&reneged, &participant);
+    client.fund_swap(&reneged, &participant, &200, &200);
+    
+    env.ledger().with_mut(|li| li.timestamp = 10);
+    assert!(client.refund_swap(&reneged, &participant));
+    assert_eq!(client.get_swap_status(&reneged), SwapStatus::Refunded);
+    
+    // Once both sides have committed the deposit stays locked until the refund deadline
+    let committed = initiate(&env, &client, &initiator, &participant, &lock);
+    client.fund_swap(&committed, &initiator, &100, &100);
+    assert!(client.try_refund_swap(&committed, &initiator).is_err());
+    env.ledger().with_mut(|li| li.timestamp = 2_001);
+    assert!(client.refund_swap(&committed, &initiator));
+    
+    // Partial fills lock against the initiator's deposit even though the named participant never committed
+    env.ledger().with_mut(|li| li.timestamp = 10);
+    let filled = client.initiate_swap(&participant, &source, &target, &lock, &1000, &2000, &DepositOrder::Either, &None, &initiator);
+    client.commit_swap(&filled, &initiator);
+    client.fund_swap(&filled, &initiator, &100, &100);
+    let filler = Address::generate(&env);
+    client.commit_swap(&filled, &filler);
+    client.fill_swap(&filled, &filler, &U256::from_u32(&env, 200), &200, &200);
+    assert!(client.try_refund_swap(&filled, &initiator).is_err());
+    assert_eq!(client.get_swap_status(&filled), SwapStatus::Initiated);
+}
+
+#[test]
+fn test_redeem_and_refund_deadlines_are_staggered() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, b"secret");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    let (source, target) = legs(&env);
+    
+    assert!(client.try_initiate_swap(&participant, &source, &target, &lock, &1000, &1000, &DepositOrder::Either, &None, &initiator).is_err());
+    assert!(client.try_initiate_swap(&participant, &source, &target, &lock, &1000, &900, &DepositOrder::Either, &None, &initiator).is_err());
+    
+    // Redeemable up to and including the redeem deadline
+    let redeemed = initiate(&env, &client, &initiator, &participant, &lock);
+    fund_both(&client, redeemed, &initiator, &participant);
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    assert!(client.try_refund_swap(&redeemed, &initiator).is_err());
+    assert!(client.redeem_swap(&redeemed, &secret, &participant));
+    
+    // Between the deadlines neither side can settle
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    let lapsed = initiate(&env, &client, &initiator, &participant, &lock);
+    fund_both(&client, lapsed, &initiator, &participant);
+    env.ledger().with_mut(|li| li.timestamp = 1_001);
+    assert!(client.try_redeem_swap(&lapsed, &secret, &participant).is_err());
+    env.ledger().with_mut(|li| li.timestamp = 2_000);
+    assert!(client.try_refund_swap(&lapsed, &initiator).is_err());
+    
+    // Refundable only once the refund deadline has passed
+    env.ledger().with_mut(|li| li.timestamp = 2_001);
+    assert!(client.try_redeem_swap(&lapsed, &secret, &participant).is_err());
+    assert!(client.refund_swap(&lapsed, &initiator));
+    assert_eq!(client.get_swap_status(&lapsed), SwapStatus::Refunded);
+}
+
+#[test]
+fn test_reputation_tier_discounts_swap_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let mut tiers = Vec::new(&env);
+    tiers.push_back((1u32, 2_000u32));
+    tiers.push_back((3u32, 5_000u32));
+    client.set_fee_discount_tiers(&admin, &tiers);
+    
+    let mut over_cap = Vec::new(&env);
+    over_cap.push_back((1u32, 5_001u32));
+    assert!(client.try_set_fee_discount_tiers(&admin, &over_cap).is_err());
+    
+    let veteran = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, b"secret");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    
+    // Unescrowed swaps earn nothing until a minimum size is set, and self-swaps never do
+    let unsized_swap = initiate(&env, &client, &veteran, &participant, &lock);
+    fund_both(&client, unsized_swap, &veteran, &participant);
+    client.redeem_swap(&unsized_swap, &secret, &participant);
+    assert_eq!(client.get_reputation(&veteran), 0);
+    
+    client.set_min_reputation_amount(&admin, &U256::from_u32(&env, 1_000_000));
+    let (source, target) = legs(&env);
+    let self_swap = client.initiate_swap(&veteran, &source, &target, &lock, &1000, &2000, &DepositOrder::InitiatorOnly, &None, &veteran);
+    client.commit_swap(&self_swap, &veteran);
+    client.fund_swap(&self_swap, &veteran, &100, &100);
+    client.redeem_swap(&self_swap, &secret, &veteran);
+    assert_eq!(client.get_reputation(&veteran), 0);
+    
+    let first = initiate(&env, &client, &veteran, &participant, &lock);
+    fund_both(&client, first, &veteran, &participant);
+    client.redeem_swap(&first, &secret, &participant);
+    assert_eq!(client.get_reputation(&veteran), 1);
+    assert_eq!(client.get_fee_discount_bps(&veteran), 2_000);
+    
+    // 0.3% of 1_000_000, less the tier's 20% for the veteran
+    let newcomer = Address::generate(&env);
+    let discounted = initiate(&env, &client, &veteran, &participant, &lock);
+    let full_price = initiate(&env, &client, &newcomer, &participant, &lock);
+    assert_eq!(client.get_swap(&full_price).fee, U256::from_u32(&env, 3_000));
+    assert_eq!(client.get_swap(&discounted).fee, U256::from_u32(&env, 2_400));
+}
+
+#[test]
+fn test_check_secret_against_swap_hash_lock() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, b"secret");
+    let salt = Bytes::from_slice(&env, b"evm-salt");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Keccak256, &salt);
+    
+    let swap_id = initiate(&env, &client, &initiator, &participant, &lock);
+    assert_eq!(client.get_swap_secret_hash(&swap_id), lock.secret_hash);
+    assert!(client.check_secret(&swap_id, &secret));
+    assert!(!client.check_secret(&swap_id, &Bytes::from_slice(&env, b"wrong")));
+    assert!(!client.check_secret(&swap_id, &salt));
+    assert!(client.try_check_secret(&(swap_id + 1), &secret).is_err());
+    
+    // Checking leaves the swap untouched
+    assert_eq!(client.get_swap_status(&swap_id), SwapStatus::Initiated);
+    assert_eq!(client.get_revealed_secret(&swap_id), None);
+}
+
+#[test]
+fn test_one_sided_swap_claimed_with_secret() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, b"proof of delivery");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    let swap_id = initiate_ordered(&env, &client, &initiator, &participant, &lock, DepositOrder::InitiatorOnly);
+    
+    // The participant has nothing to escrow, so the initiator's deposit alone funds the swap
+    assert!(client.try_fund_swap(&swap_id, &participant, &200, &200).is_err());
+    client.fund_swap(&swap_id, &initiator, &100, &100);
+    assert_eq!(client.get_swap_status(&swap_id), SwapStatus::Funded);
+    assert_eq!(client.get_swap(&swap_id).participant_deposit, DepositState::NotDeposited);
+    
+    assert!(client.try_refund_swap(&swap_id, &initiator).is_err());
+    assert!(client.redeem_swap(&swap_id, &secret, &participant));
+    assert_eq!(client.get_swap_status(&swap_id), SwapStatus::Redeemed);
+}
+
+#[test]
+fn test_one_sided_swap_refunds_initiator_after_timelock() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, b"proof of delivery");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    let swap_id = initiate_ordered(&env, &client, &initiator, &participant, &lock, DepositOrder::InitiatorOnly);
+    client.fund_swap(&swap_id, &initiator, &100, &100);
+    
+    env.ledger().with_mut(|li| li.timestamp = 2000);
+    assert!(client.try_refund_swap(&swap_id, &initiator).is_err());
+    
+    env.ledger().with_mut(|li| li.timestamp = 2001);
+    assert!(client.try_redeem_swap(&swap_id, &secret, &participant).is_err());
+    assert!(client.refund_swap(&swap_id, &initiator));
+    assert_eq!(client.get_swap_status(&swap_id), SwapStatus::Refunded);
+}
+
+#[test]
+fn test_refund_expired_batch() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, b"secret");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    
+    let funded = initiate(&env, &client, &initiator, &participant, &lock);
+    fund_both(&client, funded, &initiator, &participant);
+    let marked_expired = initiate(&env, &client, &initiator, &participant, &lock);
+    fund_both(&client, marked_expired, &initiator, &participant);
+    let redeemed = initiate(&env, &client, &initiator, &participant, &lock);
+    fund_both(&client, redeemed, &initiator, &participant);
+    client.redeem_swap(&redeemed, &secret, &participant);
+    
+    // Nothing is refundable before the refund deadline
+    let batch = Vec::from_array(&env, [funded, marked_expired, redeemed, 99]);
+    assert!(client.refund_expired_batch(&batch).is_empty());
+    
+    env.ledger().with_mut(|li| li.timestamp = 2_001);
+    assert_eq!(client.refund_expired_batch(&Vec::from_array(&env, [funded])), Vec::from_array(&env, [funded]));
+    assert_eq!(client.expire_swaps(), Vec::from_array(&env, [marked_expired]));
+    
+    // Redeemed, unknown and already refunded swaps are skipped; a second pass finds nothing left
+    assert_eq!(client.refund_expired_batch(&batch), Vec::from_array(&env, [marked_expired]));
+    assert_eq!(client.get_swap_status(&funded), SwapStatus::Refunded);
+    assert_eq!(client.get_swap_status(&marked_expired), SwapStatus::Refunded);
+    assert_eq!(client.get_swap_status(&redeemed), SwapStatus::Redeemed);
+    assert!(client.refund_expired_batch(&batch).is_empty());
+    
+    let mut oversized = Vec::new(&env);
+    for swap_id in 0..51u64 {
+        oversized.push_back(swap_id);
+    }
+    assert!(client.try_refund_expired_batch(&oversized).is_err());
+}
+
+#[test]
+fn test_escrowed_deposit_requires_full_amount() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let token_admin = Address::generate(&env);
+    let escrow_token = env.register_stellar_asset_contract(token_admin);
+    let asset = Bytes::from_slice(&env, b"XLM");
+    assert!(client.try_set_escrow_token(&Address::generate(&env), &asset, &Some(escrow_token.clone())).is_err());
+    client.set_escrow_token(&admin, &asset, &Some(escrow_token.clone()));
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let minter = token::StellarAssetClient::new(&env, &escrow_token);
+    let balances = token::Client::new(&env, &escrow_token);
+    minter.mint(&initiator, &999_999);
+    
+    let secret = Bytes::from_slice(&env, b"secret");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    let swap_id = initiate(&env, &client, &initiator, &participant, &lock);
+    
+    // One short of the source leg: nothing is recorded and nothing moves
+    assert!(client.try_fund_swap(&swap_id, &initiator, &100, &100).is_err());
+    assert_eq!(client.get_swap(&swap_id).initiator_deposit, DepositState::NotDeposited);
+    assert_eq!(balances.balance(&initiator), 999_999);
+    
+    minter.mint(&initiator, &1);
+    fund_both(&client, swap_id, &initiator, &participant);
+    assert_eq!(client.get_escrowed(&swap_id, &initiator), 1_000_000);
+    assert_eq!(balances.balance(&client.address), 1_000_000);
+    
+    // Redeeming hands the escrowed source leg to the participant
+    client.redeem_swap(&swap_id, &secret, &participant);
+    assert_eq!(balances.balance(&participant), 1_000_000);
+    assert_eq!(client.get_escrowed(&swap_id, &initiator), 0);
+    
+    // A refund returns the escrow to its depositor
+    minter.mint(&initiator, &1_000_000);
+    let refunded = initiate(&env, &client, &initiator, &participant, &lock);
+    client.fund_swap(&refunded, &initiator, &100, &100);
+    env.ledger().with_mut(|li| li.timestamp = 2_001);
+    client.refund_swap(&refunded, &initiator);
+    assert_eq!(balances.balance(&initiator), 1_000_000);
+    assert_eq!(balances.balance(&client.address), 0);
+}
+
+#[test]
+fn test_participant_reclaims_escrow_when_initiator_never_deposits() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let token_admin = Address::generate(&env);
+    let escrow_token = env.register_stellar_asset_contract(token_admin);
+    client.set_escrow_token(&admin, &Bytes::from_slice(&env, b"MATIC"), &Some(escrow_token.clone()));
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &escrow_token).mint(&participant, &1_000);
+    let balances = token::Client::new(&env, &escrow_token);
+    
+    let secret = Bytes::from_slice(&env, b"secret");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    let stalled = initiate_ordered(&env, &client, &initiator, &participant, &lock, DepositOrder::ParticipantFirst);
+    let swept = initiate_ordered(&env, &client, &initiator, &participant, &lock, DepositOrder::ParticipantFirst);
+    client.fund_swap(&stalled, &participant, &200, &200);
+    client.fund_swap(&swept, &participant, &200, &200);
+    assert_eq!(balances.balance(&participant), 0);
+    
+    // Both sides committed, so the deposit is locked until the refund deadline
+    assert!(client.try_refund_swap(&stalled, &participant).is_err());
+    env.ledger().with_mut(|li| li.timestamp = 2_001);
+    assert!(client.try_refund_swap(&stalled, &Address::generate(&env)).is_err());
+    assert!(client.refund_swap(&stalled, &participant));
+    assert_eq!(balances.balance(&participant), 500);
+    
+    // The batch refund returns a participant-only deposit too
+    assert_eq!(client.refund_expired_batch(&Vec::from_array(&env, [swept])), Vec::from_array(&env, [swept]));
+    assert_eq!(balances.balance(&participant), 1_000);
+    assert_eq!(balances.balance(&client.address), 0);
+}
+
+#[test]
+fn test_expired_funded_swap_penalizes_only_initiator() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    client.set_min_reputation_amount(&admin, &U256::from_u32(&env, 1_000_000));
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    let secret = Bytes::from_slice(&env, b"secret");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    
+    // Build up some standing on both sides first
+    for _ in 0..2 {
+        let swap_id = initiate(&env, &client, &initiator, &participant, &lock);
+        fund_both(&client, swap_id, &initiator, &participant);
+        client.redeem_swap(&swap_id, &secret, &participant);
+    }
+    assert_eq!(client.get_reputation(&initiator), 2);
+    assert_eq!(client.get_reputation(&participant), 2);
+    
+    // The participant deposited its side; the initiator, holding the secret, never redeemed
+    let refunded = initiate(&env, &client, &initiator, &participant, &lock);
+    fund_both(&client, refunded, &initiator, &participant);
+    let batched = initiate(&env, &client, &initiator, &participant, &lock);
+    fund_both(&client, batched, &initiator, &participant);
+    env.ledger().with_mut(|li| li.timestamp = 2001);
+    
+    client.refund_swap(&refunded, &initiator);
+    assert_eq!(client.get_reputation(&initiator), 1);
+    assert_eq!(client.get_reputation(&participant), 2);
+    
+    client.refund_expired_batch(&Vec::from_array(&env, [batched]));
+    assert_eq!(client.get_reputation(&initiator), 0);
+    assert_eq!(client.get_reputation(&participant), 2);
+}
+
+#[test]
+fn test_expiry_leaves_revealed_swap_redeemable() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let escrow_token = env.register_stellar_asset_contract(Address::generate(&env));
+    client.set_escrow_token(&admin, &Bytes::from_slice(&env, b"XLM"), &Some(escrow_token.clone()));
+    
+    let initiator = Address::generate(&env);
+    let participant = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &escrow_token).mint(&initiator, &2_000_000);
+    let balances = token::Client::new(&env, &escrow_token);
+    
+    let secret = Bytes::from_slice(&env, b"secret");
+    let lock = hash_lock(&env, &secret, HashAlgorithm::Sha256, &Bytes::new(&env));
+    let swap_id = initiate(&env, &client, &initiator, &participant, &lock);
+    fund_both(&client, swap_id, &initiator, &participant);
+    let stale = initiate(&env, &client, &initiator, &participant, &lock);
+    fund_both(&client, stale, &initiator, &participant);
+    
+    env.ledger().with_mut(|li| li.timestamp = 999);
+    client.reveal_secret(&swap_id, &secret);
+    client.reveal_secret(&stale, &secret);
+    env.ledger().with_mut(|li| li.timestamp = 2_001);
+    
+    // Expiry passes over both revealed swaps
+    assert!(client.expire_swaps().is_empty());
+    assert_eq!(client.get_swap_status(&swap_id), SwapStatus::Funded);
+    assert!(client.redeem_swap(&swap_id, &secret, &participant));
+    assert_eq!(balances.balance(&participant), 1_000_000);
+    
+    // One marked expired before expiry skipped revealed swaps can still be redeemed
+    let mut expired = client.get_swap(&stale);
+    expired.status = SwapStatus::Expired;
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&SwapDataKey::AtomicSwap(stale), &expired);
+    });
+    assert!(client.try_refund_swap(&stale, &initiator).is_err());
+    assert!(client.redeem_swap(&stale, &secret, &participant));
+    assert_eq!(balances.balance(&participant), 2_000_000);
+    assert_eq!(balances.balance(&client.address), 0);
+}
@@ -0,0 +1,48 @@
+//! Size guard for collections that grow inside instance storage
+//! Instance storage is a single ledger entry, so a list kept there must be checked before it is written back
+
+use soroban_sdk::{xdr::ToXdr, Env, IntoVal, Val};
+
+/// Default byte budget for a growing collection in instance storage
+pub const DEFAULT_STATE_CAPACITY: u32 = 16_384;
+
+/// Largest capacity state can be grown to, bounded by the ledger entry size limit
+pub const MAX_STATE_CAPACITY: u32 = 65_536;
+
+/// Default cap on items a contract keeps pending at once
+pub const DEFAULT_MAX_PENDING: u32 = 4_096;
+
+/// Ledgers of rent prepaid when state is grown (about 30 days)
+pub const RENT_EXTENSION_LEDGERS: u32 = 518_400;
+
+/// Serialized size of `value` as it would be written to storage
+pub fn serialized_size<T: IntoVal<Env, Val> + Clone>(env: &Env, value: &T) -> u32 {
+    value.clone().to_xdr(env).len()
+}
+
+/// Reject a write that would grow `value` past `capacity` bytes
+pub fn ensure_fits<T: IntoVal<Env, Val> + Clone>(env: &Env, value: &T, capacity: u32) {
+    if serialized_size(env, value) > capacity {
+        panic!("State capacity exceeded: reallocate state before writing");
+    }
+}
+
+/// Reject a new pending item while `pending` items already fill the `max_pending` cap
+pub fn ensure_below_max_pending(pending: u64, max_pending: u32) {
+    if pending >= max_pending as u64 {
+        panic!("Too many pending items: wait for some to drain");
+    }
+}
+
+/// Validate a capacity increase and top up instance rent for the larger entry
+pub fn realloc(env: &Env, current: u32, new_size: u32) -> u32 {
+    if new_size <= current {
+        panic!("New size must exceed current capacity");
+    }
+    if new_size > MAX_STATE_CAPACITY {
+        panic!("New size exceeds maximum state capacity");
+    }
+    
+    env.storage().instance().extend_ttl(RENT_EXTENSION_LEDGERS, RENT_EXTENSION_LEDGERS);
+    new_size
+}
@@ -1,5 +1,12 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Bytes, Env, String, Vec, Map, U256};
+use soroban_sdk::{contract, contractimpl, contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, IntoVal, InvokeError, String, Symbol, Vec, Map, U256};
+
+/// Layout version of this contract's stored state
+const STATE_VERSION: u32 = 3;
+/// Most proofs a single batch verification may cover
+const MAX_BATCH_SIZE: u32 = 20;
+/// Deepest merkle proof accepted, enough for Ethereum's 20+ level tries with headroom
+const MAX_MERKLE_DEPTH: u32 = 64;
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -24,13 +31,82 @@ pub struct VerificationRule {
     pub active: bool,
 }
 
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofCertificate {
+    pub result: ChainVerificationResult,
+    pub result_digest: BytesN<32>,
+    pub signature: BytesN<64>,
+    pub authority_key: BytesN<32>,
+    pub rules_digest: BytesN<32>,
+}
+
+/// Merkle inclusion proof for a leaf under a chain's state root
+/// `directions` packs one bit per level, least significant bit first: bit `i` (byte `i / 8`,
+/// bit `i % 8`) pairs with `siblings[i]` and is set when that sibling sits on the right
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChainProof {
+    pub chain_id: u32,
+    pub leaf: BytesN<32>,
+    pub root: BytesN<32>,
+    pub siblings: Vec<BytesN<32>>,
+    pub directions: Bytes,
+}
+
+/// Where a proof stands on a chain
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VerificationStatus {
+    Unknown,
+    Pending, // verified, awaiting the authority's attestation
+    Verified,
+    Failed,
+}
+
+/// One authority override of a proof's status, kept in an append-only log
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StatusChange {
+    pub chain_id: u32,
+    pub changed_by: Address,
+    pub changed_at: u64,
+    pub old_status: VerificationStatus,
+    pub new_status: VerificationStatus,
+    pub reason: String,
+}
+
+/// Per-entry result of a batch verification
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BatchOutcome {
+    Verified,
+    Failed,
+    Skipped,
+}
+
 #[contracttype]
 pub enum VerifierDataKey {
     VerificationResult(u64, u32), // proof_id, chain_id
+    ResultSignature(u64, u32), // proof_id, chain_id
+    ResultSigner(u64, u32), // proof_id, chain_id -> authority key the result's signature was made with
     VerificationRule(u32),
     RuleCount,
     TrustedVerifier(Address),
+    AuthorityKey,
+    StateVersion,
+    VerificationStats(u32), // chain_id -> (total, successful, failed)
+    VerifiedProof(u64), // proof ids that have verified on any chain
+    CrossChainProof(u64), // proof ids taken by cross-chain copies
+    LastAttempt(u64, u32), // proof_id, chain_id -> timestamp of the last verification attempt
+    VerifyCooldown,
+    ChainSupported(u32), // chains with at least one active verification rule
     Admin,
+    StatusHistory(u64), // proof_id -> Vec<StatusChange>, oldest first
+    Callback(u64, u32), // proof_id, chain_id -> contract notified of each verification result
+    AwaitingAttestation, // (proof_id, chain_id) of verified results without an authority signature
+    KeylessVerifiedCount, // length of the log of results verified while no authority key was set
+    KeylessVerified(u32), // log position from 1 -> (proof_id, chain_id)
 }
 
 #[contract]
@@ -46,6 +122,46 @@ impl ChainVerifier {
         
         env.storage().instance().set(&VerifierDataKey::Admin, &admin);
         env.storage().instance().set(&VerifierDataKey::RuleCount, &0u32);
+        env.storage().instance().set(&VerifierDataKey::StateVersion, &STATE_VERSION);
+    }
+
+    /// Get the layout version of stored state (state predating versioning is v1)
+    pub fn get_state_version(env: Env) -> u32 {
+        env.storage().instance().get(&VerifierDataKey::StateVersion).unwrap_or(1)
+    }
+
+    /// Upgrade state written by an older contract version to the current layout
+    pub fn migrate(env: Env, admin: Address) -> u32 {
+        let stored_admin: Address = env.storage().instance()
+            .get(&VerifierDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let version = Self::get_state_version(env.clone());
+        if version > STATE_VERSION {
+            panic!("Stored state is newer than this contract");
+        }
+        
+        // v1 -> v2: the version is now tracked explicitly, existing entries are kept as-is
+        // v2 -> v3: chains with active rules are flagged so support checks skip the rule scan
+        if version < 3 {
+            let count: u32 = env.storage().instance().get(&VerifierDataKey::RuleCount).unwrap_or(0);
+            for i in 1..=count {
+                if let Some(rule) = Self::get_verification_rule(env.clone(), i) {
+                    if rule.active {
+                        env.storage().instance().set(&VerifierDataKey::ChainSupported(rule.chain_id), &true);
+                    }
+                }
+            }
+        }
+        env.storage().instance().set(&VerifierDataKey::StateVersion, &STATE_VERSION);
+        
+        STATE_VERSION
     }
 
     /// Add trusted verifier
@@ -87,6 +203,14 @@ impl ChainVerifier {
         
         env.storage().instance().set(&VerifierDataKey::VerificationRule(rule_id), &new_rule);
         env.storage().instance().set(&VerifierDataKey::RuleCount, &rule_id);
+        if new_rule.active {
+            env.storage().instance().set(&VerifierDataKey::ChainSupported(new_rule.chain_id), &true);
+        }
+    }
+
+    /// Check whether a chain has any active verification rule
+    pub fn is_chain_supported(env: Env, chain_id: u32) -> bool {
+        env.storage().instance().has(&VerifierDataKey::ChainSupported(chain_id))
     }
 
     /// Get verification rule
@@ -132,31 +256,467 @@ impl ChainVerifier {
             panic!("No verification rules for this chain");
         }
         
+        if Self::in_cooldown(env.clone(), proof_id, chain_id) {
+            panic!("Verification cooldown active");
+        }
+        
+        // A cross-chain copy's id may not be verified over as a proof of its own
+        if env.storage().instance().has(&VerifierDataKey::CrossChainProof(proof_id)) {
+            panic!("Proof id already in use");
+        }
+        
+        Self::record_verification(env, proof_id, chain_id, proof_data, verifier, verification_hash)
+    }
+
+    /// Dry-run `verify_proof_on_chain`: return the result it would record without storing anything
+    /// Lets a submitter catch a malformed proof before paying for the real verification
+    pub fn simulate_verify_proof_on_chain(
+        env: Env,
+        proof_id: u64,
+        chain_id: u32,
+        proof_data: Bytes,
+        verifier: Address,
+        verification_hash: Bytes,
+    ) -> ChainVerificationResult {
+        if !Self::is_trusted_verifier(env.clone(), verifier.clone()) {
+            panic!("Not a trusted verifier");
+        }
+        
+        if Self::get_chain_rules(env.clone(), chain_id).is_empty() {
+            panic!("No verification rules for this chain");
+        }
+        
+        if Self::in_cooldown(env.clone(), proof_id, chain_id) {
+            panic!("Verification cooldown active");
+        }
+        
+        if env.storage().instance().has(&VerifierDataKey::CrossChainProof(proof_id)) {
+            panic!("Proof id already in use");
+        }
+        
+        Self::build_result(env, proof_id, chain_id, proof_data, verifier, verification_hash)
+    }
+
+    /// Run verification and build its result
+    fn build_result(
+        env: Env,
+        proof_id: u64,
+        chain_id: u32,
+        proof_data: Bytes,
+        verifier: Address,
+        verification_hash: Bytes,
+    ) -> ChainVerificationResult {
         // Simulate verification process
         let verified = Self::simulate_verification(env.clone(), proof_data.clone(), chain_id);
         
-        // Record verification result
-        let result = ChainVerificationResult {
+        ChainVerificationResult {
             chain_id,
             proof_id,
             verified,
-            verifier: verifier.clone(),
+            verifier,
             timestamp: env.ledger().timestamp(),
             gas_used: Self::estimate_gas_usage(env.clone(), proof_data.len()),
-            verification_hash: verification_hash.clone(),
-        };
+            verification_hash,
+        }
+    }
+
+    /// Run verification and record its result and stats
+    fn record_verification(
+        env: Env,
+        proof_id: u64,
+        chain_id: u32,
+        proof_data: Bytes,
+        verifier: Address,
+        verification_hash: Bytes,
+    ) -> bool {
+        let result = Self::build_result(env.clone(), proof_id, chain_id, proof_data, verifier, verification_hash);
+        let verified = result.verified;
         
+        // Record verification result
         env.storage().instance().set(&VerifierDataKey::VerificationResult(proof_id, chain_id), &result);
+        env.storage().instance().set(&VerifierDataKey::LastAttempt(proof_id, chain_id), &env.ledger().timestamp());
+        if verified {
+            env.storage().instance().set(&VerifierDataKey::VerifiedProof(proof_id), &true);
+        }
+        
+        // Any earlier attestation covered the previous result
+        env.storage().instance().remove(&VerifierDataKey::ResultSignature(proof_id, chain_id));
+        env.storage().instance().remove(&VerifierDataKey::ResultSigner(proof_id, chain_id));
+        Self::track_attestation(env.clone(), proof_id, chain_id);
+        
+        let (total, successful, failed) = Self::get_verification_stats(env.clone(), chain_id);
+        let stats = if verified {
+            (total + 1, successful + 1, failed)
+        } else {
+            (total + 1, successful, failed + 1)
+        };
+        env.storage().instance().set(&VerifierDataKey::VerificationStats(chain_id), &stats);
+        
+        // Called once the result is stored so the callback can read it back
+        Self::notify_callback(env, &result);
         
         verified
     }
 
+    /// Register a contract to be called with each verification result for a proof on a chain
+    /// The callback receives `on_proof_verified(proof_id, chain_id, verified)`; `None` removes it
+    pub fn set_verification_callback(env: Env, verifier: Address, proof_id: u64, chain_id: u32, callback: Option<Address>) {
+        if !Self::is_trusted_verifier(env.clone(), verifier.clone()) {
+            panic!("Not a trusted verifier");
+        }
+        
+        verifier.require_auth();
+        
+        let key = VerifierDataKey::Callback(proof_id, chain_id);
+        match callback {
+            Some(callback) => env.storage().instance().set(&key, &callback),
+            None => env.storage().instance().remove(&key),
+        }
+    }
+
+    /// Get the contract notified of verification results for a proof on a chain
+    pub fn get_verification_callback(env: Env, proof_id: u64, chain_id: u32) -> Option<Address> {
+        env.storage().instance().get(&VerifierDataKey::Callback(proof_id, chain_id))
+    }
+
+    /// Call the registered callback with a result; a failing callback is logged, never reverts verification
+    fn notify_callback(env: Env, result: &ChainVerificationResult) {
+        let callback = match Self::get_verification_callback(env.clone(), result.proof_id, result.chain_id) {
+            Some(callback) => callback,
+            None => return,
+        };
+        
+        let args = (result.proof_id, result.chain_id, result.verified).into_val(&env);
+        let outcome = env.try_invoke_contract::<(), InvokeError>(&callback, &Symbol::new(&env, "on_proof_verified"), args);
+        if !matches!(outcome, Ok(Ok(()))) {
+            env.events().publish(
+                (Symbol::new(&env, "callback_failed"), result.proof_id, result.chain_id),
+                callback
+            );
+        }
+    }
+
+    /// Set the seconds that must pass between verification attempts on the same proof and chain
+    pub fn set_verify_cooldown(env: Env, admin: Address, seconds: u64) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&VerifierDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&VerifierDataKey::VerifyCooldown, &seconds);
+    }
+
+    /// Get the cooldown between verification attempts (0 means none)
+    pub fn get_verify_cooldown(env: Env) -> u64 {
+        env.storage().instance().get(&VerifierDataKey::VerifyCooldown).unwrap_or(0)
+    }
+
+    /// Get when verification of a proof on a chain was last attempted
+    pub fn get_last_attempt(env: Env, proof_id: u64, chain_id: u32) -> Option<u64> {
+        env.storage().instance().get(&VerifierDataKey::LastAttempt(proof_id, chain_id))
+    }
+
+    /// Whether a retry on this proof and chain would come before the cooldown has passed
+    fn in_cooldown(env: Env, proof_id: u64, chain_id: u32) -> bool {
+        match Self::get_last_attempt(env.clone(), proof_id, chain_id) {
+            Some(last_attempt_at) => {
+                env.ledger().timestamp() < last_attempt_at.saturating_add(Self::get_verify_cooldown(env.clone()))
+            }
+            None => false,
+        }
+    }
+
+    /// Carry a proof verified on `source_chain` over to `target_chain` under `cross_chain_proof_id`
+    /// The new id must not already be in use; `derive_cross_chain_id` gives a collision-free choice
+    pub fn verify_cross_chain_proof(
+        env: Env,
+        original_proof_id: u64,
+        source_chain: u32,
+        target_chain: u32,
+        cross_chain_proof_id: u64,
+        verifier: Address,
+    ) -> bool {
+        if !Self::is_trusted_verifier(env.clone(), verifier.clone()) {
+            panic!("Not a trusted verifier");
+        }
+        
+        verifier.require_auth();
+        
+        if Self::get_chain_rules(env.clone(), target_chain).is_empty() {
+            panic!("No verification rules for this chain");
+        }
+        
+        let original = Self::get_verification_result(env.clone(), original_proof_id, source_chain)
+            .filter(|result| result.verified)
+            .unwrap_or_else(|| panic!("Original proof not verified"));
+        
+        // Reusing an id would overwrite another proof's record
+        if env.storage().instance().has(&VerifierDataKey::VerifiedProof(cross_chain_proof_id))
+            || env.storage().instance().has(&VerifierDataKey::VerificationResult(cross_chain_proof_id, target_chain))
+        {
+            panic!("Proof id already in use");
+        }
+        
+        let result = ChainVerificationResult {
+            chain_id: target_chain,
+            proof_id: cross_chain_proof_id,
+            verified: true,
+            verifier,
+            timestamp: env.ledger().timestamp(),
+            gas_used: Self::estimate_gas_usage(env.clone(), original.verification_hash.len()),
+            verification_hash: original.verification_hash,
+        };
+        
+        env.storage().instance().set(&VerifierDataKey::VerificationResult(cross_chain_proof_id, target_chain), &result);
+        env.storage().instance().set(&VerifierDataKey::VerifiedProof(cross_chain_proof_id), &true);
+        env.storage().instance().set(&VerifierDataKey::CrossChainProof(cross_chain_proof_id), &true);
+        Self::track_attestation(env.clone(), cross_chain_proof_id, target_chain);
+        
+        true
+    }
+
+    /// Deterministic id for the cross-chain copy of a proof
+    /// The top bit is always set so derived ids never meet sequentially issued ones
+    pub fn derive_cross_chain_id(env: Env, original_proof_id: u64, target_chain: u32) -> u64 {
+        let preimage = (String::from_str(&env, "cross_chain"), original_proof_id, target_chain).to_xdr(&env);
+        let digest = env.crypto().sha256(&preimage).to_array();
+        
+        let mut head = [0u8; 8];
+        head.copy_from_slice(&digest[0..8]);
+        u64::from_be_bytes(head) | (1 << 63)
+    }
+
+    /// Set the ed25519 authority key that attests verification results
+    /// Setting the first key indexes the results verified without one, which now await attestation
+    pub fn set_authority_key(env: Env, admin: Address, public_key: BytesN<32>) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&VerifierDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        let first_key = Self::get_authority_key(env.clone()).is_none();
+        env.storage().instance().set(&VerifierDataKey::AuthorityKey, &public_key);
+        
+        if first_key {
+            let count: u32 = env.storage().instance().get(&VerifierDataKey::KeylessVerifiedCount).unwrap_or(0);
+            for position in 1..=count {
+                let key = VerifierDataKey::KeylessVerified(position);
+                if let Some((proof_id, chain_id)) = env.storage().instance().get::<VerifierDataKey, (u64, u32)>(&key) {
+                    env.storage().instance().remove(&key);
+                    Self::track_attestation(env.clone(), proof_id, chain_id);
+                }
+            }
+            env.storage().instance().remove(&VerifierDataKey::KeylessVerifiedCount);
+        }
+    }
+
+    /// Get the ed25519 authority key that attests verification results
+    pub fn get_authority_key(env: Env) -> Option<BytesN<32>> {
+        env.storage().instance().get(&VerifierDataKey::AuthorityKey)
+    }
+
+    /// Attach the authority's signature over a verified result's digest
+    pub fn attest_verification_result(env: Env, proof_id: u64, chain_id: u32, signature: BytesN<64>) {
+        let authority_key = Self::get_authority_key(env.clone())
+            .unwrap_or_else(|| panic!("Authority key not set"));
+        
+        let result = Self::get_verification_result(env.clone(), proof_id, chain_id)
+            .unwrap_or_else(|| panic!("Verification result not found"));
+        
+        if !result.verified {
+            panic!("Proof not verified");
+        }
+        
+        // Panics if the signature does not match the authority key
+        let digest = Self::result_digest(env.clone(), result);
+        env.crypto().ed25519_verify(&authority_key, &digest.into(), &signature);
+        
+        env.storage().instance().set(&VerifierDataKey::ResultSignature(proof_id, chain_id), &signature);
+        env.storage().instance().set(&VerifierDataKey::ResultSigner(proof_id, chain_id), &authority_key);
+        Self::track_attestation(env, proof_id, chain_id);
+    }
+
+    /// Get the oldest result still awaiting the authority's attestation as `(proof_id, chain_id, timestamp)`
+    /// Always `None` while no authority key is set, since results then need no attestation
+    pub fn get_oldest_pending(env: Env) -> Option<(u64, u32, u64)> {
+        if Self::get_authority_key(env.clone()).is_none() {
+            return None;
+        }
+        
+        let mut oldest: Option<(u64, u32, u64)> = None;
+        for (proof_id, chain_id) in Self::get_awaiting_attestation(env.clone()).iter() {
+            if let Some(result) = Self::get_verification_result(env.clone(), proof_id, chain_id) {
+                if oldest.map_or(true, |(_, _, timestamp)| result.timestamp < timestamp) {
+                    oldest = Some((proof_id, chain_id, result.timestamp));
+                }
+            }
+        }
+        
+        oldest
+    }
+
+    fn get_awaiting_attestation(env: Env) -> Vec<(u64, u32)> {
+        env.storage().instance()
+            .get(&VerifierDataKey::AwaitingAttestation)
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Keep a result's entry in the awaiting-attestation index in step with its stored state
+    /// Without an authority key nothing awaits attestation yet; verified results are only logged,
+    /// and indexed once the first key is set
+    fn track_attestation(env: Env, proof_id: u64, chain_id: u32) {
+        let awaiting = Self::get_verification_result(env.clone(), proof_id, chain_id)
+            .map_or(false, |result| result.verified)
+            && !env.storage().instance().has(&VerifierDataKey::ResultSignature(proof_id, chain_id));
+        
+        if Self::get_authority_key(env.clone()).is_none() {
+            if awaiting {
+                let position: u32 = env.storage().instance().get(&VerifierDataKey::KeylessVerifiedCount).unwrap_or(0) + 1;
+                env.storage().instance().set(&VerifierDataKey::KeylessVerified(position), &(proof_id, chain_id));
+                env.storage().instance().set(&VerifierDataKey::KeylessVerifiedCount, &position);
+            }
+            return;
+        }
+        
+        let mut index = Self::get_awaiting_attestation(env.clone());
+        match (index.first_index_of((proof_id, chain_id)), awaiting) {
+            (None, true) => index.push_back((proof_id, chain_id)),
+            (Some(position), false) => { index.remove(position); }
+            _ => return,
+        }
+        env.storage().instance().set(&VerifierDataKey::AwaitingAttestation, &index);
+    }
+
+    /// Export an XDR-encoded certificate for a verified and attested proof
+    /// The certificate carries the key the result was signed with, which outlives any later key rotation
+    pub fn get_proof_certificate(env: Env, proof_id: u64, chain_id: u32) -> Bytes {
+        let result = Self::get_verification_result(env.clone(), proof_id, chain_id)
+            .unwrap_or_else(|| panic!("Verification result not found"));
+        
+        let signature: BytesN<64> = env.storage().instance()
+            .get(&VerifierDataKey::ResultSignature(proof_id, chain_id))
+            .unwrap_or_else(|| panic!("Verification result not attested"));
+        
+        let certificate = ProofCertificate {
+            result_digest: Self::result_digest(env.clone(), result.clone()),
+            result,
+            signature,
+            authority_key: Self::get_result_signer(env.clone(), proof_id, chain_id),
+            rules_digest: env.crypto().sha256(&Self::get_chain_rules(env.clone(), chain_id).to_xdr(&env)),
+        };
+        
+        certificate.to_xdr(&env)
+    }
+
+    /// Key a result's attestation was signed with; attestations stored before signers were recorded
+    /// fall back to the current key
+    fn get_result_signer(env: Env, proof_id: u64, chain_id: u32) -> BytesN<32> {
+        env.storage().instance()
+            .get(&VerifierDataKey::ResultSigner(proof_id, chain_id))
+            .or_else(|| Self::get_authority_key(env.clone()))
+            .unwrap_or_else(|| panic!("Authority key not set"))
+    }
+
+    /// Get a proof's status on a chain from its single result entry
+    /// While an authority key is set, a verified result stays pending until it is attested
+    pub fn get_verification_status(env: Env, proof_id: u64, chain_id: u32) -> VerificationStatus {
+        let result = match Self::get_verification_result(env.clone(), proof_id, chain_id) {
+            Some(result) => result,
+            None => return VerificationStatus::Unknown,
+        };
+        
+        if !result.verified {
+            return VerificationStatus::Failed;
+        }
+        
+        let attested = env.storage().instance().has(&VerifierDataKey::ResultSignature(proof_id, chain_id));
+        if Self::get_authority_key(env).is_some() && !attested {
+            VerificationStatus::Pending
+        } else {
+            VerificationStatus::Verified
+        }
+    }
+
+    /// Override a recorded result's outcome; every override is appended to the proof's status history
+    /// Any attestation covered the old outcome, so it is dropped
+    pub fn update_verification_status(
+        env: Env,
+        admin: Address,
+        proof_id: u64,
+        chain_id: u32,
+        verified: bool,
+        reason: String,
+    ) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&VerifierDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        if reason.len() == 0 {
+            panic!("Reason required");
+        }
+        
+        let mut result = Self::get_verification_result(env.clone(), proof_id, chain_id)
+            .unwrap_or_else(|| panic!("Verification result not found"));
+        let old_status = Self::get_verification_status(env.clone(), proof_id, chain_id);
+        
+        result.verified = verified;
+        env.storage().instance().set(&VerifierDataKey::VerificationResult(proof_id, chain_id), &result);
+        env.storage().instance().remove(&VerifierDataKey::ResultSignature(proof_id, chain_id));
+        env.storage().instance().remove(&VerifierDataKey::ResultSigner(proof_id, chain_id));
+        if verified {
+            env.storage().instance().set(&VerifierDataKey::VerifiedProof(proof_id), &true);
+        }
+        Self::track_attestation(env.clone(), proof_id, chain_id);
+        
+        let change = StatusChange {
+            chain_id,
+            changed_by: admin,
+            changed_at: env.ledger().timestamp(),
+            old_status,
+            new_status: Self::get_verification_status(env.clone(), proof_id, chain_id),
+            reason,
+        };
+        
+        let mut history = Self::get_status_history(env.clone(), proof_id);
+        history.push_back(change.clone());
+        env.storage().instance().set(&VerifierDataKey::StatusHistory(proof_id), &history);
+        
+        env.events().publish(
+            (Symbol::new(&env, "status_changed"), proof_id, chain_id),
+            change
+        );
+    }
+
+    /// Get every status override of a proof across chains, oldest first
+    pub fn get_status_history(env: Env, proof_id: u64) -> Vec<StatusChange> {
+        env.storage().instance()
+            .get(&VerifierDataKey::StatusHistory(proof_id))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
     /// Get verification result
     pub fn get_verification_result(env: Env, proof_id: u64, chain_id: u32) -> Option<ChainVerificationResult> {
         env.storage().instance().get(&VerifierDataKey::VerificationResult(proof_id, chain_id))
     }
 
     /// Batch verify proofs across multiple chains
+    /// Entries for chains without verification rules are skipped rather than failing the batch;
+    /// a per-entry summary is published as a `batch_verified` event
     pub fn batch_verify_proofs(
         env: Env,
         proofs: Vec<(u64, u32, Bytes)>, // (proof_id, chain_id, proof_data)
@@ -168,27 +728,68 @@ impl ChainVerifier {
             panic!("Not a trusted verifier");
         }
         
+        if proofs.len() > MAX_BATCH_SIZE {
+            panic!("Batch too large");
+        }
+        
         let mut results = Vec::new(&env);
+        let mut summary = Vec::new(&env);
         
-        for i in 0..proofs.len() {
-            let (proof_id, chain_id, proof_data) = proofs.get(i).unwrap();
-            let verification_hash = Self::generate_verification_hash(env.clone(), proof_data.clone());
-            
-            let verified = Self::verify_proof_on_chain(
-                env.clone(),
-                *proof_id,
-                *chain_id,
-                proof_data.clone(),
-                verifier.clone(),
-                verification_hash,
-            );
+        for (proof_id, chain_id, proof_data) in proofs.iter() {
+            let outcome = if Self::get_chain_rules(env.clone(), chain_id).is_empty()
+                || Self::in_cooldown(env.clone(), proof_id, chain_id)
+                || env.storage().instance().has(&VerifierDataKey::CrossChainProof(proof_id))
+            {
+                BatchOutcome::Skipped
+            } else {
+                let verification_hash = Self::generate_verification_hash(env.clone(), proof_data.clone());
+                if Self::record_verification(env.clone(), proof_id, chain_id, proof_data, verifier.clone(), verification_hash) {
+                    BatchOutcome::Verified
+                } else {
+                    BatchOutcome::Failed
+                }
+            };
             
-            results.push_back(verified);
+            results.push_back(outcome == BatchOutcome::Verified);
+            summary.push_back((proof_id, chain_id, outcome));
         }
         
+        env.events().publish(
+            (Symbol::new(&env, "batch_verified"), verifier),
+            summary
+        );
+        
         results
     }
 
+    /// Fold a merkle proof from its leaf up and check it reaches the claimed root
+    pub fn verify_merkle_proof(env: Env, proof: ChainProof) -> bool {
+        let depth = proof.siblings.len();
+        if depth > MAX_MERKLE_DEPTH {
+            panic!("Merkle proof too deep");
+        }
+        
+        if proof.directions.len() != (depth + 7) / 8 {
+            panic!("Direction bitmap does not match proof depth");
+        }
+        
+        let mut node = proof.leaf;
+        for level in 0..depth {
+            let sibling = proof.siblings.get(level).unwrap();
+            let mut preimage = Bytes::new(&env);
+            if direction_bit(&proof.directions, level) {
+                preimage.append(&node.into());
+                preimage.append(&sibling.into());
+            } else {
+                preimage.append(&sibling.into());
+                preimage.append(&node.into());
+            }
+            node = env.crypto().sha256(&preimage);
+        }
+        
+        node == proof.root
+    }
+
     /// Check if proof is verified on all required chains
     pub fn is_fully_verified(env: Env, proof_id: u64, required_chains: Vec<u32>) -> bool {
         for i in 0..required_chains.len() {
@@ -207,14 +808,9 @@ impl ChainVerifier {
     /// Get verification statistics
     pub fn get_verification_stats(env: Env, chain_id: u32) -> (u64, u64, u64) {
         // Returns (total_verifications, successful_verifications, failed_verifications)
-        let mut total = 0u64;
-        let mut successful = 0u64;
-        let mut failed = 0u64;
-        
-        // This is a simplified implementation
-        // In practice, you'd iterate through all verification results
-        
-        (total, successful, failed)
+        env.storage().instance()
+            .get(&VerifierDataKey::VerificationStats(chain_id))
+            .unwrap_or((0, 0, 0))
     }
 
     /// Simulate verification process (placeholder)
@@ -241,6 +837,11 @@ impl ChainVerifier {
         hash
     }
 
+    /// Digest of a verification result as signed by the authority
+    fn result_digest(env: Env, result: ChainVerificationResult) -> BytesN<32> {
+        env.crypto().sha256(&result.to_xdr(&env))
+    }
+
     /// Get admin address
     pub fn get_admin(env: Env) -> Address {
         env.storage().instance().get(&VerifierDataKey::Admin).unwrap()
@@ -255,3 +856,9 @@ impl ChainVerifier {
 fn min(a: u32, b: u32) -> u32 {
     if a < b { a } else { b }
 }
+
+/// Whether the sibling at `level` sits on the right, read from a packed direction bitmap
+fn direction_bit(directions: &Bytes, level: u32) -> bool {
+    let byte = directions.get(level / 8).unwrap();
+    (byte >> (level % 8)) & 1 == 1
+}
@@ -0,0 +1,479 @@
+#![cfg(test)]
+use ed25519_dalek::{Signer, SigningKey, Verifier};
+use soroban_sdk::{contract, contractimpl, testutils::{Address as _, Events as _, Ledger as _}, xdr::{FromXdr, ToXdr}, Address, Bytes, BytesN, Env, IntoVal, String, Symbol, TryFromVal, Vec};
+use crate::chainVerifier::{BatchOutcome, ChainProof, ChainVerifier, ChainVerifierClient, ProofCertificate, StatusChange, VerificationRule, VerificationStatus, VerifierDataKey};
+
+fn setup(env: &Env) -> (ChainVerifierClient, Address, Address) {
+    let contract_id = env.register_contract(None, ChainVerifier);
+    let client = ChainVerifierClient::new(env, &contract_id);
+    
+    let admin = Address::generate(env);
+    client.initialize(&admin);
+    
+    let verifier = Address::generate(env);
+    client.add_trusted_verifier(&admin, &verifier);
+    client.add_verification_rule(&admin, &VerificationRule {
+        rule_id: 0,
+        chain_id: 1,
+        min_confirmations: 1,
+        gas_limit: 1_000_000,
+        verification_method: String::from_str(env, "merkle"),
+        active: true,
+    });
+    
+    (client, admin, verifier)
+}
+
+#[test]
+fn test_proof_certificate_round_trip() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin, verifier) = setup(&env);
+    
+    let authority = SigningKey::from_bytes(&[7u8; 32]);
+    let authority_key = BytesN::from_array(&env, &authority.verifying_key().to_bytes());
+    client.set_authority_key(&admin, &authority_key);
+    
+    let proof_data = Bytes::from_slice(&env, b"proof data");
+    let verification_hash = Bytes::from_slice(&env, b"verification hash");
+    assert!(client.verify_proof_on_chain(&1, &1, &proof_data, &verifier, &verification_hash));
+    
+    // Authority signs the digest of the recorded result off-chain
+    let result = client.get_verification_result(&1, &1).unwrap();
+    let digest = env.crypto().sha256(&result.clone().to_xdr(&env)).to_array();
+    let signature = authority.sign(&digest);
+    client.attest_verification_result(&1, &1, &BytesN::from_array(&env, &signature.to_bytes()));
+    
+    let encoded = client.get_proof_certificate(&1, &1);
+    let certificate = ProofCertificate::from_xdr(&env, &encoded).unwrap();
+    assert_eq!(certificate.result, result);
+    assert_eq!(certificate.authority_key, authority_key);
+    
+    // A relying party checks the certificate offline against the authority key
+    let recomputed = env.crypto().sha256(&certificate.result.clone().to_xdr(&env)).to_array();
+    assert_eq!(recomputed, certificate.result_digest.to_array());
+    let signature = ed25519_dalek::Signature::from_bytes(&certificate.signature.to_array());
+    assert!(authority.verifying_key().verify(&recomputed, &signature).is_ok());
+    
+    // Rotating the key leaves the certificate naming the key that actually signed it
+    let rotated = SigningKey::from_bytes(&[8u8; 32]);
+    client.set_authority_key(&admin, &BytesN::from_array(&env, &rotated.verifying_key().to_bytes()));
+    let certificate = ProofCertificate::from_xdr(&env, &client.get_proof_certificate(&1, &1)).unwrap();
+    assert_eq!(certificate.authority_key, authority_key);
+    let signature = ed25519_dalek::Signature::from_bytes(&certificate.signature.to_array());
+    assert!(authority.verifying_key().verify(&recomputed, &signature).is_ok());
+}
+
+#[test]
+fn test_attestation_rejects_foreign_signature() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin, verifier) = setup(&env);
+    
+    let authority = SigningKey::from_bytes(&[7u8; 32]);
+    let impostor = SigningKey::from_bytes(&[9u8; 32]);
+    client.set_authority_key(&admin, &BytesN::from_array(&env, &authority.verifying_key().to_bytes()));
+    
+    let proof_data = Bytes::from_slice(&env, b"proof data");
+    client.verify_proof_on_chain(&1, &1, &proof_data, &verifier, &Bytes::from_slice(&env, b"hash"));
+    
+    let result = client.get_verification_result(&1, &1).unwrap();
+    let digest = env.crypto().sha256(&result.to_xdr(&env)).to_array();
+    let signature = impostor.sign(&digest);
+    
+    let attempt = client.try_attest_verification_result(&1, &1, &BytesN::from_array(&env, &signature.to_bytes()));
+    assert!(attempt.is_err());
+    assert!(client.try_get_proof_certificate(&1, &1).is_err());
+}
+
+#[test]
+fn test_migrate_v1_state() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin, verifier) = setup(&env);
+    
+    // State written before versioning carries no version entry
+    env.as_contract(&client.address, || {
+        env.storage().instance().remove(&VerifierDataKey::StateVersion);
+    });
+    assert_eq!(client.get_state_version(), 1);
+    
+    assert_eq!(client.migrate(&admin), 3);
+    assert_eq!(client.get_state_version(), 3);
+    assert!(client.is_trusted_verifier(&verifier));
+    assert_eq!(client.get_rule_count(), 1);
+    assert_eq!(client.get_admin(), admin);
+}
+
+#[test]
+fn test_batch_verify_mixed_proofs() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, verifier) = setup(&env);
+    
+    let mut proofs = Vec::new(&env);
+    proofs.push_back((1u64, 1u32, Bytes::from_slice(&env, b"proof data")));
+    proofs.push_back((2u64, 1u32, Bytes::new(&env)));
+    proofs.push_back((3u64, 999u32, Bytes::from_slice(&env, b"proof data")));
+    
+    let results = client.batch_verify_proofs(&proofs, &verifier);
+    assert_eq!(results, Vec::from_array(&env, [true, false, false]));
+    
+    assert!(client.get_verification_result(&1, &1).unwrap().verified);
+    assert!(!client.get_verification_result(&2, &1).unwrap().verified);
+    // Chains without rules are skipped without recording anything
+    assert!(client.get_verification_result(&3, &999).is_none());
+    assert_eq!(client.get_verification_stats(&1), (2, 1, 1));
+    
+    let (_, _, data) = env.events().all().last().unwrap();
+    let summary: Vec<(u64, u32, BatchOutcome)> = data.into_val(&env);
+    assert_eq!(summary.get(0).unwrap(), (1, 1, BatchOutcome::Verified));
+    assert_eq!(summary.get(1).unwrap(), (2, 1, BatchOutcome::Failed));
+    assert_eq!(summary.get(2).unwrap(), (3, 999, BatchOutcome::Skipped));
+}
+
+#[test]
+fn test_batch_verify_rejects_oversized_batch() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, verifier) = setup(&env);
+    
+    let mut proofs = Vec::new(&env);
+    for proof_id in 0..21u64 {
+        proofs.push_back((proof_id, 1u32, Bytes::from_slice(&env, b"proof data")));
+    }
+    
+    assert!(client.try_batch_verify_proofs(&proofs, &verifier).is_err());
+}
+
+/// Build a proof of `depth` levels whose sibling at level `i` sits on the right when `i % 3 == 0`
+fn deep_merkle_proof(env: &Env, depth: u32) -> ChainProof {
+    let leaf = env.crypto().sha256(&Bytes::from_slice(env, b"account state"));
+    let mut siblings = Vec::new(env);
+    let mut directions = Bytes::new(env);
+    let mut node = leaf.clone();
+    
+    for level in 0..depth {
+        let sibling = env.crypto().sha256(&Bytes::from_slice(env, &level.to_be_bytes()));
+        let right = level % 3 == 0;
+        
+        if level % 8 == 0 {
+            directions.push_back(0);
+        }
+        if right {
+            let idx = level / 8;
+            directions.set(idx, directions.get(idx).unwrap() | (1 << (level % 8)));
+        }
+        
+        let mut preimage = Bytes::new(env);
+        if right {
+            preimage.append(&node.clone().into());
+            preimage.append(&sibling.clone().into());
+        } else {
+            preimage.append(&sibling.clone().into());
+            preimage.append(&node.clone().into());
+        }
+        node = env.crypto().sha256(&preimage);
+        siblings.push_back(sibling);
+    }
+    
+    ChainProof {
+        chain_id: 1,
+        leaf,
+        root: node,
+        siblings,
+        directions,
+    }
+}
+
+#[test]
+fn test_verify_deep_merkle_proof_with_direction_bitmap() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, _verifier) = setup(&env);
+    
+    // 24 levels pack into 3 direction bytes
+    let proof = deep_merkle_proof(&env, 24);
+    assert_eq!(proof.directions.len(), 3);
+    assert!(client.verify_merkle_proof(&proof));
+    
+    // Flipping the direction of the deepest level changes the root
+    let mut flipped = proof.clone();
+    flipped.directions.set(2, flipped.directions.get(2).unwrap() ^ 0x80);
+    assert!(!client.verify_merkle_proof(&flipped));
+    
+    // The bitmap must cover exactly one bit per level
+    let mut short = proof.clone();
+    short.directions = short.directions.slice(0..2);
+    assert!(client.try_verify_merkle_proof(&short).is_err());
+}
+
+#[test]
+fn test_cross_chain_proof_id_collision_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin, verifier) = setup(&env);
+    client.add_verification_rule(&admin, &VerificationRule {
+        rule_id: 0,
+        chain_id: 137,
+        min_confirmations: 1,
+        gas_limit: 1_000_000,
+        verification_method: String::from_str(&env, "merkle"),
+        active: true,
+    });
+    
+    let proof_data = Bytes::from_slice(&env, b"proof data");
+    let hash = Bytes::from_slice(&env, b"hash");
+    assert!(client.verify_proof_on_chain(&7, &1, &proof_data, &verifier, &hash));
+    assert!(client.verify_proof_on_chain(&8, &1, &proof_data, &verifier, &hash));
+    
+    // Picking another verified proof's id is rejected
+    assert!(client.try_verify_cross_chain_proof(&7, &1, &137, &8, &verifier).is_err());
+    
+    let derived = client.derive_cross_chain_id(&7, &137);
+    assert_eq!(derived, client.derive_cross_chain_id(&7, &137));
+    assert_ne!(derived, client.derive_cross_chain_id(&7, &56));
+    
+    assert!(client.verify_cross_chain_proof(&7, &1, &137, &derived, &verifier));
+    assert!(client.get_verification_result(&derived, &137).unwrap().verified);
+    
+    // Replaying the same carry-over is rejected too
+    assert!(client.try_verify_cross_chain_proof(&7, &1, &137, &derived, &verifier).is_err());
+    
+    // The other direction: a cross-chain id can't later be verified as a proof of its own, on any chain
+    assert!(client.try_verify_proof_on_chain(&derived, &1, &proof_data, &verifier, &hash).is_err());
+    assert!(client.try_verify_proof_on_chain(&derived, &137, &proof_data, &verifier, &hash).is_err());
+    assert!(client.try_simulate_verify_proof_on_chain(&derived, &1, &proof_data, &verifier, &hash).is_err());
+    let batch = Vec::from_array(&env, [(derived, 1u32, proof_data.clone())]);
+    assert_eq!(client.batch_verify_proofs(&batch, &verifier), Vec::from_array(&env, [false]));
+}
+
+#[test]
+fn test_retry_rejected_until_cooldown_passes() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin, verifier) = setup(&env);
+    client.set_verify_cooldown(&admin, &60);
+    
+    let hash = Bytes::from_slice(&env, b"hash");
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    
+    // An empty payload fails verification, as a transient failure would
+    assert!(!client.verify_proof_on_chain(&1, &1, &Bytes::new(&env), &verifier, &hash));
+    assert_eq!(client.get_last_attempt(&1, &1), Some(1_000));
+    
+    env.ledger().with_mut(|li| li.timestamp = 1_059);
+    assert!(client.try_verify_proof_on_chain(&1, &1, &Bytes::from_slice(&env, b"proof data"), &verifier, &hash).is_err());
+    
+    env.ledger().with_mut(|li| li.timestamp = 1_060);
+    assert!(client.verify_proof_on_chain(&1, &1, &Bytes::from_slice(&env, b"proof data"), &verifier, &hash));
+    assert_eq!(client.get_last_attempt(&1, &1), Some(1_060));
+}
+
+#[test]
+fn test_is_chain_supported() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin, _verifier) = setup(&env);
+    
+    assert!(client.is_chain_supported(&1));
+    assert!(!client.is_chain_supported(&56));
+    
+    // Rules added before support was tracked are picked up by the migration
+    env.as_contract(&client.address, || {
+        env.storage().instance().remove(&VerifierDataKey::ChainSupported(1));
+        env.storage().instance().set(&VerifierDataKey::StateVersion, &2u32);
+    });
+    assert!(!client.is_chain_supported(&1));
+    client.migrate(&admin);
+    assert!(client.is_chain_supported(&1));
+}
+
+#[test]
+fn test_verification_status_lookup() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin, verifier) = setup(&env);
+    
+    let authority = SigningKey::from_bytes(&[7u8; 32]);
+    client.set_authority_key(&admin, &BytesN::from_array(&env, &authority.verifying_key().to_bytes()));
+    
+    let hash = Bytes::from_slice(&env, b"verification hash");
+    client.verify_proof_on_chain(&1, &1, &Bytes::from_slice(&env, b"proof data"), &verifier, &hash);
+    client.verify_proof_on_chain(&2, &1, &Bytes::new(&env), &verifier, &hash);
+    
+    assert_eq!(client.get_verification_status(&1, &1), VerificationStatus::Pending);
+    assert_eq!(client.get_verification_status(&2, &1), VerificationStatus::Failed);
+    assert_eq!(client.get_verification_status(&3, &1), VerificationStatus::Unknown);
+    
+    let result = client.get_verification_result(&1, &1).unwrap();
+    let digest = env.crypto().sha256(&result.to_xdr(&env)).to_array();
+    client.attest_verification_result(&1, &1, &BytesN::from_array(&env, &authority.sign(&digest).to_bytes()));
+    assert_eq!(client.get_verification_status(&1, &1), VerificationStatus::Verified);
+}
+
+#[test]
+fn test_status_overrides_accumulate_history() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin, verifier) = setup(&env);
+    
+    let hash = Bytes::from_slice(&env, b"verification hash");
+    client.verify_proof_on_chain(&1, &1, &Bytes::from_slice(&env, b"proof data"), &verifier, &hash);
+    assert!(client.get_status_history(&1).is_empty());
+    
+    let fraud = String::from_str(&env, "source block reorged");
+    assert!(client.try_update_verification_status(&verifier, &1, &1, &false, &fraud).is_err());
+    assert!(client.try_update_verification_status(&admin, &1, &1, &false, &String::from_str(&env, "")).is_err());
+    assert!(client.try_update_verification_status(&admin, &2, &1, &false, &fraud).is_err());
+    
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    client.update_verification_status(&admin, &1, &1, &false, &fraud);
+    assert_eq!(client.get_verification_status(&1, &1), VerificationStatus::Failed);
+    
+    let restored = String::from_str(&env, "reorg resolved");
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    client.update_verification_status(&admin, &1, &1, &true, &restored);
+    assert_eq!(client.get_verification_status(&1, &1), VerificationStatus::Verified);
+    
+    let history = client.get_status_history(&1);
+    assert_eq!(history, Vec::from_array(&env, [
+        StatusChange {
+            chain_id: 1,
+            changed_by: admin.clone(),
+            changed_at: 100,
+            old_status: VerificationStatus::Verified,
+            new_status: VerificationStatus::Failed,
+            reason: fraud,
+        },
+        StatusChange {
+            chain_id: 1,
+            changed_by: admin.clone(),
+            changed_at: 200,
+            old_status: VerificationStatus::Failed,
+            new_status: VerificationStatus::Verified,
+            reason: restored,
+        },
+    ]));
+    assert!(client.get_status_history(&2).is_empty());
+}
+
+#[test]
+fn test_simulated_verification_matches_real_without_state_change() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, verifier) = setup(&env);
+    
+    let proof_data = Bytes::from_slice(&env, b"proof data");
+    let verification_hash = Bytes::from_slice(&env, b"verification hash");
+    let simulated = client.simulate_verify_proof_on_chain(&1, &1, &proof_data, &verifier, &verification_hash);
+    assert!(simulated.verified);
+    
+    // Nothing is recorded by the dry run
+    assert!(client.get_verification_result(&1, &1).is_none());
+    assert!(client.get_last_attempt(&1, &1).is_none());
+    assert_eq!(client.get_verification_stats(&1), (0, 0, 0));
+    
+    client.verify_proof_on_chain(&1, &1, &proof_data, &verifier, &verification_hash);
+    assert_eq!(client.get_verification_result(&1, &1), Some(simulated));
+    
+    // A malformed proof is caught before it is submitted
+    let empty = Bytes::new(&env);
+    assert!(!client.simulate_verify_proof_on_chain(&2, &1, &empty, &verifier, &verification_hash).verified);
+    assert!(client.try_simulate_verify_proof_on_chain(&2, &1, &proof_data, &Address::generate(&env), &verification_hash).is_err());
+}
+
+/// Records the last verification result it was notified of, or rejects it once told to fail
+#[contract]
+struct RecordingCallback;
+
+#[contractimpl]
+impl RecordingCallback {
+    pub fn set_failing(env: Env, failing: bool) {
+        env.storage().instance().set(&Symbol::new(&env, "failing"), &failing);
+    }
+    
+    pub fn on_proof_verified(env: Env, proof_id: u64, chain_id: u32, verified: bool) {
+        if env.storage().instance().get(&Symbol::new(&env, "failing")).unwrap_or(false) {
+            panic!("callback failed");
+        }
+        env.storage().instance().set(&Symbol::new(&env, "last"), &(proof_id, chain_id, verified));
+    }
+}
+
+#[test]
+fn test_verification_notifies_callback() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, verifier) = setup(&env);
+    let recorder = env.register_contract(None, RecordingCallback);
+    let failing = env.register_contract(None, RecordingCallback);
+    RecordingCallbackClient::new(&env, &failing).set_failing(&true);
+    
+    let verification_hash = Bytes::from_slice(&env, b"verification hash");
+    assert!(client.try_set_verification_callback(&Address::generate(&env), &1, &1, &Some(recorder.clone())).is_err());
+    client.set_verification_callback(&verifier, &1, &1, &Some(recorder.clone()));
+    assert_eq!(client.get_verification_callback(&1, &1), Some(recorder.clone()));
+    
+    client.verify_proof_on_chain(&1, &1, &Bytes::from_slice(&env, b"proof data"), &verifier, &verification_hash);
+    let last: (u64, u32, bool) = env.as_contract(&recorder, || {
+        env.storage().instance().get(&Symbol::new(&env, "last")).unwrap()
+    });
+    assert_eq!(last, (1, 1, true));
+    
+    // A failing callback is logged and the verification still stands
+    client.set_verification_callback(&verifier, &2, &1, &Some(failing.clone()));
+    assert!(client.verify_proof_on_chain(&2, &1, &Bytes::from_slice(&env, b"proof data"), &verifier, &verification_hash));
+    assert!(client.get_verification_result(&2, &1).unwrap().verified);
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (Symbol::new(&env, "callback_failed"), 2u64, 1u32).into_val(&env));
+    assert_eq!(Address::try_from_val(&env, &data).unwrap(), failing);
+    
+    client.set_verification_callback(&verifier, &1, &1, &None);
+    assert_eq!(client.get_verification_callback(&1, &1), None);
+}
+
+#[test]
+fn test_oldest_pending_verification() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin, verifier) = setup(&env);
+    
+    let hash = Bytes::from_slice(&env, b"verification hash");
+    let proof_data = Bytes::from_slice(&env, b"proof data");
+    
+    // Nothing awaits attestation, or is indexed, until there is an authority to attest
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    client.verify_proof_on_chain(&9, &1, &proof_data, &verifier, &hash);
+    client.verify_proof_on_chain(&8, &1, &proof_data, &verifier, &hash);
+    client.update_verification_status(&admin, &8, &1, &false, &String::from_str(&env, "bad proof"));
+    assert_eq!(client.get_oldest_pending(), None);
+    env.as_contract(&client.address, || {
+        assert!(!env.storage().instance().has(&VerifierDataKey::AwaitingAttestation));
+    });
+    
+    // The first key picks up results verified before it, skipping those that have since failed
+    let authority = SigningKey::from_bytes(&[7u8; 32]);
+    client.set_authority_key(&admin, &BytesN::from_array(&env, &authority.verifying_key().to_bytes()));
+    assert_eq!(client.get_oldest_pending(), Some((9, 1, 50)));
+    let result = client.get_verification_result(&9, &1).unwrap();
+    let digest = env.crypto().sha256(&result.to_xdr(&env)).to_array();
+    client.attest_verification_result(&9, &1, &BytesN::from_array(&env, &authority.sign(&digest).to_bytes()));
+    assert_eq!(client.get_oldest_pending(), None);
+    
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    client.verify_proof_on_chain(&5, &1, &proof_data, &verifier, &hash);
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    client.verify_proof_on_chain(&3, &1, &proof_data, &verifier, &hash);
+    env.ledger().with_mut(|li| li.timestamp = 300);
+    client.verify_proof_on_chain(&4, &1, &Bytes::new(&env), &verifier, &hash);
+    assert_eq!(client.get_oldest_pending(), Some((5, 1, 100)));
+    
+    let result = client.get_verification_result(&5, &1).unwrap();
+    let digest = env.crypto().sha256(&result.to_xdr(&env)).to_array();
+    client.attest_verification_result(&5, &1, &BytesN::from_array(&env, &authority.sign(&digest).to_bytes()));
+    assert_eq!(client.get_oldest_pending(), Some((3, 1, 200)));
+    
+    client.update_verification_status(&admin, &3, &1, &false, &String::from_str(&env, "bad proof"));
+    assert_eq!(client.get_oldest_pending(), None);
+}
@@ -1,5 +1,17 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Bytes, Env, String, Vec, Map, U256};
+use soroban_sdk::{contract, contractimpl, contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, String, Vec, Map, U256};
+
+use crate::capacity;
+use crate::fees;
+
+/// Layout version of this contract's stored state
+const STATE_VERSION: u32 = 3;
+/// Most chain configs a single batch import may carry
+const MAX_CHAIN_CONFIG_BATCH: u32 = 32;
+/// Most recipients a chain's allowlist may hold
+const MAX_RECIPIENT_ALLOWLIST: u32 = 256;
+/// Ledgers a persistent entry is kept alive for after each write or check (about 30 days)
+const PERSISTENT_TTL_LEDGERS: u32 = 518_400;
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -9,6 +21,7 @@ pub struct ChainConfig {
     pub bridge_address: Address,
     pub gas_price: u64,
     pub block_time: u64,
+    pub required_confirmations: u64,
 }
 
 #[contracttype]
@@ -18,10 +31,13 @@ pub struct CrossChainProof {
     pub source_chain: u32,
     pub target_chain: u32,
     pub proof_data: Bytes,
+    pub source_block: u64,
+    pub target_block: u64,
     pub source_verification: bool,
     pub target_verification: bool,
     pub timestamp: u64,
     pub gas_used: u64,
+    pub fee: u64,
 }
 
 #[contracttype]
@@ -38,6 +54,15 @@ pub struct BridgeMessage {
     pub timestamp: u64,
 }
 
+/// Proofs submitted but not yet verified on their target chain
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingCounts {
+    pub total: u64,
+    pub by_source_chain: Map<u32, u64>,
+    pub by_target_chain: Map<u32, u64>,
+}
+
 #[contracttype]
 pub enum BridgeDataKey {
     ChainConfig(u32),
@@ -46,6 +71,21 @@ pub enum BridgeDataKey {
     ProofCount,
     MessageCount,
     SupportedChains,
+    FeeToken,
+    FeeRate,
+    FeeRateOverride(u32), // destination chain_id -> flat fee replacing the global rate
+    SubmitterTrust(Address),
+    MinSubmitTrust(u32), // chain_id -> trust level required to submit proofs touching it
+    MinFeeReceivedBps,
+    StateVersion,
+    PendingCounts,
+    MaxPending,
+    ProofSubmitter(u64),
+    RefundedProof(u64),
+    OldestPending, // lowest proof id that may still be pending; every id below it is settled
+    FeePaid(u64), // proof_id -> (fee token, amount the vault received); absent when no fee was taken
+    RecipientAllowlist(u32), // target chain_id -> the only recipients messages to it may name
+    Denylisted(Address), // parties messages may neither come from nor go to
     Admin,
 }
 
@@ -69,6 +109,49 @@ impl CrossChainBridge {
         supported_chains.push_back(137u32); // Polygon
         supported_chains.push_back(56u32); // BSC
         env.storage().instance().set(&BridgeDataKey::SupportedChains, &supported_chains);
+        env.storage().instance().set(&BridgeDataKey::MaxPending, &capacity::DEFAULT_MAX_PENDING);
+        env.storage().instance().set(&BridgeDataKey::StateVersion, &STATE_VERSION);
+    }
+
+    /// Get the layout version of stored state (state predating versioning is v1)
+    pub fn get_state_version(env: Env) -> u32 {
+        env.storage().instance().get(&BridgeDataKey::StateVersion).unwrap_or(1)
+    }
+
+    /// Upgrade state written by an older contract version to the current layout
+    pub fn migrate(env: Env, admin: Address) -> u32 {
+        let stored_admin: Address = env.storage().instance()
+            .get(&BridgeDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let version = Self::get_state_version(env.clone());
+        if version > STATE_VERSION {
+            panic!("Stored state is newer than this contract");
+        }
+        
+        // v1 -> v2: the version is now tracked explicitly, existing entries are kept as-is
+        // v2 -> v3: pending counters are seeded from proofs submitted before they existed
+        if version < 3 {
+            let mut counts = Self::empty_pending_counts(env.clone());
+            let count: u64 = env.storage().instance().get(&BridgeDataKey::ProofCount).unwrap_or(0);
+            for proof_id in 1..=count {
+                if let Some(proof) = env.storage().instance().get::<BridgeDataKey, CrossChainProof>(&BridgeDataKey::CrossChainProof(proof_id)) {
+                    if !proof.target_verification {
+                        Self::adjust_pending(&mut counts, proof.source_chain, proof.target_chain, true);
+                    }
+                }
+            }
+            env.storage().instance().set(&BridgeDataKey::PendingCounts, &counts);
+        }
+        env.storage().instance().set(&BridgeDataKey::StateVersion, &STATE_VERSION);
+        
+        STATE_VERSION
     }
 
     /// Add supported chain configuration
@@ -83,9 +166,50 @@ impl CrossChainBridge {
         
         admin.require_auth();
         
+        let mut supported_chains = Self::get_supported_chains(env.clone());
+        if !supported_chains.contains(&chain_config.chain_id) {
+            supported_chains.push_back(chain_config.chain_id);
+            env.storage().instance().set(&BridgeDataKey::SupportedChains, &supported_chains);
+        }
         env.storage().instance().set(&BridgeDataKey::ChainConfig(chain_config.chain_id), &chain_config);
     }
 
+    /// Add and support several chains at once; the whole batch is rejected if any chain id is already configured or repeated
+    pub fn add_chain_configs(env: Env, admin: Address, chain_configs: Vec<ChainConfig>) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&BridgeDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        if chain_configs.len() > MAX_CHAIN_CONFIG_BATCH {
+            panic!("Chain config batch too large");
+        }
+        
+        // Validate everything before writing so a bad entry leaves no partial import
+        let mut batch_ids: Vec<u32> = Vec::new(&env);
+        for chain_config in chain_configs.iter() {
+            if batch_ids.contains(&chain_config.chain_id)
+                || env.storage().instance().has(&BridgeDataKey::ChainConfig(chain_config.chain_id)) {
+                panic!("Duplicate chain config");
+            }
+            batch_ids.push_back(chain_config.chain_id);
+        }
+        
+        let mut supported_chains = Self::get_supported_chains(env.clone());
+        for chain_config in chain_configs.iter() {
+            if !supported_chains.contains(&chain_config.chain_id) {
+                supported_chains.push_back(chain_config.chain_id);
+            }
+            env.storage().instance().set(&BridgeDataKey::ChainConfig(chain_config.chain_id), &chain_config);
+        }
+        env.storage().instance().set(&BridgeDataKey::SupportedChains, &supported_chains);
+    }
+
     /// Get chain configuration
     pub fn get_chain_config(env: Env, chain_id: u32) -> Option<ChainConfig> {
         env.storage().instance().get(&BridgeDataKey::ChainConfig(chain_id))
@@ -96,12 +220,104 @@ impl CrossChainBridge {
         env.storage().instance().get(&BridgeDataKey::SupportedChains).unwrap_or(Vec::new(&env))
     }
 
+    /// Check whether a chain is supported, the same check proof submission and refunds apply
+    pub fn is_chain_supported(env: Env, chain_id: u32) -> bool {
+        Self::get_supported_chains(env).contains(&chain_id)
+    }
+
+    /// Stop supporting a chain and drop its configuration; pending proofs targeting it become refundable by anyone
+    pub fn remove_supported_chain(env: Env, admin: Address, chain_id: u32) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&BridgeDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let mut supported_chains = Self::get_supported_chains(env.clone());
+        let index = supported_chains.first_index_of(&chain_id)
+            .unwrap_or_else(|| panic!("Unsupported chain"));
+        supported_chains.remove(index);
+        env.storage().instance().set(&BridgeDataKey::SupportedChains, &supported_chains);
+        env.storage().instance().remove(&BridgeDataKey::ChainConfig(chain_id));
+    }
+
+    /// Set how many items may be pending at once before new submissions are rejected
+    pub fn set_max_pending(env: Env, admin: Address, max_pending: u32) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&BridgeDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        if max_pending == 0 {
+            panic!("Max pending must be positive");
+        }
+        
+        env.storage().instance().set(&BridgeDataKey::MaxPending, &max_pending);
+    }
+
+    /// Get how many items may be pending at once
+    pub fn get_max_pending(env: Env) -> u32 {
+        env.storage().instance()
+            .get(&BridgeDataKey::MaxPending)
+            .unwrap_or(capacity::DEFAULT_MAX_PENDING)
+    }
+
+    /// Record a submitter's trust level
+    pub fn set_submitter_trust(env: Env, admin: Address, submitter: Address, trust_level: u32) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&BridgeDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&BridgeDataKey::SubmitterTrust(submitter), &trust_level);
+    }
+
+    /// Get a submitter's trust level, zero when never set
+    pub fn get_submitter_trust(env: Env, submitter: Address) -> u32 {
+        env.storage().instance().get(&BridgeDataKey::SubmitterTrust(submitter)).unwrap_or(0)
+    }
+
+    /// Require submitters of proofs from or to `chain_id` to hold at least `min_trust`
+    pub fn set_min_submit_trust(env: Env, admin: Address, chain_id: u32, min_trust: u32) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&BridgeDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&BridgeDataKey::MinSubmitTrust(chain_id), &min_trust);
+    }
+
+    /// Get the trust level required to submit proofs touching a chain, zero when open
+    pub fn get_min_submit_trust(env: Env, chain_id: u32) -> u32 {
+        env.storage().instance().get(&BridgeDataKey::MinSubmitTrust(chain_id)).unwrap_or(0)
+    }
+
     /// Submit cross-chain proof for verification
     pub fn submit_cross_chain_proof(
         env: Env,
         source_chain: u32,
         target_chain: u32,
         proof_data: Bytes,
+        source_block: u64,
         submitter: Address,
     ) -> u64 {
         submitter.require_auth();
@@ -112,28 +328,55 @@ impl CrossChainBridge {
             panic!("Unsupported chain");
         }
         
+        // Both ends of the route must accept this submitter
+        let trust = Self::get_submitter_trust(env.clone(), submitter.clone());
+        if trust < Self::get_min_submit_trust(env.clone(), source_chain)
+            || trust < Self::get_min_submit_trust(env.clone(), target_chain)
+        {
+            panic!("Submitter trust too low");
+        }
+        
+        capacity::ensure_below_max_pending(
+            Self::get_pending_counts(env.clone()).total,
+            Self::get_max_pending(env.clone()),
+        );
+        
         let count: u64 = env.storage().instance().get(&BridgeDataKey::ProofCount).unwrap_or(0);
         let proof_id = count + 1;
         
+        let gas_used = Self::estimate_proof_gas(env.clone(), proof_data.len());
+        let fee = Self::compute_fee(env.clone(), gas_used, target_chain);
+        // Refunds return only what reached the vault, and nothing when no fee token was set
+        let paid = Self::collect_fee(env.clone(), submitter.clone(), fee);
+        let fee = paid.clone().map_or(fee, |(_, received)| received);
+        
         let proof = CrossChainProof {
             proof_id,
             source_chain,
             target_chain,
             proof_data: proof_data.clone(),
+            source_block,
+            target_block: 0,
             source_verification: false,
             target_verification: false,
             timestamp: env.ledger().timestamp(),
-            gas_used: 0,
+            gas_used,
+            fee,
         };
         
         env.storage().instance().set(&BridgeDataKey::CrossChainProof(proof_id), &proof);
         env.storage().instance().set(&BridgeDataKey::ProofCount, &proof_id);
+        env.storage().instance().set(&BridgeDataKey::ProofSubmitter(proof_id), &submitter);
+        if let Some(paid) = paid {
+            env.storage().instance().set(&BridgeDataKey::FeePaid(proof_id), &paid);
+        }
+        Self::update_pending_counts(env.clone(), source_chain, target_chain, true);
         
         proof_id
     }
 
-    /// Verify proof on source chain
-    pub fn verify_source_proof(env: Env, admin: Address, proof_id: u64) -> bool {
+    /// Verify proof on source chain once its block is buried under `head_block`
+    pub fn verify_source_proof(env: Env, admin: Address, proof_id: u64, head_block: u64) -> bool {
         let stored_admin: Address = env.storage().instance()
             .get(&BridgeDataKey::Admin)
             .unwrap_or_else(|| panic!("Admin not found"));
@@ -148,14 +391,26 @@ impl CrossChainBridge {
             .get(&BridgeDataKey::CrossChainProof(proof_id))
             .unwrap_or_else(|| panic!("Proof not found"));
         
+        if Self::is_refunded(env.clone(), proof_id) {
+            panic!("Proof refunded");
+        }
+        
+        Self::check_confirmations(env.clone(), proof.source_chain, proof.source_block, head_block);
+        
         proof.source_verification = true;
         env.storage().instance().set(&BridgeDataKey::CrossChainProof(proof_id), &proof);
         
         true
     }
 
-    /// Verify proof on target chain
-    pub fn verify_target_proof(env: Env, admin: Address, proof_id: u64) -> bool {
+    /// Verify proof on target chain once `target_block` is buried under `head_block`
+    pub fn verify_target_proof(
+        env: Env,
+        admin: Address,
+        proof_id: u64,
+        target_block: u64,
+        head_block: u64,
+    ) -> bool {
         let stored_admin: Address = env.storage().instance()
             .get(&BridgeDataKey::Admin)
             .unwrap_or_else(|| panic!("Admin not found"));
@@ -170,16 +425,287 @@ impl CrossChainBridge {
             .get(&BridgeDataKey::CrossChainProof(proof_id))
             .unwrap_or_else(|| panic!("Proof not found"));
         
+        if Self::is_refunded(env.clone(), proof_id) {
+            panic!("Proof refunded");
+        }
+        
         if !proof.source_verification {
             panic!("Source proof must be verified first");
         }
         
+        Self::check_confirmations(env.clone(), proof.target_chain, target_block, head_block);
+        
+        proof.target_block = target_block;
         proof.target_verification = true;
         env.storage().instance().set(&BridgeDataKey::CrossChainProof(proof_id), &proof);
+        Self::update_pending_counts(env.clone(), proof.source_chain, proof.target_chain, false);
         
         true
     }
 
+    /// Refund a pending proof's fee to its submitter and drop it from the pending set
+    /// The admin may refund any pending proof; once the target chain is no longer supported
+    /// the proof can never complete, so anyone may refund it
+    pub fn refund_proof(env: Env, caller: Address, proof_id: u64) {
+        caller.require_auth();
+        
+        let proof: CrossChainProof = env.storage().instance()
+            .get(&BridgeDataKey::CrossChainProof(proof_id))
+            .unwrap_or_else(|| panic!("Proof not found"));
+        
+        if proof.target_verification {
+            panic!("Proof already completed");
+        }
+        if Self::is_refunded(env.clone(), proof_id) {
+            panic!("Proof already refunded");
+        }
+        
+        let stranded = !Self::get_supported_chains(env.clone()).contains(&proof.target_chain);
+        if !stranded && caller != Self::get_admin(env.clone()) {
+            panic!("Not authorized");
+        }
+        
+        Self::void_proof(env, proof);
+    }
+
+    /// Void pending proofs whose source block was reorged away below `new_head`, refunding their fees
+    /// Scans at most `limit` proof ids from `start`, never below the oldest pending proof; returns the
+    /// voided ids and the id to resume from, which is 0 once every proof has been checked
+    pub fn report_source_reorg(env: Env, admin: Address, chain_id: u32, new_head: u64, start: u64, limit: u32) -> (Vec<u64>, u64) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&BridgeDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let count: u64 = env.storage().instance().get(&BridgeDataKey::ProofCount).unwrap_or(0);
+        let oldest_pending: u64 = env.storage().instance().get(&BridgeDataKey::OldestPending).unwrap_or(1);
+        let mut voided = Vec::new(&env);
+        
+        let mut proof_id = start.max(oldest_pending);
+        let end = count.min(proof_id.saturating_add(limit as u64).saturating_sub(1));
+        while proof_id <= end {
+            if let Some(proof) = env.storage().instance().get::<BridgeDataKey, CrossChainProof>(&BridgeDataKey::CrossChainProof(proof_id)) {
+                if proof.source_chain == chain_id
+                    && proof.source_block > new_head
+                    && !proof.target_verification
+                    && !Self::is_refunded(env.clone(), proof_id)
+                {
+                    Self::void_proof(env.clone(), proof);
+                    voided.push_back(proof_id);
+                }
+            }
+            proof_id += 1;
+        }
+        
+        let next = if proof_id > count { 0 } else { proof_id };
+        (voided, next)
+    }
+
+    /// Check whether a proof's fee has been refunded
+    pub fn is_refunded(env: Env, proof_id: u64) -> bool {
+        env.storage().instance().has(&BridgeDataKey::RefundedProof(proof_id))
+    }
+
+    /// Get the oldest proof not yet verified on its target chain as `(proof_id, timestamp)`
+    /// Ids are issued in submission order, so the oldest is the one the pending cursor rests on
+    pub fn get_oldest_pending(env: Env) -> Option<(u64, u64)> {
+        let proof_id: u64 = env.storage().instance().get(&BridgeDataKey::OldestPending).unwrap_or(1);
+        env.storage().instance()
+            .get::<BridgeDataKey, CrossChainProof>(&BridgeDataKey::CrossChainProof(proof_id))
+            .filter(|_| Self::is_pending(env.clone(), proof_id))
+            .map(|proof| (proof_id, proof.timestamp))
+    }
+
+    /// Get the number of pending proofs, in total and per source and target chain
+    pub fn get_pending_counts(env: Env) -> PendingCounts {
+        env.storage().instance()
+            .get(&BridgeDataKey::PendingCounts)
+            .unwrap_or_else(|| Self::empty_pending_counts(env.clone()))
+    }
+
+    /// Set the token fees are collected in
+    pub fn set_fee_token(env: Env, admin: Address, fee_token: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&BridgeDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&BridgeDataKey::FeeToken, &fee_token);
+    }
+
+    /// Get the token fees are collected in
+    pub fn get_fee_token(env: Env) -> Option<Address> {
+        env.storage().instance().get(&BridgeDataKey::FeeToken)
+    }
+
+    /// Set the flat settlement fee charged per proof on top of its gas cost
+    pub fn set_fee_rate(env: Env, admin: Address, fee_rate: u64) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&BridgeDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&BridgeDataKey::FeeRate, &fee_rate);
+    }
+
+    /// Get the global settlement fee
+    pub fn get_fee_rate(env: Env) -> u64 {
+        env.storage().instance().get(&BridgeDataKey::FeeRate).unwrap_or(0)
+    }
+
+    /// Price settlement on a destination chain separately from the global rate; `None` clears the override
+    pub fn set_fee_rate_override(env: Env, admin: Address, chain_id: u32, fee_rate_override: Option<u64>) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&BridgeDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        match fee_rate_override {
+            Some(rate) => env.storage().instance().set(&BridgeDataKey::FeeRateOverride(chain_id), &rate),
+            None => env.storage().instance().remove(&BridgeDataKey::FeeRateOverride(chain_id)),
+        }
+    }
+
+    /// Get a destination chain's settlement fee override, if any
+    pub fn get_fee_rate_override(env: Env, chain_id: u32) -> Option<u64> {
+        env.storage().instance().get(&BridgeDataKey::FeeRateOverride(chain_id))
+    }
+
+    /// Restrict which recipients messages to a chain may name; `None` lifts the restriction
+    pub fn set_recipient_allowlist(env: Env, admin: Address, chain_id: u32, recipient_allowlist: Option<Vec<Address>>) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&BridgeDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        match recipient_allowlist {
+            Some(recipients) => {
+                if recipients.len() > MAX_RECIPIENT_ALLOWLIST {
+                    panic!("Recipient allowlist too large");
+                }
+                env.storage().instance().set(&BridgeDataKey::RecipientAllowlist(chain_id), &recipients)
+            }
+            None => env.storage().instance().remove(&BridgeDataKey::RecipientAllowlist(chain_id)),
+        }
+    }
+
+    /// Get the recipients messages to a chain are restricted to, if any
+    pub fn get_recipient_allowlist(env: Env, chain_id: u32) -> Option<Vec<Address>> {
+        env.storage().instance().get(&BridgeDataKey::RecipientAllowlist(chain_id))
+    }
+
+    /// Check whether messages to a chain may name `recipient`
+    pub fn is_recipient_allowed(env: Env, chain_id: u32, recipient: Address) -> bool {
+        match Self::get_recipient_allowlist(env, chain_id) {
+            Some(recipients) => recipients.contains(&recipient),
+            None => true,
+        }
+    }
+
+    /// Block an address from sending or receiving bridge messages
+    pub fn add_to_denylist(env: Env, admin: Address, party: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&BridgeDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let key = BridgeDataKey::Denylisted(party);
+        env.storage().persistent().set(&key, &true);
+        Self::extend_persistent_ttl(env, &key);
+    }
+
+    /// Lift a denylist block
+    pub fn remove_from_denylist(env: Env, admin: Address, party: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&BridgeDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().persistent().remove(&BridgeDataKey::Denylisted(party));
+    }
+
+    /// Check whether an address is denylisted; each check keeps the entry alive
+    pub fn is_denylisted(env: Env, party: Address) -> bool {
+        let key = BridgeDataKey::Denylisted(party);
+        if !env.storage().persistent().has(&key) {
+            return false;
+        }
+        Self::extend_persistent_ttl(env, &key);
+        true
+    }
+
+    /// Set the share of a charged fee (basis points) the vault must actually receive
+    /// Lower it below 100% to accept fee tokens that skim a fee on transfer
+    pub fn set_min_fee_received_bps(env: Env, admin: Address, min_bps: u32) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&BridgeDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        if min_bps > fees::FEE_DENOMINATOR {
+            panic!("Invalid minimum");
+        }
+        
+        env.storage().instance().set(&BridgeDataKey::MinFeeReceivedBps, &min_bps);
+    }
+
+    /// Get the share of a charged fee (basis points) the vault must actually receive
+    pub fn get_min_fee_received_bps(env: Env) -> u32 {
+        env.storage().instance()
+            .get(&BridgeDataKey::MinFeeReceivedBps)
+            .unwrap_or(fees::FEE_DENOMINATOR)
+    }
+
+    /// Get the fee vault collected fees are sent to
+    pub fn get_fee_vault(env: Env) -> Address {
+        fees::fee_vault(&env)
+    }
+
+    /// Get the fee a proof consuming `gas` would be charged on `chain_id`
+    pub fn get_fee_estimate(env: Env, gas: u64, chain_id: u32) -> u64 {
+        Self::compute_fee(env, gas, chain_id)
+    }
+
     /// Get cross-chain proof details
     pub fn get_cross_chain_proof(env: Env, proof_id: u64) -> CrossChainProof {
         env.storage().instance()
@@ -187,6 +713,44 @@ impl CrossChainBridge {
             .unwrap_or_else(|| panic!("Proof not found"))
     }
 
+    /// Get the hash a destination chain recomputes to verify a submitted proof
+    pub fn get_transfer_proof(env: Env, proof_id: u64) -> BytesN<32> {
+        let proof = Self::get_cross_chain_proof(env.clone(), proof_id);
+        Self::generate_transfer_proof(env, proof.proof_id, proof.source_chain, proof.target_chain, proof.proof_data, proof.source_block)
+    }
+
+    /// Hash committing to a proof's immutable terms: sha256 of their XDR encoding
+    pub fn generate_transfer_proof(
+        env: Env,
+        proof_id: u64,
+        source_chain: u32,
+        target_chain: u32,
+        proof_data: Bytes,
+        source_block: u64,
+    ) -> BytesN<32> {
+        let terms = (proof_id, source_chain, target_chain, proof_data, source_block).to_xdr(&env);
+        env.crypto().sha256(&terms)
+    }
+
+    /// Preview a submission without making it: the proof id it would get, its transfer proof and its fee
+    /// The id only holds if no other proof is submitted first
+    pub fn simulate_transfer(
+        env: Env,
+        source_chain: u32,
+        target_chain: u32,
+        proof_data: Bytes,
+        source_block: u64,
+    ) -> (u64, BytesN<32>, u64) {
+        let count: u64 = env.storage().instance().get(&BridgeDataKey::ProofCount).unwrap_or(0);
+        let proof_id = count + 1;
+        
+        let gas_used = Self::estimate_proof_gas(env.clone(), proof_data.len());
+        let fee = Self::compute_fee(env.clone(), gas_used, target_chain);
+        let proof_hash = Self::generate_transfer_proof(env, proof_id, source_chain, target_chain, proof_data, source_block);
+        
+        (proof_id, proof_hash, fee)
+    }
+
     /// Send bridge message
     pub fn send_bridge_message(
         env: Env,
@@ -199,6 +763,14 @@ impl CrossChainBridge {
     ) -> u64 {
         sender.require_auth();
         
+        if Self::is_denylisted(env.clone(), sender.clone()) || Self::is_denylisted(env.clone(), recipient.clone()) {
+            panic!("Party denylisted");
+        }
+        
+        if !Self::is_recipient_allowed(env.clone(), target_chain, recipient.clone()) {
+            panic!("Recipient not allowed on target chain");
+        }
+        
         let count: u64 = env.storage().instance().get(&BridgeDataKey::MessageCount).unwrap_or(0);
         let message_id = count + 1;
         
@@ -241,4 +813,128 @@ impl CrossChainBridge {
     pub fn get_admin(env: Env) -> Address {
         env.storage().instance().get(&BridgeDataKey::Admin).unwrap()
     }
+
+    /// Fee for `gas` units priced at the chain's configured gas price, plus the chain's settlement fee
+    fn compute_fee(env: Env, gas: u64, chain_id: u32) -> u64 {
+        // Chains without a config are not priced
+        let gas_price = Self::get_chain_config(env.clone(), chain_id)
+            .map(|config| config.gas_price)
+            .unwrap_or(0);
+        
+        let settlement_fee = Self::get_fee_rate_override(env.clone(), chain_id)
+            .unwrap_or_else(|| Self::get_fee_rate(env.clone()));
+        
+        fees::gas_fee(gas, gas_price)
+            .checked_add(settlement_fee)
+            .unwrap_or_else(|| panic!("Fee overflow"))
+    }
+
+    /// Transfer a charged fee to the fee vault when a fee token is set
+    /// Returns the fee token and what the vault received, or `None` when nothing was taken
+    fn collect_fee(env: Env, payer: Address, fee: u64) -> Option<(Address, u64)> {
+        let fee_token = Self::get_fee_token(env.clone())?;
+        
+        let received = fees::collect_fee(&env, &fee_token, &payer, fee as i128) as u64;
+        let min_bps = Self::get_min_fee_received_bps(env.clone()) as u128;
+        if (received as u128) * (fees::FEE_DENOMINATOR as u128) < (fee as u128) * min_bps {
+            panic!("Fee received below minimum");
+        }
+        
+        Some((fee_token, received))
+    }
+
+    /// Drop a pending proof and return its fee to the submitter
+    fn void_proof(env: Env, proof: CrossChainProof) {
+        let submitter: Address = env.storage().instance()
+            .get(&BridgeDataKey::ProofSubmitter(proof.proof_id))
+            .unwrap_or_else(|| panic!("Proof submitter unknown"));
+        
+        env.storage().instance().set(&BridgeDataKey::RefundedProof(proof.proof_id), &true);
+        Self::update_pending_counts(env.clone(), proof.source_chain, proof.target_chain, false);
+        
+        // Only a fee that was actually taken is returned, in the token it was paid in
+        let paid: Option<(Address, u64)> = env.storage().instance().get(&BridgeDataKey::FeePaid(proof.proof_id));
+        if let Some((fee_token, amount)) = paid {
+            fees::release_fee(&env, &fee_token, &submitter, amount as i128);
+        }
+    }
+
+    fn empty_pending_counts(env: Env) -> PendingCounts {
+        PendingCounts {
+            total: 0,
+            by_source_chain: Map::new(&env),
+            by_target_chain: Map::new(&env),
+        }
+    }
+
+    fn update_pending_counts(env: Env, source_chain: u32, target_chain: u32, added: bool) {
+        let mut counts = Self::get_pending_counts(env.clone());
+        Self::adjust_pending(&mut counts, source_chain, target_chain, added);
+        env.storage().instance().set(&BridgeDataKey::PendingCounts, &counts);
+        
+        if !added {
+            Self::advance_oldest_pending(env);
+        }
+    }
+
+    fn is_pending(env: Env, proof_id: u64) -> bool {
+        env.storage().instance()
+            .get::<BridgeDataKey, CrossChainProof>(&BridgeDataKey::CrossChainProof(proof_id))
+            .map_or(false, |proof| !proof.target_verification)
+            && !Self::is_refunded(env, proof_id)
+    }
+
+    /// Move the oldest-pending cursor past proofs that have settled
+    /// Each id is stepped over once over the contract's lifetime, so the cost stays amortized per proof
+    fn advance_oldest_pending(env: Env) {
+        let count: u64 = env.storage().instance().get(&BridgeDataKey::ProofCount).unwrap_or(0);
+        let mut cursor: u64 = env.storage().instance().get(&BridgeDataKey::OldestPending).unwrap_or(1);
+        while cursor <= count && !Self::is_pending(env.clone(), cursor) {
+            cursor += 1;
+        }
+        env.storage().instance().set(&BridgeDataKey::OldestPending, &cursor);
+    }
+
+    /// Bump a persistent entry's TTL so it is not archived while still in force
+    fn extend_persistent_ttl(env: Env, key: &BridgeDataKey) {
+        env.storage().persistent().extend_ttl(key, PERSISTENT_TTL_LEDGERS, PERSISTENT_TTL_LEDGERS);
+    }
+
+    fn adjust_pending(counts: &mut PendingCounts, source_chain: u32, target_chain: u32, added: bool) {
+        let adjust = |count: u64| if added { count + 1 } else { count.saturating_sub(1) };
+        
+        counts.total = adjust(counts.total);
+        Self::adjust_chain(&mut counts.by_source_chain, source_chain, adjust);
+        Self::adjust_chain(&mut counts.by_target_chain, target_chain, adjust);
+    }
+
+    fn adjust_chain(by_chain: &mut Map<u32, u64>, chain_id: u32, adjust: impl Fn(u64) -> u64) {
+        // Chains drop out of the breakdown once nothing is pending on them
+        match adjust(by_chain.get(chain_id).unwrap_or(0)) {
+            0 => { by_chain.remove(chain_id); }
+            count => by_chain.set(chain_id, count),
+        }
+    }
+
+    /// Estimate gas usage for relaying a proof
+    fn estimate_proof_gas(_env: Env, proof_size: u32) -> u64 {
+        // Base gas + gas per byte of proof data
+        21000u64 + (proof_size as u64 * 50)
+    }
+
+    /// Ensure a block has the chain's required confirmation depth below the head
+    fn check_confirmations(env: Env, chain_id: u32, block_number: u64, head_block: u64) {
+        // Chains without a config have no depth requirement
+        let required = Self::get_chain_config(env, chain_id)
+            .map(|config| config.required_confirmations)
+            .unwrap_or(0);
+        
+        if head_block < block_number {
+            panic!("Head block is behind proof block");
+        }
+        
+        if head_block - block_number < required {
+            panic!("Insufficient confirmations");
+        }
+    }
 }
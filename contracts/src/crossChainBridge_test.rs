@@ -1,13 +1,19 @@
 #![cfg(test)]
-use soroban_sdk::{testutils::{Address as _, Ledger as _}, Address, Bytes, Env};
-use crate::crossChainBridge::{CrossChainBridge, BridgeDataKey, ChainConfig, CrossChainProof, BridgeMessage};
+use soroban_sdk::{contract, contractimpl, testutils::{Address as _, Ledger as _}, Address, Bytes, Env, String, Vec};
+use crate::crossChainBridge::{ChainConfig, CrossChainBridge, CrossChainBridgeClient, PendingCounts};
+
+fn create_bridge(env: &Env) -> CrossChainBridgeClient {
+    let contract_id = env.register_contract(None, CrossChainBridge);
+    CrossChainBridgeClient::new(env, &contract_id)
+}
 
 #[test]
 fn test_initialize() {
     let env = Env::default();
+    env.mock_all_auths();
     let admin = Address::generate(&env);
     
-    let contract = CrossChainBridge::new(&env);
+    let contract = create_bridge(&env);
     contract.initialize(&admin);
     
     assert_eq!(contract.get_admin(), admin);
@@ -24,8 +30,9 @@ fn test_initialize() {
 #[test]
 fn test_add_chain_config() {
     let env = Env::default();
+    env.mock_all_auths();
     let admin = Address::generate(&env);
-    let contract = CrossChainBridge::new(&env);
+    let contract = create_bridge(&env);
     contract.initialize(&admin);
     
     let chain_config = ChainConfig {
@@ -34,6 +41,7 @@ fn test_add_chain_config() {
         bridge_address: Address::generate(&env),
         gas_price: 10000000000,
         block_time: 250,
+        required_confirmations: 12,
     };
     
     contract.add_chain_config(&admin, &chain_config);
@@ -42,18 +50,23 @@ fn test_add_chain_config() {
     assert_eq!(retrieved_config.chain_id, 42161);
     assert_eq!(retrieved_config.chain_name, String::from_str(&env, "Arbitrum"));
     assert_eq!(retrieved_config.gas_price, 10000000000);
+    
+    // Configuring a chain also supports it
+    assert!(contract.is_chain_supported(&42161));
+    assert!(contract.get_supported_chains().contains(&42161u32));
 }
 
 #[test]
 fn test_submit_cross_chain_proof() {
     let env = Env::default();
+    env.mock_all_auths();
     let admin = Address::generate(&env);
     let submitter = Address::generate(&env);
-    let contract = CrossChainBridge::new(&env);
+    let contract = create_bridge(&env);
     contract.initialize(&admin);
     
     let proof_data = Bytes::from_slice(&env, b"test proof data");
-    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &submitter);
+    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
     
     assert_eq!(proof_id, 1);
     assert_eq!(contract.get_proof_count(), 1);
@@ -70,15 +83,16 @@ fn test_submit_cross_chain_proof() {
 #[test]
 fn test_verify_source_proof() {
     let env = Env::default();
+    env.mock_all_auths();
     let admin = Address::generate(&env);
     let submitter = Address::generate(&env);
-    let contract = CrossChainBridge::new(&env);
+    let contract = create_bridge(&env);
     contract.initialize(&admin);
     
     let proof_data = Bytes::from_slice(&env, b"test proof data");
-    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &submitter);
+    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
     
-    let verified = contract.verify_source_proof(&admin, &proof_id);
+    let verified = contract.verify_source_proof(&admin, &proof_id, &100);
     assert!(verified);
     
     let proof = contract.get_cross_chain_proof(&proof_id);
@@ -86,22 +100,108 @@ fn test_verify_source_proof() {
     assert!(!proof.target_verification);
 }
 
+#[test]
+fn test_verify_source_proof_with_confirmations() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let submitter = Address::generate(&env);
+    let contract = create_bridge(&env);
+    contract.initialize(&admin);
+    
+    let chain_config = ChainConfig {
+        chain_id: 1,
+        chain_name: String::from_str(&env, "Ethereum"),
+        bridge_address: Address::generate(&env),
+        gas_price: 20000000000,
+        block_time: 12,
+        required_confirmations: 12,
+    };
+    contract.add_chain_config(&admin, &chain_config);
+    
+    let proof_data = Bytes::from_slice(&env, b"test proof data");
+    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
+    
+    // Head exactly 12 blocks above the proof block
+    let verified = contract.verify_source_proof(&admin, &proof_id, &112);
+    assert!(verified);
+    
+    let proof = contract.get_cross_chain_proof(&proof_id);
+    assert!(proof.source_verification);
+    assert_eq!(proof.source_block, 100);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient confirmations")]
+fn test_verify_source_proof_insufficient_confirmations() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let submitter = Address::generate(&env);
+    let contract = create_bridge(&env);
+    contract.initialize(&admin);
+    
+    let chain_config = ChainConfig {
+        chain_id: 1,
+        chain_name: String::from_str(&env, "Ethereum"),
+        bridge_address: Address::generate(&env),
+        gas_price: 20000000000,
+        block_time: 12,
+        required_confirmations: 12,
+    };
+    contract.add_chain_config(&admin, &chain_config);
+    
+    let proof_data = Bytes::from_slice(&env, b"test proof data");
+    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
+    
+    // Proof block is still reorg-able at this head
+    contract.verify_source_proof(&admin, &proof_id, &111);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient confirmations")]
+fn test_verify_target_proof_insufficient_confirmations() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let submitter = Address::generate(&env);
+    let contract = create_bridge(&env);
+    contract.initialize(&admin);
+    
+    let chain_config = ChainConfig {
+        chain_id: 137,
+        chain_name: String::from_str(&env, "Polygon"),
+        bridge_address: Address::generate(&env),
+        gas_price: 30000000000,
+        block_time: 2,
+        required_confirmations: 64,
+    };
+    contract.add_chain_config(&admin, &chain_config);
+    
+    let proof_data = Bytes::from_slice(&env, b"test proof data");
+    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
+    contract.verify_source_proof(&admin, &proof_id, &100);
+    
+    contract.verify_target_proof(&admin, &proof_id, &500, &520);
+}
+
 #[test]
 fn test_verify_target_proof() {
     let env = Env::default();
+    env.mock_all_auths();
     let admin = Address::generate(&env);
     let submitter = Address::generate(&env);
-    let contract = CrossChainBridge::new(&env);
+    let contract = create_bridge(&env);
     contract.initialize(&admin);
     
     let proof_data = Bytes::from_slice(&env, b"test proof data");
-    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &submitter);
+    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
     
     // Verify source first
-    contract.verify_source_proof(&admin, &proof_id);
+    contract.verify_source_proof(&admin, &proof_id, &100);
     
     // Then verify target
-    let verified = contract.verify_target_proof(&admin, &proof_id);
+    let verified = contract.verify_target_proof(&admin, &proof_id, &200, &200);
     assert!(verified);
     
     let proof = contract.get_cross_chain_proof(&proof_id);
@@ -113,25 +213,27 @@ fn test_verify_target_proof() {
 #[should_panic(expected = "Source proof must be verified first")]
 fn test_verify_target_proof_without_source() {
     let env = Env::default();
+    env.mock_all_auths();
     let admin = Address::generate(&env);
     let submitter = Address::generate(&env);
-    let contract = CrossChainBridge::new(&env);
+    let contract = create_bridge(&env);
     contract.initialize(&admin);
     
     let proof_data = Bytes::from_slice(&env, b"test proof data");
-    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &submitter);
+    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
     
     // Try to verify target without source verification
-    contract.verify_target_proof(&admin, &proof_id);
+    contract.verify_target_proof(&admin, &proof_id, &200, &200);
 }
 
 #[test]
 fn test_send_bridge_message() {
     let env = Env::default();
+    env.mock_all_auths();
     let admin = Address::generate(&env);
     let sender = Address::generate(&env);
     let recipient = Address::generate(&env);
-    let contract = CrossChainBridge::new(&env);
+    let contract = create_bridge(&env);
     contract.initialize(&admin);
     
     let data = Bytes::from_slice(&env, b"test message data");
@@ -153,46 +255,41 @@ fn test_send_bridge_message() {
 #[test]
 fn test_unsupported_chain() {
     let env = Env::default();
+    env.mock_all_auths();
     let admin = Address::generate(&env);
     let submitter = Address::generate(&env);
-    let contract = CrossChainBridge::new(&env);
+    let contract = create_bridge(&env);
     contract.initialize(&admin);
     
     let proof_data = Bytes::from_slice(&env, b"test proof data");
     
     // Try to submit proof for unsupported chain
-    let result = std::panic::catch_unwind(|| {
-        contract.submit_cross_chain_proof(&1, &999, &proof_data, &submitter);
-    });
-    
-    assert!(result.is_err());
+    assert!(contract.try_submit_cross_chain_proof(&1, &999, &proof_data, &100, &submitter).is_err());
 }
 
 #[test]
 fn test_unauthorized_access() {
     let env = Env::default();
+    env.mock_all_auths();
     let admin = Address::generate(&env);
     let unauthorized = Address::generate(&env);
-    let contract = CrossChainBridge::new(&env);
+    let contract = create_bridge(&env);
     contract.initialize(&admin);
     
     let proof_data = Bytes::from_slice(&env, b"test proof data");
-    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &admin);
+    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &admin);
     
     // Try to verify proof with unauthorized address
-    let result = std::panic::catch_unwind(|| {
-        contract.verify_source_proof(&unauthorized, &proof_id);
-    });
-    
-    assert!(result.is_err());
+    assert!(contract.try_verify_source_proof(&unauthorized, &proof_id, &100).is_err());
 }
 
 #[test]
 fn test_multiple_proofs() {
     let env = Env::default();
+    env.mock_all_auths();
     let admin = Address::generate(&env);
     let submitter = Address::generate(&env);
-    let contract = CrossChainBridge::new(&env);
+    let contract = create_bridge(&env);
     contract.initialize(&admin);
     
     // Submit multiple proofs
@@ -200,9 +297,9 @@ fn test_multiple_proofs() {
     let proof_data2 = Bytes::from_slice(&env, b"proof data 2");
     let proof_data3 = Bytes::from_slice(&env, b"proof data 3");
     
-    let proof_id1 = contract.submit_cross_chain_proof(&1, &137, &proof_data1, &submitter);
-    let proof_id2 = contract.submit_cross_chain_proof(&137, &56, &proof_data2, &submitter);
-    let proof_id3 = contract.submit_cross_chain_proof(&56, &1, &proof_data3, &submitter);
+    let proof_id1 = contract.submit_cross_chain_proof(&1, &137, &proof_data1, &100, &submitter);
+    let proof_id2 = contract.submit_cross_chain_proof(&137, &56, &proof_data2, &100, &submitter);
+    let proof_id3 = contract.submit_cross_chain_proof(&56, &1, &proof_data3, &100, &submitter);
     
     assert_eq!(proof_id1, 1);
     assert_eq!(proof_id2, 2);
@@ -210,9 +307,9 @@ fn test_multiple_proofs() {
     assert_eq!(contract.get_proof_count(), 3);
     
     // Verify each proof
-    assert!(contract.verify_source_proof(&admin, &proof_id1));
-    assert!(contract.verify_source_proof(&admin, &proof_id2));
-    assert!(contract.verify_source_proof(&admin, &proof_id3));
+    assert!(contract.verify_source_proof(&admin, &proof_id1, &100));
+    assert!(contract.verify_source_proof(&admin, &proof_id2, &100));
+    assert!(contract.verify_source_proof(&admin, &proof_id3, &100));
     
     // Check proof details
     let proof1 = contract.get_cross_chain_proof(&proof_id1);
@@ -231,10 +328,11 @@ fn test_multiple_proofs() {
 #[test]
 fn test_message_nonce_increment() {
     let env = Env::default();
+    env.mock_all_auths();
     let admin = Address::generate(&env);
     let sender = Address::generate(&env);
     let recipient = Address::generate(&env);
-    let contract = CrossChainBridge::new(&env);
+    let contract = create_bridge(&env);
     contract.initialize(&admin);
     
     let data = Bytes::from_slice(&env, b"test message data");
@@ -262,16 +360,17 @@ fn test_message_nonce_increment() {
 #[test]
 fn test_timestamp_functionality() {
     let env = Env::default();
+    env.mock_all_auths();
     let admin = Address::generate(&env);
     let submitter = Address::generate(&env);
-    let contract = CrossChainBridge::new(&env);
+    let contract = create_bridge(&env);
     contract.initialize(&admin);
     
     // Set a specific timestamp
-    env.ledger().set_timestamp(1234567890);
+    env.ledger().with_mut(|li| li.timestamp = 1234567890);
     
     let proof_data = Bytes::from_slice(&env, b"test proof data");
-    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &submitter);
+    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
     
     let proof = contract.get_cross_chain_proof(&proof_id);
     assert_eq!(proof.timestamp, 1234567890);
@@ -281,9 +380,433 @@ fn test_timestamp_functionality() {
     let data = Bytes::from_slice(&env, b"test message data");
     let signature = Bytes::from_slice(&env, b"test signature");
     
-    env.ledger().set_timestamp(1234567900);
+    env.ledger().with_mut(|li| li.timestamp = 1234567900);
     let message_id = contract.send_bridge_message(&1, &137, &recipient, &data, &submitter, &signature);
     
     let message = contract.get_bridge_message(&message_id);
     assert_eq!(message.timestamp, 1234567900);
 }
+
+#[test]
+fn test_fee_estimate_matches_charged_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let submitter = Address::generate(&env);
+    let contract = create_bridge(&env);
+    contract.initialize(&admin);
+    
+    let chain_config = ChainConfig {
+        chain_id: 137,
+        chain_name: String::from_str(&env, "Polygon"),
+        bridge_address: Address::generate(&env),
+        gas_price: 30,
+        block_time: 2,
+        required_confirmations: 0,
+    };
+    contract.add_chain_config(&admin, &chain_config);
+    
+    let proof_data = Bytes::from_slice(&env, b"test proof data");
+    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
+    
+    let proof = contract.get_cross_chain_proof(&proof_id);
+    assert_eq!(proof.fee, proof.gas_used * 30);
+    assert_eq!(contract.get_fee_estimate(&proof.gas_used, &137), proof.fee);
+    
+    // Unconfigured chains are not priced
+    assert_eq!(contract.get_fee_estimate(&proof.gas_used, &56), 0);
+}
+
+#[test]
+fn test_pending_counts() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let submitter = Address::generate(&env);
+    let contract = create_bridge(&env);
+    contract.initialize(&admin);
+    
+    let proof_data = Bytes::from_slice(&env, b"test proof data");
+    let first = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
+    contract.submit_cross_chain_proof(&1, &56, &proof_data, &100, &submitter);
+    contract.submit_cross_chain_proof(&137, &56, &proof_data, &100, &submitter);
+    
+    let counts: PendingCounts = contract.get_pending_counts();
+    assert_eq!(counts.total, 3);
+    assert_eq!(counts.by_source_chain.get(1), Some(2));
+    assert_eq!(counts.by_source_chain.get(137), Some(1));
+    assert_eq!(counts.by_target_chain.get(56), Some(2));
+    assert_eq!(counts.by_target_chain.get(137), Some(1));
+    
+    // Completing a proof takes it out of every count
+    contract.verify_source_proof(&admin, &first, &100);
+    contract.verify_target_proof(&admin, &first, &200, &200);
+    
+    let counts = contract.get_pending_counts();
+    assert_eq!(counts.total, 2);
+    assert_eq!(counts.by_source_chain.get(1), Some(1));
+    assert_eq!(counts.by_target_chain.get(137), None);
+    assert_eq!(counts.by_target_chain.get(56), Some(2));
+}
+
+#[test]
+fn test_refund_stranded_proof_after_chain_removed() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let submitter = Address::generate(&env);
+    let anyone = Address::generate(&env);
+    let contract = create_bridge(&env);
+    contract.initialize(&admin);
+    
+    let proof_data = Bytes::from_slice(&env, b"test proof data");
+    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
+    
+    // While the target chain is supported only the admin may refund
+    assert!(contract.try_refund_proof(&anyone, &proof_id).is_err());
+    
+    contract.remove_supported_chain(&admin, &137);
+    assert!(!contract.get_supported_chains().contains(&137u32));
+    
+    // The stranded proof is refundable immediately, by anyone
+    contract.refund_proof(&anyone, &proof_id);
+    assert!(contract.is_refunded(&proof_id));
+    assert_eq!(contract.get_pending_counts().total, 0);
+    
+    assert!(contract.try_refund_proof(&anyone, &proof_id).is_err());
+    assert!(contract.try_verify_source_proof(&admin, &proof_id, &100).is_err());
+}
+
+#[test]
+fn test_source_reorg_voids_proof_above_new_head() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let submitter = Address::generate(&env);
+    let contract = create_bridge(&env);
+    contract.initialize(&admin);
+    
+    let proof_data = Bytes::from_slice(&env, b"test proof data");
+    let kept = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
+    let reorged = contract.submit_cross_chain_proof(&1, &137, &proof_data, &150, &submitter);
+    let other_chain = contract.submit_cross_chain_proof(&56, &137, &proof_data, &150, &submitter);
+    
+    // Chain 1 reorgs back to block 120, dropping the block-150 deposit; the scan runs in bounded windows
+    let (voided, next) = contract.report_source_reorg(&admin, &1, &120, &0, &1);
+    assert!(voided.is_empty());
+    assert_eq!(next, reorged);
+    let (voided, next) = contract.report_source_reorg(&admin, &1, &120, &next, &10);
+    assert_eq!(voided.len(), 1);
+    assert_eq!(voided.get(0).unwrap(), reorged);
+    assert_eq!(next, 0);
+    
+    assert!(contract.is_refunded(&reorged));
+    assert!(!contract.is_refunded(&kept));
+    assert!(!contract.is_refunded(&other_chain));
+    assert_eq!(contract.get_pending_counts().total, 2);
+    assert!(contract.try_verify_source_proof(&admin, &reorged, &200).is_err());
+}
+
+#[test]
+fn test_fee_rate_override_prices_destination_chain() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let submitter = Address::generate(&env);
+    let contract = create_bridge(&env);
+    contract.initialize(&admin);
+    
+    contract.set_fee_rate(&admin, &500);
+    contract.set_fee_rate_override(&admin, &137, &Some(2_000));
+    
+    let proof_data = Bytes::from_slice(&env, b"test proof data");
+    let overridden = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
+    let global = contract.submit_cross_chain_proof(&1, &56, &proof_data, &100, &submitter);
+    
+    // Neither chain has a gas price configured, so only the settlement fee is charged
+    assert_eq!(contract.get_cross_chain_proof(&overridden).fee, 2_000);
+    assert_eq!(contract.get_cross_chain_proof(&global).fee, 500);
+    
+    // Clearing the override falls back to the global rate
+    contract.set_fee_rate_override(&admin, &137, &None);
+    assert_eq!(contract.get_fee_estimate(&0, &137), 500);
+}
+
+#[test]
+fn test_min_submit_trust_restricts_chain() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let submitter = Address::generate(&env);
+    let contract = create_bridge(&env);
+    contract.initialize(&admin);
+    
+    contract.set_min_submit_trust(&admin, &137, &50);
+    contract.set_submitter_trust(&admin, &submitter, &10);
+    
+    let proof_data = Bytes::from_slice(&env, b"test proof data");
+    assert!(contract.try_submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter).is_err());
+    
+    // Routes avoiding the restricted chain stay open
+    let proof_id = contract.submit_cross_chain_proof(&1, &56, &proof_data, &100, &submitter);
+    assert_eq!(contract.get_cross_chain_proof(&proof_id).target_chain, 56);
+    
+    contract.set_submitter_trust(&admin, &submitter, &50);
+    contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
+}
+
+#[test]
+fn test_is_chain_supported() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract = create_bridge(&env);
+    contract.initialize(&admin);
+    
+    // Seeded chains are supported, as proof submission treats them
+    assert!(contract.is_chain_supported(&137));
+    assert!(!contract.is_chain_supported(&10));
+    
+    // Configuring a chain supports it too
+    contract.add_chain_config(&admin, &ChainConfig {
+        chain_id: 10,
+        chain_name: String::from_str(&env, "Optimism"),
+        bridge_address: Address::generate(&env),
+        gas_price: 30,
+        block_time: 2,
+        required_confirmations: 0,
+    });
+    assert!(contract.is_chain_supported(&10));
+    
+    contract.remove_supported_chain(&admin, &137);
+    assert!(!contract.is_chain_supported(&137));
+    assert!(contract.get_chain_config(&137).is_none());
+}
+
+/// Token that keeps 1% of every transfer, delivering only the remainder
+#[contract]
+pub struct SkimmingToken;
+
+#[contractimpl]
+impl SkimmingToken {
+    pub fn mint(env: Env, to: Address, amount: i128) {
+        let balance = Self::balance(env.clone(), to.clone());
+        env.storage().instance().set(&to, &(balance + amount));
+    }
+
+    pub fn balance(env: Env, id: Address) -> i128 {
+        env.storage().instance().get(&id).unwrap_or(0)
+    }
+
+    pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        let from_balance = Self::balance(env.clone(), from.clone());
+        env.storage().instance().set(&from, &(from_balance - amount));
+        Self::mint(env, to, amount - amount / 100);
+    }
+}
+
+#[test]
+fn test_fee_on_transfer_token_records_net_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let submitter = Address::generate(&env);
+    let contract = create_bridge(&env);
+    contract.initialize(&admin);
+    
+    let token_id = env.register_contract(None, SkimmingToken);
+    let token = SkimmingTokenClient::new(&env, &token_id);
+    token.mint(&submitter, &10_000);
+    contract.set_fee_token(&admin, &token_id);
+    contract.set_fee_rate(&admin, &1_000);
+    
+    // By default the vault must receive the full fee
+    let proof_data = Bytes::from_slice(&env, b"test proof data");
+    assert!(contract.try_submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter).is_err());
+    
+    contract.set_min_fee_received_bps(&admin, &9_800);
+    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
+    
+    assert_eq!(contract.get_cross_chain_proof(&proof_id).fee, 990);
+    assert_eq!(token.balance(&contract.get_fee_vault()), 990);
+}
+
+#[test]
+fn test_refund_of_fee_free_proof_pays_nothing_after_fee_token_set() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let submitter = Address::generate(&env);
+    let payer = Address::generate(&env);
+    let contract = create_bridge(&env);
+    contract.initialize(&admin);
+    
+    let proof_data = Bytes::from_slice(&env, b"test proof data");
+    let free = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
+    
+    let token_id = env.register_contract(None, SkimmingToken);
+    let token = SkimmingTokenClient::new(&env, &token_id);
+    token.mint(&payer, &10_000);
+    contract.set_fee_token(&admin, &token_id);
+    contract.set_fee_rate(&admin, &1_000);
+    contract.set_min_fee_received_bps(&admin, &9_800);
+    let paid = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &payer);
+    let vault = contract.get_fee_vault();
+    assert_eq!(token.balance(&vault), 990);
+    
+    // The fee-free proof takes nothing out of the vault
+    contract.refund_proof(&admin, &free);
+    assert_eq!(token.balance(&submitter), 0);
+    assert_eq!(token.balance(&vault), 990);
+    
+    // The paid one gets back what the vault received
+    contract.refund_proof(&admin, &paid);
+    assert_eq!(token.balance(&vault), 0);
+}
+
+#[test]
+fn test_add_chain_configs_batch() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract = create_bridge(&env);
+    contract.initialize(&admin);
+    
+    let chain_config = |chain_id: u32, chain_name: &str| ChainConfig {
+        chain_id,
+        chain_name: String::from_str(&env, chain_name),
+        bridge_address: Address::generate(&env),
+        gas_price: 1000,
+        block_time: 2,
+        required_confirmations: 12,
+    };
+    
+    let mut chain_configs = Vec::new(&env);
+    chain_configs.push_back(chain_config(1, "Ethereum"));
+    chain_configs.push_back(chain_config(42161, "Arbitrum"));
+    chain_configs.push_back(chain_config(10, "Optimism"));
+    contract.add_chain_configs(&admin, &chain_configs);
+    
+    assert_eq!(contract.get_chain_config(&42161).unwrap().chain_name, String::from_str(&env, "Arbitrum"));
+    assert!(contract.is_chain_supported(&10));
+    assert_eq!(contract.get_supported_chains().len(), 5);
+    
+    // A repeat within the batch rejects the whole import
+    let mut duplicated = Vec::new(&env);
+    duplicated.push_back(chain_config(8453, "Base"));
+    duplicated.push_back(chain_config(8453, "Base"));
+    assert!(contract.try_add_chain_configs(&admin, &duplicated).is_err());
+    assert!(contract.get_chain_config(&8453).is_none());
+    
+    let mut existing = Vec::new(&env);
+    existing.push_back(chain_config(8453, "Base"));
+    existing.push_back(chain_config(42161, "Arbitrum"));
+    assert!(contract.try_add_chain_configs(&admin, &existing).is_err());
+    assert!(!contract.is_chain_supported(&8453));
+}
+
+#[test]
+fn test_get_transfer_proof_matches_simulation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let submitter = Address::generate(&env);
+    let contract = create_bridge(&env);
+    contract.initialize(&admin);
+    
+    let proof_data = Bytes::from_slice(&env, b"test proof data");
+    let (preview_id, preview_hash, preview_fee) = contract.simulate_transfer(&1, &137, &proof_data, &100);
+    assert!(contract.try_get_transfer_proof(&preview_id).is_err());
+    
+    let proof_id = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
+    assert_eq!(proof_id, preview_id);
+    assert_eq!(contract.get_cross_chain_proof(&proof_id).fee, preview_fee);
+    
+    let proof_hash = contract.get_transfer_proof(&proof_id);
+    assert_eq!(proof_hash, preview_hash);
+    assert_eq!(proof_hash, contract.generate_transfer_proof(&proof_id, &1, &137, &proof_data, &100));
+    assert_ne!(proof_hash, contract.generate_transfer_proof(&proof_id, &1, &137, &proof_data, &101));
+}
+
+#[test]
+fn test_recipient_allowlist_restricts_target_chain() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let allowed = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let contract = create_bridge(&env);
+    contract.initialize(&admin);
+    
+    let data = Bytes::from_slice(&env, b"test message data");
+    let signature = Bytes::from_slice(&env, b"test signature");
+    let mut recipients = Vec::new(&env);
+    recipients.push_back(allowed.clone());
+    assert!(contract.try_set_recipient_allowlist(&sender, &137, &Some(recipients.clone())).is_err());
+    contract.set_recipient_allowlist(&admin, &137, &Some(recipients.clone()));
+    assert_eq!(contract.get_recipient_allowlist(&137), Some(recipients));
+    
+    assert!(contract.try_send_bridge_message(&1, &137, &stranger, &data, &sender, &signature).is_err());
+    let message_id = contract.send_bridge_message(&1, &137, &allowed, &data, &sender, &signature);
+    assert_eq!(contract.get_bridge_message(&message_id).recipient, allowed);
+    
+    // Other chains and a cleared list accept any recipient
+    contract.send_bridge_message(&1, &56, &stranger, &data, &sender, &signature);
+    contract.set_recipient_allowlist(&admin, &137, &None);
+    assert!(contract.is_recipient_allowed(&137, &stranger));
+    contract.send_bridge_message(&1, &137, &stranger, &data, &sender, &signature);
+}
+
+#[test]
+fn test_denylisted_party_blocked_from_bridge_messages() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let denied = Address::generate(&env);
+    let contract = create_bridge(&env);
+    contract.initialize(&admin);
+    
+    let data = Bytes::from_slice(&env, b"test message data");
+    let signature = Bytes::from_slice(&env, b"test signature");
+    assert!(contract.try_add_to_denylist(&sender, &denied).is_err());
+    contract.add_to_denylist(&admin, &denied);
+    assert!(contract.is_denylisted(&denied));
+    
+    // Denied as either sender or recipient; everyone else proceeds
+    assert!(contract.try_send_bridge_message(&1, &137, &recipient, &data, &denied, &signature).is_err());
+    assert!(contract.try_send_bridge_message(&1, &137, &denied, &data, &sender, &signature).is_err());
+    contract.send_bridge_message(&1, &137, &recipient, &data, &sender, &signature);
+    
+    contract.remove_from_denylist(&admin, &denied);
+    contract.send_bridge_message(&1, &137, &recipient, &data, &denied, &signature);
+}
+
+#[test]
+fn test_oldest_pending_proof() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let submitter = Address::generate(&env);
+    let contract = create_bridge(&env);
+    contract.initialize(&admin);
+    assert_eq!(contract.get_oldest_pending(), None);
+    
+    let proof_data = Bytes::from_slice(&env, b"test proof data");
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let first = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
+    env.ledger().with_mut(|li| li.timestamp = 1_010);
+    let second = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
+    env.ledger().with_mut(|li| li.timestamp = 1_030);
+    let third = contract.submit_cross_chain_proof(&1, &137, &proof_data, &100, &submitter);
+    assert_eq!(contract.get_oldest_pending(), Some((first, 1_000)));
+    
+    // Completed and refunded proofs drop out of the backlog
+    contract.verify_source_proof(&admin, &first, &100);
+    contract.verify_target_proof(&admin, &first, &200, &200);
+    contract.refund_proof(&admin, &second);
+    assert_eq!(contract.get_oldest_pending(), Some((third, 1_030)));
+}
@@ -0,0 +1,50 @@
+//! Shared fee math for the cross-chain contracts
+//! Both the charging paths and the fee estimate views go through these helpers
+
+use soroban_sdk::{token, Address, Env, U256};
+
+/// Basis-point denominator for percentage fees
+pub const FEE_DENOMINATOR: u32 = 10_000;
+
+/// Default swap fee rate when no chain-specific rate is configured (0.3%)
+pub const DEFAULT_FEE_BPS: u32 = 30;
+
+/// Fee for executing `gas` units at the chain's `gas_price`
+pub fn gas_fee(gas: u64, gas_price: u64) -> u64 {
+    gas.checked_mul(gas_price)
+        .unwrap_or_else(|| panic!("Fee overflow"))
+}
+
+/// Fee for moving `amount` at `rate_bps` basis points
+pub fn amount_fee(env: &Env, amount: &U256, rate_bps: u32) -> U256 {
+    amount
+        .mul(&U256::from_u32(env, rate_bps))
+        .div(&U256::from_u32(env, FEE_DENOMINATOR))
+}
+
+/// Fee vault of the calling contract: the contract itself
+/// Only an address with a contract behind it can authorize moving fees out again, so the vault is not derived
+pub fn fee_vault(env: &Env) -> Address {
+    env.current_contract_address()
+}
+
+/// Move fees out of the calling contract's fee vault
+pub fn release_fee(env: &Env, fee_token: &Address, recipient: &Address, amount: i128) {
+    if amount > 0 {
+        token::Client::new(env, fee_token).transfer(&fee_vault(env), recipient, &amount);
+    }
+}
+
+/// Move a charged fee from `payer` into the calling contract's fee vault
+/// Returns what the vault actually received, which is less than `amount` for fee-on-transfer tokens
+pub fn collect_fee(env: &Env, fee_token: &Address, payer: &Address, amount: i128) -> i128 {
+    if amount <= 0 {
+        return 0;
+    }
+    
+    let token = token::Client::new(env, fee_token);
+    let vault = fee_vault(env);
+    let before = token.balance(&vault);
+    token.transfer(payer, &vault, &amount);
+    token.balance(&vault) - before
+}
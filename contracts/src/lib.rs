@@ -6,25 +6,333 @@ mod crossChainBridge;
 mod chainVerifier;
 mod atomicSwap;
 mod messagePassing;
+mod fees;
+mod capacity;
+#[cfg(test)]
+mod atomicSwap_test;
+#[cfg(test)]
+mod messagePassing_test;
+#[cfg(test)]
+mod chainVerifier_test;
+#[cfg(test)]
+mod crossChainBridge_test;
 
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Bytes, Env, String, Vec};
+use chainVerifier::ChainVerifierClient;
+use soroban_sdk::{contract, contractimpl, contracttype, symbol_short, token, xdr::ToXdr, Address, Bytes, BytesN, Env, String, Symbol, TryFromVal, Val, Vec};
+
+/// Maximum length of a proof's metadata URI
+const MAX_METADATA_URI_LEN: u32 = 256;
+/// Default ledgers a proof's TTL is bumped to when it is read (about 7 days)
+const DEFAULT_PROOF_TTL_EXTENSION: u32 = 120_960;
+/// Upper bound on the configurable TTL extension (about 30 days)
+const MAX_PROOF_TTL_EXTENSION: u32 = 518_400;
+/// Default largest `event_data` a proof may carry, in bytes
+const DEFAULT_MAX_EVENT_DATA_SIZE: u32 = 2_048;
+/// Most proofs a single `issue_proofs_batch` call may issue
+const MAX_ISSUE_BATCH: u32 = 50;
+/// Most proofs a single `verify_proofs_batch` call may verify
+const MAX_VERIFY_BATCH: u32 = 50;
+/// Most leaves a single committed proof batch may hold
+const MAX_BATCH_LEAVES: u32 = 1_024;
+/// Most proofs a single registry root may cover
+const MAX_REGISTRY_ROOT_RANGE: u64 = 1_024;
+/// Stands in for the hash of a deleted proof in a registry root, keeping later leaves in place
+const DELETED_PROOF_LEAF: &[u8] = b"verinode:deleted-proof";
+/// Length of an external block hash a proof may be anchored to
+const ANCHOR_BLOCK_HASH_LEN: u32 = 32;
+/// Default seconds after verification during which it can be challenged (1 day)
+const DEFAULT_DISPUTE_PERIOD: u64 = 86_400;
+/// Seconds the admin has to resolve a dispute before it lapses and the verification stands (7 days)
+const DISPUTE_RESOLUTION_PERIOD: u64 = 604_800;
+/// Most proof ids a single admin expiry sweep may scan
+const MAX_EXPIRY_SWEEP_WINDOW: u32 = 500;
+/// Deepest chain of linked proofs, counting the proof itself
+const MAX_PROOF_CHAIN_DEPTH: u32 = 32;
+/// Most proof ids a single filtered lookup (by issuer or status) scans
+const PROOF_SCAN_WINDOW: u64 = 500;
+/// Most proofs a single `get_latest_proofs` call returns
+const MAX_LATEST_PROOFS: u32 = 100;
 
 #[contracttype]
 pub enum DataKey {
     Proof(u64),
     ProofCount,
     Admin,
+    Verifier(Address),
+    VerifierList,
+    StateCapacity,
+    VerifierKey(Address),
+    ProofAttester(u64),
+    AttestationKey(u64), // proof id -> key its attestation was signed with
+    ProofTtlExtension,
+    ProofRevoked(u64),
+    ProofExpired(u64),
+    ProofValidity,
+    ExpiryCursor,
+    ProofBreakdown,
+    ProofBatch(u64),
+    BatchCount,
+    MaxEventDataSize,
+    IssueFee,
+    FeeToken,
+    Treasury,
+    FeesCollected,
+    VerificationDelegate(Address),
+    VerifierProofCount(Address), // length of the verifier's attestation log
+    VerifierProof(Address, u64), // (verifier, log position from 1) -> proof id, in attestation order
+    ProofLogPosition(u64), // where a proof's current attestation sits in its verifier's log
+    ProofVerifiedAt(u64),
+    DisputePeriod,
+    ProofDispute(u64),
+    DisputeBond,
+    DisputeBondPaid(u64), // (token, amount) a proof's challenger posted
+    PendingAdmin,
+    LinkedVerification(u64),
+    ChainVerifier,
+    RegistryRoot,
+    ProofRejection(u64),
+    AllowedIssuer(Address),
+    IssuerAllowlistEnabled,
+    ProofDeleted(u64), // tombstone left where a proof's data was purged
+    Denylisted(Address),
+}
+
+/// Where a proof is in its lifecycle; expiry is tracked separately
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProofStatus {
+    Pending,
+    Verified,
+    Rejected,
+    Revoked,
+}
+
+/// Off-chain metadata location a proof links to
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MetadataUri {
+    /// The issuer gave no URI
+    Unset,
+    /// Non-empty URI of at most `MAX_METADATA_URI_LEN` bytes
+    Uri(String),
+}
+
+/// External chain block a proof is anchored to, showing it existed by that block
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProofAnchor {
+    Unanchored,
+    /// Chain id and the `ANCHOR_BLOCK_HASH_LEN`-byte hash of the block on it
+    Block(u64, Bytes),
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Proof {
+    pub id: u64,
+    pub issuer: Address,
+    pub event_data: Bytes,
+    pub timestamp: u64,
+    pub status: ProofStatus,
+    pub hash: Bytes,
+    /// Off-chain metadata location, if the issuer gave one
+    pub metadata_uri: MetadataUri,
+    /// Verifier's ed25519 signature over the attestation digest; empty until verified
+    pub verifier_signature: Bytes,
+    /// Proof this one was reissued to correct
+    pub supersedes: Option<u64>,
+    /// Earlier proof this one follows on from in an audit trail
+    pub parent_id: Option<u64>,
+    /// External chain block the proof is anchored to, if any
+    pub anchor: ProofAnchor,
+    /// Time after which the proof can no longer be verified; 0 when it never expires
+    pub expires_at: u64,
+}
+
+/// Proof layout stored before the metadata URI and anchor became explicit enums, with empty values for none
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofV8 {
+    pub id: u64,
+    pub issuer: Address,
+    pub event_data: Bytes,
+    pub timestamp: u64,
+    pub status: ProofStatus,
+    pub hash: Bytes,
+    pub metadata_uri: String,
+    pub verifier_signature: Bytes,
+    pub supersedes: Option<u64>,
+    pub parent_id: Option<u64>,
+    pub anchor_chain_id: Option<u64>,
+    pub anchor_block_hash: Bytes,
+    pub expires_at: u64,
+}
+
+/// Proof layout stored before `parent_id` was added
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofV7 {
+    pub id: u64,
+    pub issuer: Address,
+    pub event_data: Bytes,
+    pub timestamp: u64,
+    pub status: ProofStatus,
+    pub hash: Bytes,
+    pub metadata_uri: String,
+    pub verifier_signature: Bytes,
+    pub supersedes: Option<u64>,
+    pub anchor_chain_id: Option<u64>,
+    pub anchor_block_hash: Bytes,
+    pub expires_at: u64,
+}
+
+/// Proof layout stored before `metadata_uri` was added
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofV1 {
+    pub id: u64,
+    pub issuer: Address,
+    pub event_data: Bytes,
+    pub timestamp: u64,
+    pub verified: bool,
+    pub hash: Bytes,
+}
+
+/// Proof layout stored before `verifier_signature` was added
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofV2 {
+    pub id: u64,
+    pub issuer: Address,
+    pub event_data: Bytes,
+    pub timestamp: u64,
+    pub verified: bool,
+    pub hash: Bytes,
+    pub metadata_uri: String,
+}
+
+/// Proof layout stored before `supersedes` was added
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofV3 {
+    pub id: u64,
+    pub issuer: Address,
+    pub event_data: Bytes,
+    pub timestamp: u64,
+    pub verified: bool,
+    pub hash: Bytes,
+    pub metadata_uri: String,
+    pub verifier_signature: Bytes,
+}
+
+/// Proof layout stored before the external chain anchor was added
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofV4 {
+    pub id: u64,
+    pub issuer: Address,
+    pub event_data: Bytes,
+    pub timestamp: u64,
+    pub verified: bool,
+    pub hash: Bytes,
+    pub metadata_uri: String,
+    pub verifier_signature: Bytes,
+    pub supersedes: Option<u64>,
+}
+
+/// Proof layout stored before per-proof expiry was added
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofV5 {
+    pub id: u64,
+    pub issuer: Address,
+    pub event_data: Bytes,
+    pub timestamp: u64,
+    pub verified: bool,
+    pub hash: Bytes,
+    pub metadata_uri: String,
+    pub verifier_signature: Bytes,
+    pub supersedes: Option<u64>,
+    pub anchor_chain_id: Option<u64>,
+    pub anchor_block_hash: Bytes,
+}
+
+/// Proof layout stored before the verified flag became a status
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofV6 {
     pub id: u64,
     pub issuer: Address,
     pub event_data: Bytes,
     pub timestamp: u64,
     pub verified: bool,
     pub hash: Bytes,
+    pub metadata_uri: String,
+    pub verifier_signature: Bytes,
+    pub supersedes: Option<u64>,
+    pub anchor_chain_id: Option<u64>,
+    pub anchor_block_hash: Bytes,
+    pub expires_at: u64,
+}
+
+/// Number of proofs in each lifecycle state
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofBreakdown {
+    pub verified: u64,
+    pub unverified: u64,
+    pub revoked: u64,
+    pub expired: u64,
+}
+
+/// Time-bounded grant letting `delegate` authorize verifications for a verifier
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VerificationDelegation {
+    pub delegate: Address,
+    pub expires_at: u64,
+}
+
+/// Where a proof was verified cross-chain: a result held by a chainVerifier contract
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChainVerificationRef {
+    pub chain_verifier: Address,
+    pub chain_id: u32,
+    pub proof_id: u64, // id under which the chainVerifier recorded the result
+}
+
+/// Open challenge against a proof's verification
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofDispute {
+    pub challenger: Address,
+    pub evidence: Bytes,
+    pub opened_at: u64,
+}
+
+/// Merkle root over the hashes of a range of proofs, published for external anchoring
+/// Hashed the same way as a committed proof batch, with each proof's `hash` as a leaf in id order
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistryRoot {
+    pub root: BytesN<32>,
+    pub from_id: u64,
+    pub to_id: u64,
+    pub published_at: u64,
+}
+
+/// Proofs committed together under a single merkle root
+/// Leaves hash as sha256(0x00 || leaf) and inner nodes as sha256(0x01 || left || right);
+/// a node without a sibling is carried up to the next level unchanged
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofBatch {
+    pub batch_id: u64,
+    pub issuer: Address,
+    pub root: BytesN<32>,
+    pub leaf_count: u32,
+    pub timestamp: u64,
 }
 
 #[contracttype]
@@ -54,76 +362,2054 @@ impl VerinodeContract {
         env.storage().instance().set(&DataKey::ProofCount, &0u64);
     }
 
+    /// Nominate a new admin; the change only takes effect once they accept, so a mistyped address can't lock the contract
+    /// A later nomination replaces an earlier one that hasn't been accepted yet
+    pub fn transfer_admin(env: Env, current_admin: Address, new_admin: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if current_admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        current_admin.require_auth();
+        env.storage().instance().set(&DataKey::PendingAdmin, &new_admin);
+    }
+
+    /// Accept a pending admin nomination, replacing the current admin
+    pub fn accept_admin(env: Env, new_admin: Address) {
+        let pending = Self::get_pending_admin(env.clone())
+            .unwrap_or_else(|| panic!("No pending admin"));
+        
+        if new_admin != pending {
+            panic!("Not authorized");
+        }
+        
+        new_admin.require_auth();
+        
+        let old_admin = Self::get_admin(env.clone());
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+        
+        env.events().publish(
+            (symbol_short!("admin"), symbol_short!("changed")),
+            (old_admin, new_admin)
+        );
+    }
+
+    /// Get the admin nominated by `transfer_admin` and not yet accepted, if any
+    pub fn get_pending_admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::PendingAdmin)
+    }
+
     /// Issue a new cryptographic proof
+    /// An anchor ties the proof to an external chain's block hash, showing it existed by that block
+    /// A `ttl` in seconds stops the proof being verified once it has passed; none or 0 never expires
     pub fn issue_proof(
         env: Env,
         issuer: Address,
         event_data: Bytes,
         hash: Bytes,
+        metadata_uri: Option<String>,
+        anchor_chain_id: Option<u64>,
+        anchor_block_hash: Option<Bytes>,
+        ttl: Option<u64>,
+    ) -> u64 {
+        issuer.require_auth();
+        Self::require_allowed_issuer(env.clone(), &issuer);
+        
+        if let Some(uri) = &metadata_uri {
+            if uri.len() == 0 || uri.len() > MAX_METADATA_URI_LEN {
+                panic!("Invalid metadata URI");
+            }
+        }
+        
+        let anchor = match (anchor_chain_id, anchor_block_hash) {
+            (None, None) => ProofAnchor::Unanchored,
+            (Some(chain_id), Some(block_hash)) => {
+                if block_hash.len() != ANCHOR_BLOCK_HASH_LEN {
+                    panic!("Invalid anchor block hash length");
+                }
+                ProofAnchor::Block(chain_id, block_hash)
+            }
+            _ => panic!("Anchor chain id and block hash must be set together"),
+        };
+        
+        let expires_at = match ttl {
+            None | Some(0) => 0,
+            Some(ttl) => env.ledger().timestamp().saturating_add(ttl),
+        };
+        
+        let metadata_uri = match metadata_uri {
+            Some(uri) => MetadataUri::Uri(uri),
+            None => MetadataUri::Unset,
+        };
+        Self::create_proof(env, issuer, event_data, hash, metadata_uri, None, None, anchor, expires_at)
+    }
+
+    /// Issue a proof that follows on from an existing one, e.g. an update to a prior attestation
+    pub fn issue_linked_proof(env: Env, issuer: Address, event_data: Bytes, hash: Bytes, parent_id: u64) -> u64 {
+        issuer.require_auth();
+        Self::require_allowed_issuer(env.clone(), &issuer);
+        
+        if Self::load_proof(env.clone(), parent_id).is_none() {
+            panic!("Parent proof not found");
+        }
+        
+        // Parents always predate their children, so a bounded depth also keeps chain walks bounded
+        if Self::get_proof_chain(env.clone(), parent_id).len() >= MAX_PROOF_CHAIN_DEPTH {
+            panic!("Proof chain too deep");
+        }
+        
+        Self::create_proof(env, issuer, event_data, hash, MetadataUri::Unset, None, Some(parent_id), ProofAnchor::Unanchored, 0)
+    }
+
+    /// Get a proof followed by its parents up to the root of its chain
+    /// The walk stops early at a parent that has been deleted
+    pub fn get_proof_chain(env: Env, proof_id: u64) -> Vec<Proof> {
+        let mut chain = Vec::new(&env);
+        let mut next = Some(proof_id);
+        
+        while let Some(id) = next {
+            if chain.len() >= MAX_PROOF_CHAIN_DEPTH {
+                break;
+            }
+            let proof = match Self::load_proof(env.clone(), id) {
+                Some(proof) => proof,
+                None if id == proof_id => panic!("Proof not found"),
+                None => break,
+            };
+            next = proof.parent_id;
+            chain.push_back(proof);
+        }
+        
+        chain
+    }
+
+    /// Revoke an unverified proof and issue its correction in one step
+    /// The new proof keeps the old one's metadata URI and records which proof it supersedes
+    pub fn reissue_proof(env: Env, issuer: Address, old_proof_id: u64, new_event_data: Bytes, new_hash: Bytes) -> u64 {
+        let old = Self::load_proof(env.clone(), old_proof_id)
+            .unwrap_or_else(|| panic!("Proof not found"));
+        
+        if issuer != old.issuer {
+            panic!("Not authorized");
+        }
+        
+        issuer.require_auth();
+        Self::require_allowed_issuer(env.clone(), &issuer);
+        Self::require_live(env.clone(), old_proof_id);
+        
+        if old.status == ProofStatus::Verified {
+            panic!("Verified proofs cannot be reissued");
+        }
+        
+        Self::mark_revoked(env.clone(), &old, issuer.clone());
+        Self::create_proof(env, issuer, new_event_data, new_hash, old.metadata_uri, Some(old_proof_id), old.parent_id, ProofAnchor::Unanchored, old.expires_at)
+    }
+
+    /// Store a new unverified proof, charging the issue fee
+    fn create_proof(
+        env: Env,
+        issuer: Address,
+        event_data: Bytes,
+        hash: Bytes,
+        metadata_uri: MetadataUri,
+        supersedes: Option<u64>,
+        parent_id: Option<u64>,
+        anchor: ProofAnchor,
+        expires_at: u64,
     ) -> u64 {
+        let count: u64 = env.storage().instance().get(&DataKey::ProofCount).unwrap_or(0);
+        let proof_id = count.checked_add(1).unwrap_or_else(|| panic!("proof id space exhausted"));
+        
+        Self::store_new_proof(env.clone(), proof_id, issuer, event_data, hash, metadata_uri, supersedes, parent_id, anchor, expires_at);
+        
+        let mut breakdown = Self::get_proof_breakdown(env.clone());
+        breakdown.unverified += 1;
+        env.storage().instance().set(&DataKey::ProofCount, &proof_id);
+        env.storage().instance().set(&DataKey::ProofBreakdown, &breakdown);
+        
+        proof_id
+    }
+
+    /// Issue several proofs in one call, returning their ids in order
+    /// Each entry is `(event_data, hash)`; the proof count and breakdown are written once for the whole batch
+    pub fn issue_proofs_batch(env: Env, issuer: Address, entries: Vec<(Bytes, Bytes)>) -> Vec<u64> {
         issuer.require_auth();
+        Self::require_allowed_issuer(env.clone(), &issuer);
+        
+        if entries.is_empty() {
+            panic!("Empty batch");
+        }
+        if entries.len() > MAX_ISSUE_BATCH {
+            panic!("Batch too large");
+        }
         
         let count: u64 = env.storage().instance().get(&DataKey::ProofCount).unwrap_or(0);
-        let proof_id = count + 1;
+        // Checking the last id up front keeps every id in the batch in range
+        let new_count = count.checked_add(entries.len() as u64).unwrap_or_else(|| panic!("proof id space exhausted"));
+        let mut proof_ids = Vec::new(&env);
+        
+        for (event_data, hash) in entries.iter() {
+            let proof_id = count + proof_ids.len() as u64 + 1;
+            Self::store_new_proof(env.clone(), proof_id, issuer.clone(), event_data, hash, MetadataUri::Unset, None, None, ProofAnchor::Unanchored, 0);
+            proof_ids.push_back(proof_id);
+        }
+        
+        let mut breakdown = Self::get_proof_breakdown(env.clone());
+        breakdown.unverified += proof_ids.len() as u64;
+        env.storage().instance().set(&DataKey::ProofCount, &new_count);
+        env.storage().instance().set(&DataKey::ProofBreakdown, &breakdown);
+        
+        proof_ids
+    }
+
+    /// Validate and store a new unverified proof under `proof_id`, charging the issue fee
+    /// Callers account for it in the proof count and breakdown
+    fn store_new_proof(
+        env: Env,
+        proof_id: u64,
+        issuer: Address,
+        event_data: Bytes,
+        hash: Bytes,
+        metadata_uri: MetadataUri,
+        supersedes: Option<u64>,
+        parent_id: Option<u64>,
+        anchor: ProofAnchor,
+        expires_at: u64,
+    ) {
+        Self::validate_event_data(env.clone(), &event_data, &hash);
         
         let proof = Proof {
             id: proof_id,
             issuer: issuer.clone(),
             event_data: event_data.clone(),
             timestamp: env.ledger().timestamp(),
-            verified: false,
+            status: ProofStatus::Pending,
             hash: hash.clone(),
+            metadata_uri,
+            verifier_signature: Bytes::new(&env),
+            supersedes,
+            parent_id,
+            anchor,
+            expires_at,
         };
         
-        env.storage().instance().set(&DataKey::Proof(proof_id), &proof);
-        env.storage().instance().set(&DataKey::ProofCount, &proof_id);
+        Self::collect_issue_fee(env.clone(), &issuer);
+        Self::save_proof(env.clone(), &proof);
         
-        proof_id
+        env.events().publish(
+            (symbol_short!("issued"), issuer),
+            (proof_id, hash)
+        );
+    }
+
+    /// Check event data fits on-chain and that `hash` commits to it
+    fn validate_event_data(env: Env, event_data: &Bytes, hash: &Bytes) {
+        // Larger payloads belong off-chain, referenced through `metadata_uri`
+        if event_data.len() > Self::get_max_event_data_size(env.clone()) {
+            panic!("Event data too large: store it off-chain and set metadata_uri");
+        }
+        
+        // The stored hash must be a commitment to the stored data, not whatever the issuer claims
+        let expected: Bytes = env.crypto().sha256(event_data).into();
+        if *hash != expected {
+            panic!("Hash does not match event data");
+        }
+    }
+
+    /// Correct a pending proof's event data in place (original issuer only)
+    /// Unlike `reissue_proof` the proof keeps its id; once reviewed it can no longer change
+    pub fn amend_proof(env: Env, issuer: Address, proof_id: u64, new_event_data: Bytes, new_hash: Bytes) {
+        let mut proof = Self::load_proof(env.clone(), proof_id)
+            .unwrap_or_else(|| panic!("Proof not found"));
+        
+        if issuer != proof.issuer {
+            panic!("Not authorized");
+        }
+        
+        issuer.require_auth();
+        
+        // Amending rewrites what was issued, so it is held to the same issuer rules as issuing
+        Self::require_allowed_issuer(env.clone(), &issuer);
+        Self::require_live(env.clone(), proof_id);
+        if proof.status != ProofStatus::Pending {
+            panic!("Only pending proofs can be amended");
+        }
+        
+        Self::validate_event_data(env.clone(), &new_event_data, &new_hash);
+        
+        let old_hash = proof.hash;
+        proof.event_data = new_event_data;
+        proof.hash = new_hash.clone();
+        Self::save_proof(env.clone(), &proof);
+        
+        env.events().publish(
+            (symbol_short!("proof"), symbol_short!("amended"), proof_id),
+            (old_hash, new_hash)
+        );
     }
 
-    /// Verify a proof
-    pub fn verify_proof(env: Env, admin: Address, proof_id: u64) -> bool {
+    /// Verify a proof (admin or a registered verifier)
+    /// The verifier signs the proof's attestation digest with its registered key
+    pub fn verify_proof(env: Env, verifier: Address, proof_id: u64, signature: BytesN<64>) -> bool {
         let stored_admin: Address = env.storage().instance()
             .get(&DataKey::Admin)
             .unwrap_or_else(|| panic!("Admin not found"));
         
-        if admin != stored_admin {
+        if verifier != stored_admin && !Self::is_verifier(env.clone(), verifier.clone()) {
             panic!("Not authorized");
         }
         
-        admin.require_auth();
+        // A live delegate authorizes in the verifier's place; the attestation is still the verifier's
+        match Self::get_verification_delegate(env.clone(), verifier.clone()) {
+            Some(delegation) if delegation.expires_at > env.ledger().timestamp() => delegation.delegate.require_auth(),
+            _ => verifier.require_auth(),
+        }
         
-        let mut proof: Proof = env.storage().instance()
-            .get(&DataKey::Proof(proof_id))
+        let mut proof = Self::load_proof(env.clone(), proof_id)
             .unwrap_or_else(|| panic!("Proof not found"));
         
-        proof.verified = true;
-        env.storage().instance().set(&DataKey::Proof(proof_id), &proof);
+        Self::require_live(env.clone(), proof_id);
+        
+        if proof.status == ProofStatus::Rejected {
+            panic!("Proof rejected");
+        }
+        
+        if Self::get_proof_dispute(env.clone(), proof_id).is_some() {
+            panic!("Proof under dispute");
+        }
+        
+        let public_key = Self::get_verifier_key(env.clone(), verifier.clone())
+            .unwrap_or_else(|| panic!("Verifier key not set"));
+        
+        Self::record_verification(env, &verifier, &public_key, &mut proof, signature);
         
         true
     }
 
-    /// Get proof details
-    pub fn get_proof(env: Env, proof_id: u64) -> Proof {
-        env.storage().instance()
-            .get(&DataKey::Proof(proof_id))
-            .unwrap_or_else(|| panic!("Proof not found"))
+    /// Check `signature` against the verifier's key and mark the proof verified under it
+    fn record_verification(env: Env, verifier: &Address, public_key: &BytesN<32>, proof: &mut Proof, signature: BytesN<64>) {
+        // Panics if the signature does not match the verifier's key
+        let digest = Self::attestation_digest(env.clone(), proof);
+        env.crypto().ed25519_verify(public_key, &digest.into(), &signature);
+        
+        if proof.status == ProofStatus::Pending {
+            let mut breakdown = Self::get_proof_breakdown(env.clone());
+            breakdown.unverified -= 1;
+            breakdown.verified += 1;
+            env.storage().instance().set(&DataKey::ProofBreakdown, &breakdown);
+        }
+        
+        proof.status = ProofStatus::Verified;
+        proof.verifier_signature = signature.into();
+        Self::save_proof(env.clone(), proof);
+        env.storage().persistent().set(&DataKey::ProofAttester(proof.id), verifier);
+        env.storage().persistent().set(&DataKey::AttestationKey(proof.id), public_key);
+        env.storage().persistent().set(&DataKey::ProofVerifiedAt(proof.id), &env.ledger().timestamp());
+        Self::extend_proof_entries_ttl(env.clone(), proof.id);
+        Self::index_verified_proof(env.clone(), verifier, proof.id);
+        
+        env.events().publish(
+            (symbol_short!("verified"), verifier.clone()),
+            (proof.id, proof.hash.clone())
+        );
     }
 
-    /// Get all proofs for an issuer
-    pub fn get_proofs_by_issuer(env: Env, issuer: Address) -> Vec<Proof> {
-        let count: u64 = env.storage().instance().get(&DataKey::ProofCount).unwrap_or(0);
-        let mut proofs = Vec::new(&env);
+    /// Verify several pending proofs in one call (admin only), returning a success flag per id
+    /// `signatures[i]` is the admin's attestation of `proof_ids[i]`, checked as verify_proof checks it;
+    /// a signature that does not match fails the whole batch.
+    /// Missing, deleted, revoked, expired, rejected, disputed and already verified proofs are skipped
+    pub fn verify_proofs_batch(env: Env, admin: Address, proof_ids: Vec<u64>, signatures: Vec<BytesN<64>>) -> Vec<bool> {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
         
-        for i in 1..=count {
-            if let Some(proof) = env.storage().instance().get::<DataKey, Proof>(&DataKey::Proof(i)) {
-                if proof.issuer == issuer {
-                    proofs.push_back(proof);
-                }
-            }
+        if admin != stored_admin {
+            panic!("Not authorized");
         }
         
-        proofs
+        admin.require_auth();
+        
+        if proof_ids.len() > MAX_VERIFY_BATCH {
+            panic!("Batch too large");
+        }
+        
+        if signatures.len() != proof_ids.len() {
+            panic!("Signature count mismatch");
+        }
+        
+        let public_key = Self::get_verifier_key(env.clone(), admin.clone())
+            .unwrap_or_else(|| panic!("Verifier key not set"));
+        
+        let mut results = Vec::new(&env);
+        let mut verified: u64 = 0;
+        
+        for (proof_id, signature) in proof_ids.iter().zip(signatures.iter()) {
+            let mut proof = match Self::load_proof(env.clone(), proof_id) {
+                Some(proof) => proof,
+                None => {
+                    results.push_back(false);
+                    continue;
+                }
+            };
+            
+            if proof.status != ProofStatus::Pending
+                || Self::is_proof_revoked(env.clone(), proof_id)
+                || Self::is_expired(env.clone(), proof_id)
+                || Self::get_proof_dispute(env.clone(), proof_id).is_some()
+            {
+                results.push_back(false);
+                continue;
+            }
+            
+            Self::record_verification(env.clone(), &admin, &public_key, &mut proof, signature);
+            verified += 1;
+            results.push_back(true);
+        }
+        
+        env.events().publish(
+            (symbol_short!("proof"), Symbol::new(&env, "batch_verified")),
+            (admin, verified)
+        );
+        
+        results
+    }
+
+    /// Reject a pending proof, recording why (admin only)
+    pub fn reject_proof(env: Env, admin: Address, proof_id: u64, reason: String) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let mut proof = Self::load_proof(env.clone(), proof_id)
+            .unwrap_or_else(|| panic!("Proof not found"));
+        
+        Self::require_live(env.clone(), proof_id);
+        
+        if proof.status != ProofStatus::Pending {
+            panic!("Only pending proofs can be rejected");
+        }
+        
+        if reason.len() == 0 {
+            panic!("Rejection reason required");
+        }
+        
+        proof.status = ProofStatus::Rejected;
+        Self::save_proof(env.clone(), &proof);
+        env.storage().persistent().set(&DataKey::ProofRejection(proof_id), &reason);
+        Self::extend_persistent_ttl(env.clone(), &DataKey::ProofRejection(proof_id));
+        
+        env.events().publish(
+            (symbol_short!("proof"), symbol_short!("rejected"), proof_id),
+            reason
+        );
+    }
+
+    /// Get the reason a proof was rejected, if it was
+    pub fn get_rejection_reason(env: Env, proof_id: u64) -> Option<String> {
+        env.storage().persistent().get(&DataKey::ProofRejection(proof_id))
+    }
+
+    /// Set how many seconds after verification it can still be challenged
+    pub fn set_dispute_period(env: Env, admin: Address, seconds: u64) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::DisputePeriod, &seconds);
+    }
+
+    /// Get how many seconds after verification it can still be challenged
+    pub fn get_dispute_period(env: Env) -> u64 {
+        env.storage().instance().get(&DataKey::DisputePeriod).unwrap_or(DEFAULT_DISPUTE_PERIOD)
+    }
+
+    /// Set the bond, in the fee token, a challenger posts to dispute a verification
+    pub fn set_dispute_bond(env: Env, admin: Address, bond: i128) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        if bond < 0 {
+            panic!("Invalid dispute bond");
+        }
+        if bond > 0 && Self::get_fee_token(env.clone()).is_none() {
+            panic!("Fee token not set");
+        }
+        
+        env.storage().instance().set(&DataKey::DisputeBond, &bond);
+    }
+
+    /// Get the bond a challenger posts to dispute a verification
+    pub fn get_dispute_bond(env: Env) -> i128 {
+        env.storage().instance().get(&DataKey::DisputeBond).unwrap_or(0)
+    }
+
+    /// Challenge a proof's verification with counter-evidence while its dispute period is open
+    /// The proof stops counting as verified until the admin resolves the dispute or it lapses.
+    /// Challengers post the dispute bond; while no bond is set only verifiers may challenge
+    pub fn challenge_proof(env: Env, challenger: Address, proof_id: u64, evidence: Bytes) {
+        challenger.require_auth();
+        
+        let proof = Self::load_proof(env.clone(), proof_id)
+            .unwrap_or_else(|| panic!("Proof not found"));
+        
+        Self::require_live(env.clone(), proof_id);
+        
+        if proof.status != ProofStatus::Verified {
+            panic!("Proof not verified");
+        }
+        
+        Self::settle_lapsed_dispute(env.clone(), proof_id);
+        if Self::get_proof_dispute(env.clone(), proof_id).is_some() {
+            panic!("Proof already disputed");
+        }
+        
+        if evidence.is_empty() {
+            panic!("Evidence required");
+        }
+        
+        if Self::is_verification_final(env.clone(), proof_id) {
+            panic!("Dispute period over");
+        }
+        
+        let bond = Self::get_dispute_bond(env.clone());
+        if bond > 0 {
+            let fee_token = Self::get_fee_token(env.clone())
+                .unwrap_or_else(|| panic!("Fee token not set"));
+            let token = token::Client::new(&env, &fee_token);
+            let before = token.balance(&env.current_contract_address());
+            token.transfer(&challenger, &env.current_contract_address(), &bond);
+            if token.balance(&env.current_contract_address()) - before < bond {
+                panic!("Dispute bond underpaid");
+            }
+            env.storage().persistent().set(&DataKey::DisputeBondPaid(proof_id), &(fee_token, bond));
+            Self::extend_persistent_ttl(env.clone(), &DataKey::DisputeBondPaid(proof_id));
+        } else if !Self::is_verifier(env.clone(), challenger.clone()) {
+            panic!("Not authorized");
+        }
+        
+        let dispute = ProofDispute {
+            challenger: challenger.clone(),
+            evidence,
+            opened_at: env.ledger().timestamp(),
+        };
+        env.storage().persistent().set(&DataKey::ProofDispute(proof_id), &dispute);
+        Self::extend_persistent_ttl(env.clone(), &DataKey::ProofDispute(proof_id));
+        
+        env.events().publish(
+            (symbol_short!("proof"), symbol_short!("disputed"), proof_id),
+            challenger
+        );
+    }
+
+    /// Close a dispute; upholding it overturns the verification and returns the bond,
+    /// rejecting it restores the verification and forfeits the bond to the treasury
+    pub fn resolve_dispute(env: Env, admin: Address, proof_id: u64, upheld: bool) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let dispute = Self::get_proof_dispute(env.clone(), proof_id)
+            .unwrap_or_else(|| panic!("Dispute not found"));
+        env.storage().persistent().remove(&DataKey::ProofDispute(proof_id));
+        Self::release_dispute_bond(env.clone(), proof_id, if upheld { Some(dispute.challenger) } else { None });
+        
+        let mut proof = Self::load_proof(env.clone(), proof_id)
+            .unwrap_or_else(|| panic!("Proof not found"));
+        
+        // An overturned proof goes back to pending and can be verified afresh
+        if upheld && proof.status == ProofStatus::Verified && !Self::is_proof_revoked(env.clone(), proof_id) && !Self::is_proof_expired(env.clone(), proof_id) {
+            let mut breakdown = Self::get_proof_breakdown(env.clone());
+            breakdown.verified -= 1;
+            breakdown.unverified += 1;
+            env.storage().instance().set(&DataKey::ProofBreakdown, &breakdown);
+            
+            proof.status = ProofStatus::Pending;
+            proof.verifier_signature = Bytes::new(&env);
+            Self::save_proof(env.clone(), &proof);
+            env.storage().persistent().remove(&DataKey::ProofAttester(proof_id));
+            env.storage().persistent().remove(&DataKey::AttestationKey(proof_id));
+            env.storage().persistent().remove(&DataKey::ProofVerifiedAt(proof_id));
+        }
+        
+        env.events().publish(
+            (symbol_short!("proof"), Symbol::new(&env, "dispute_resolved"), proof_id),
+            upheld
+        );
+    }
+
+    /// Get the open dispute against a proof, if any; lapsed disputes no longer count
+    pub fn get_proof_dispute(env: Env, proof_id: u64) -> Option<ProofDispute> {
+        env.storage().persistent()
+            .get::<DataKey, ProofDispute>(&DataKey::ProofDispute(proof_id))
+            .filter(|dispute| !Self::is_dispute_lapsed(&env, dispute))
+    }
+
+    /// Close a dispute left unresolved past the resolution period, returning the challenger's bond
+    /// Anyone may call this; the verification stands
+    pub fn close_lapsed_dispute(env: Env, proof_id: u64) {
+        if !Self::settle_lapsed_dispute(env, proof_id) {
+            panic!("No lapsed dispute");
+        }
+    }
+
+    fn settle_lapsed_dispute(env: Env, proof_id: u64) -> bool {
+        let dispute: ProofDispute = match env.storage().persistent().get(&DataKey::ProofDispute(proof_id)) {
+            Some(dispute) => dispute,
+            None => return false,
+        };
+        if !Self::is_dispute_lapsed(&env, &dispute) {
+            return false;
+        }
+        
+        env.storage().persistent().remove(&DataKey::ProofDispute(proof_id));
+        Self::release_dispute_bond(env.clone(), proof_id, Some(dispute.challenger.clone()));
+        
+        env.events().publish(
+            (symbol_short!("proof"), Symbol::new(&env, "dispute_lapsed"), proof_id),
+            dispute.challenger
+        );
+        true
+    }
+
+    fn is_dispute_lapsed(env: &Env, dispute: &ProofDispute) -> bool {
+        env.ledger().timestamp() > dispute.opened_at.saturating_add(DISPUTE_RESOLUTION_PERIOD)
+    }
+
+    /// Pay out the bond posted against a proof, if any; `None` forfeits it to the treasury
+    fn release_dispute_bond(env: Env, proof_id: u64, recipient: Option<Address>) {
+        let key = DataKey::DisputeBondPaid(proof_id);
+        let (bond_token, bond): (Address, i128) = match env.storage().persistent().get(&key) {
+            Some(paid) => paid,
+            None => return,
+        };
+        
+        let recipient = recipient.unwrap_or_else(|| {
+            Self::get_treasury(env.clone()).unwrap_or_else(|| panic!("Treasury not set"))
+        });
+        token::Client::new(&env, &bond_token).transfer(&env.current_contract_address(), &recipient, &bond);
+        env.storage().instance().remove(&key);
+    }
+
+    /// Check whether a proof's verification is past its dispute period and undisputed
+    pub fn is_verification_final(env: Env, proof_id: u64) -> bool {
+        let verified_at: Option<u64> = env.storage().persistent().get(&DataKey::ProofVerifiedAt(proof_id));
+        
+        match verified_at {
+            Some(verified_at) => {
+                Self::get_proof_dispute(env.clone(), proof_id).is_none()
+                    && env.ledger().timestamp() > verified_at.saturating_add(Self::get_dispute_period(env.clone()))
+            }
+            // Verified before dispute periods existed, so already final
+            None => Self::load_proof(env, proof_id).map(|proof| proof.status == ProofStatus::Verified).unwrap_or(false),
+        }
+    }
+
+    /// Get the verifier whose attestation a proof carries, if it is verified
+    pub fn get_proof_verifier(env: Env, proof_id: u64) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::ProofAttester(proof_id))
+    }
+
+    /// Get up to `limit` proofs attested by `verifier`, in attestation order, reading its log from position `start` on
+    /// Scans at most a fixed window of log entries; returns the proofs and the position to resume from,
+    /// which is 0 once the log has been read to the end
+    pub fn get_proofs_verified_by(env: Env, verifier: Address, start: u64, limit: u32) -> (Vec<Proof>, u64) {
+        let count: u64 = env.storage().persistent()
+            .get(&DataKey::VerifierProofCount(verifier.clone()))
+            .unwrap_or(0);
+        let mut proofs = Vec::new(&env);
+        
+        let mut position = start.max(1);
+        let end = count.min(position.saturating_add(PROOF_SCAN_WINDOW - 1));
+        while position <= end && proofs.len() < limit {
+            let key = DataKey::VerifierProof(verifier.clone(), position);
+            if let Some(proof_id) = env.storage().persistent().get::<DataKey, u64>(&key) {
+                Self::extend_persistent_ttl(env.clone(), &key);
+                
+                // Skip entries a later attestation of the same proof, by anyone, has superseded
+                let current = Self::get_proof_verifier(env.clone(), proof_id) == Some(verifier.clone())
+                    && env.storage().persistent().get::<DataKey, u64>(&DataKey::ProofLogPosition(proof_id)) == Some(position);
+                if current {
+                    if let Some(proof) = Self::load_proof(env.clone(), proof_id) {
+                        proofs.push_back(proof);
+                    }
+                }
+            }
+            position += 1;
+        }
+        
+        let next = if position > count { 0 } else { position };
+        (proofs, next)
+    }
+
+    /// Append a proof to its verifier's attestation log, one storage entry per attestation
+    fn index_verified_proof(env: Env, verifier: &Address, proof_id: u64) {
+        let count_key = DataKey::VerifierProofCount(verifier.clone());
+        let position: u64 = env.storage().persistent().get(&count_key).unwrap_or(0) + 1;
+        let entry_key = DataKey::VerifierProof(verifier.clone(), position);
+        let position_key = DataKey::ProofLogPosition(proof_id);
+        
+        env.storage().persistent().set(&entry_key, &proof_id);
+        env.storage().persistent().set(&count_key, &position);
+        env.storage().persistent().set(&position_key, &position);
+        for key in [count_key, entry_key, position_key] {
+            Self::extend_persistent_ttl(env.clone(), &key);
+        }
+    }
+
+    /// Bump a persistent entry's TTL by the configured proof TTL extension
+    fn extend_persistent_ttl(env: Env, key: &DataKey) {
+        let extension = Self::get_proof_ttl_extension(env.clone());
+        if extension > 0 && env.storage().persistent().has(key) {
+            env.storage().persistent().extend_ttl(key, extension, extension);
+        }
+    }
+
+    /// Bump the TTL of every per-proof entry kept alongside a proof, so none is evicted before the proof
+    fn extend_proof_entries_ttl(env: Env, proof_id: u64) {
+        let keys = [
+            DataKey::ProofAttester(proof_id),
+            DataKey::AttestationKey(proof_id),
+            DataKey::ProofVerifiedAt(proof_id),
+            DataKey::ProofDispute(proof_id),
+            DataKey::DisputeBondPaid(proof_id),
+            DataKey::LinkedVerification(proof_id),
+            DataKey::ProofRevoked(proof_id),
+            DataKey::ProofExpired(proof_id),
+            DataKey::ProofRejection(proof_id),
+        ];
+        for key in keys {
+            Self::extend_persistent_ttl(env.clone(), &key);
+        }
+    }
+
+    /// Link a proof to the chainVerifier result that verified it cross-chain
+    /// The referenced result must come from the configured chainVerifier, be verified and carry the proof's hash;
+    /// a proof can only be linked once
+    pub fn link_verified_proof(env: Env, proof_id: u64, chain_verifier_ref: ChainVerificationRef) {
+        let proof = Self::load_proof(env.clone(), proof_id)
+            .unwrap_or_else(|| panic!("Proof not found"));
+        
+        proof.issuer.require_auth();
+        
+        Self::require_live(env.clone(), proof_id);
+        
+        if Self::get_linked_verification(env.clone(), proof_id).is_some() {
+            panic!("Proof already linked");
+        }
+        
+        let trusted_verifier = Self::get_chain_verifier(env.clone())
+            .unwrap_or_else(|| panic!("Chain verifier not configured"));
+        if chain_verifier_ref.chain_verifier != trusted_verifier || chain_verifier_ref.chain_id == 0 {
+            panic!("Invalid chain verifier reference");
+        }
+        
+        let verifier = ChainVerifierClient::new(&env, &chain_verifier_ref.chain_verifier);
+        let result = verifier
+            .get_verification_result(&chain_verifier_ref.proof_id, &chain_verifier_ref.chain_id)
+            .filter(|result| result.verified)
+            .unwrap_or_else(|| panic!("Referenced proof not verified"));
+        if result.verification_hash != proof.hash {
+            panic!("Referenced verification is for a different proof");
+        }
+        
+        env.storage().persistent().set(&DataKey::LinkedVerification(proof_id), &chain_verifier_ref);
+        Self::extend_persistent_ttl(env.clone(), &DataKey::LinkedVerification(proof_id));
+        
+        env.events().publish(
+            (symbol_short!("proof"), symbol_short!("linked"), proof_id),
+            chain_verifier_ref
+        );
+    }
+
+    /// Get the cross-chain verification a proof is linked to, if any
+    pub fn get_linked_verification(env: Env, proof_id: u64) -> Option<ChainVerificationRef> {
+        env.storage().persistent().get(&DataKey::LinkedVerification(proof_id))
+    }
+
+    /// Set the chainVerifier contract whose results proofs may be linked to
+    pub fn set_chain_verifier(env: Env, admin: Address, chain_verifier: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        if chain_verifier == env.current_contract_address() {
+            panic!("Invalid chain verifier");
+        }
+        
+        env.storage().instance().set(&DataKey::ChainVerifier, &chain_verifier);
+    }
+
+    /// Get the chainVerifier contract whose results proofs may be linked to
+    pub fn get_chain_verifier(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::ChainVerifier)
+    }
+
+    /// Let `delegate` authorize verify_proof calls for `verifier` until `expires_at`, replacing any earlier delegation
+    pub fn delegate_verification(env: Env, verifier: Address, delegate: Address, expires_at: u64) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if verifier != stored_admin && !Self::is_verifier(env.clone(), verifier.clone()) {
+            panic!("Not authorized");
+        }
+        
+        verifier.require_auth();
+        
+        if expires_at <= env.ledger().timestamp() {
+            panic!("Delegation already expired");
+        }
+        
+        let delegation = VerificationDelegation { delegate, expires_at };
+        env.storage().instance().set(&DataKey::VerificationDelegate(verifier), &delegation);
+    }
+
+    /// End a verifier's delegation immediately
+    pub fn revoke_delegation(env: Env, verifier: Address) {
+        verifier.require_auth();
+        
+        if !env.storage().instance().has(&DataKey::VerificationDelegate(verifier.clone())) {
+            panic!("No delegation to revoke");
+        }
+        
+        env.storage().instance().remove(&DataKey::VerificationDelegate(verifier));
+    }
+
+    /// Get a verifier's delegation, including one that has expired
+    pub fn get_verification_delegate(env: Env, verifier: Address) -> Option<VerificationDelegation> {
+        env.storage().instance().get(&DataKey::VerificationDelegate(verifier))
+    }
+
+    /// Set the fee charged per issued proof (0 keeps issuance free)
+    pub fn set_issue_fee(env: Env, admin: Address, fee: i128) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        if fee < 0 {
+            panic!("Invalid fee");
+        }
+        env.storage().instance().set(&DataKey::IssueFee, &fee);
+    }
+
+    /// Get the fee charged per issued proof
+    pub fn get_issue_fee(env: Env) -> i128 {
+        env.storage().instance().get(&DataKey::IssueFee).unwrap_or(0)
+    }
+
+    /// Set the token issue fees are paid in, normally the native asset contract
+    pub fn set_fee_token(env: Env, admin: Address, fee_token: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::FeeToken, &fee_token);
+    }
+
+    /// Get the token issue fees are paid in
+    pub fn get_fee_token(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::FeeToken)
+    }
+
+    /// Set the address issue fees are paid to
+    pub fn set_treasury(env: Env, admin: Address, treasury: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::Treasury, &treasury);
+    }
+
+    /// Get the address issue fees are paid to
+    pub fn get_treasury(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Treasury)
+    }
+
+    /// Get the total issue fees collected
+    pub fn get_fees_collected(env: Env) -> i128 {
+        env.storage().instance().get(&DataKey::FeesCollected).unwrap_or(0)
+    }
+
+    /// Charge the issue fee, rejecting a transfer that delivers less than the full fee
+    fn collect_issue_fee(env: Env, issuer: &Address) {
+        let fee = Self::get_issue_fee(env.clone());
+        if fee == 0 {
+            return;
+        }
+        
+        let fee_token = Self::get_fee_token(env.clone())
+            .unwrap_or_else(|| panic!("Fee token not set"));
+        let treasury = Self::get_treasury(env.clone())
+            .unwrap_or_else(|| panic!("Treasury not set"));
+        
+        let token = token::Client::new(&env, &fee_token);
+        let before = token.balance(&treasury);
+        token.transfer(issuer, &treasury, &fee);
+        if token.balance(&treasury) - before < fee {
+            panic!("Issue fee underpaid");
+        }
+        
+        let collected = Self::get_fees_collected(env.clone()) + fee;
+        env.storage().instance().set(&DataKey::FeesCollected, &collected);
+    }
+
+    /// Set the largest `event_data` a proof may carry, in bytes
+    pub fn set_max_event_data_size(env: Env, admin: Address, size: u32) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::MaxEventDataSize, &size);
+    }
+
+    /// Get the largest `event_data` a proof may carry, in bytes
+    pub fn get_max_event_data_size(env: Env) -> u32 {
+        env.storage().instance()
+            .get(&DataKey::MaxEventDataSize)
+            .unwrap_or(DEFAULT_MAX_EVENT_DATA_SIZE)
+    }
+
+    /// Revoke a proof (its issuer or the admin)
+    pub fn revoke_proof(env: Env, caller: Address, proof_id: u64) {
+        let proof = Self::load_proof(env.clone(), proof_id)
+            .unwrap_or_else(|| panic!("Proof not found"));
+        
+        if caller != proof.issuer && caller != Self::get_admin(env.clone()) {
+            panic!("Not authorized");
+        }
+        
+        caller.require_auth();
+        Self::require_live(env.clone(), proof_id);
+        
+        Self::mark_revoked(env, &proof, caller);
+    }
+
+    fn mark_revoked(env: Env, proof: &Proof, caller: Address) {
+        let mut breakdown = Self::get_proof_breakdown(env.clone());
+        Self::remove_live(&mut breakdown, proof);
+        breakdown.revoked += 1;
+        
+        let mut revoked = proof.clone();
+        revoked.status = ProofStatus::Revoked;
+        Self::save_proof(env.clone(), &revoked);
+        env.storage().persistent().set(&DataKey::ProofRevoked(proof.id), &true);
+        Self::extend_persistent_ttl(env.clone(), &DataKey::ProofRevoked(proof.id));
+        env.storage().instance().set(&DataKey::ProofBreakdown, &breakdown);
+        
+        env.events().publish(
+            (symbol_short!("proof"), symbol_short!("revoked"), proof.id),
+            caller
+        );
+    }
+
+    /// Purge a proof and its records from storage (admin only), e.g. on a data removal request
+    /// A tombstone keeps the id from being read or reused, so the proof count still covers it;
+    /// id-range queries such as `get_proofs_by_issuer` skip deleted ids, and registry roots fold a marker leaf for them
+    pub fn delete_proof(env: Env, admin: Address, proof_id: u64) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let proof = Self::load_proof(env.clone(), proof_id)
+            .unwrap_or_else(|| panic!("Proof not found"));
+        
+        // The breakdown only counts proofs still stored
+        let mut breakdown = Self::get_proof_breakdown(env.clone());
+        if Self::is_proof_expired(env.clone(), proof_id) {
+            breakdown.expired -= 1;
+        } else if Self::is_proof_revoked(env.clone(), proof_id) {
+            breakdown.revoked -= 1;
+        } else {
+            Self::remove_live(&mut breakdown, &proof);
+        }
+        env.storage().instance().set(&DataKey::ProofBreakdown, &breakdown);
+        
+        let key = DataKey::Proof(proof_id);
+        env.storage().persistent().remove(&key);
+        env.storage().instance().remove(&key);
+        env.storage().persistent().remove(&DataKey::ProofRevoked(proof_id));
+        env.storage().persistent().remove(&DataKey::ProofExpired(proof_id));
+        env.storage().persistent().remove(&DataKey::ProofAttester(proof_id));
+        env.storage().persistent().remove(&DataKey::AttestationKey(proof_id));
+        env.storage().persistent().remove(&DataKey::ProofLogPosition(proof_id));
+        env.storage().persistent().remove(&DataKey::ProofVerifiedAt(proof_id));
+        // A dispute cannot outlive its proof, so its challenger gets the bond back
+        if let Some(dispute) = env.storage().persistent().get::<DataKey, ProofDispute>(&DataKey::ProofDispute(proof_id)) {
+            Self::release_dispute_bond(env.clone(), proof_id, Some(dispute.challenger));
+        }
+        env.storage().persistent().remove(&DataKey::ProofDispute(proof_id));
+        env.storage().persistent().remove(&DataKey::LinkedVerification(proof_id));
+        env.storage().persistent().remove(&DataKey::ProofRejection(proof_id));
+        let tombstone = DataKey::ProofDeleted(proof_id);
+        env.storage().persistent().set(&tombstone, &true);
+        Self::extend_persistent_ttl(env.clone(), &tombstone);
+        
+        env.events().publish(
+            (symbol_short!("proof"), symbol_short!("deleted"), proof_id),
+            admin
+        );
+    }
+
+    /// Check whether a proof's data has been purged
+    pub fn is_proof_deleted(env: Env, proof_id: u64) -> bool {
+        let tombstone = DataKey::ProofDeleted(proof_id);
+        let deleted = env.storage().persistent().has(&tombstone);
+        Self::extend_persistent_ttl(env, &tombstone);
+        deleted
+    }
+
+    /// Hand a live proof over to a new issuer
+    pub fn transfer_proof(env: Env, issuer: Address, proof_id: u64, new_issuer: Address) {
+        let mut proof = Self::load_proof(env.clone(), proof_id)
+            .unwrap_or_else(|| panic!("Proof not found"));
+        
+        if issuer != proof.issuer {
+            panic!("Not authorized");
+        }
+        
+        issuer.require_auth();
+        Self::require_live(env.clone(), proof_id);
+        
+        proof.issuer = new_issuer.clone();
+        Self::save_proof(env.clone(), &proof);
+        
+        env.events().publish(
+            (symbol_short!("proof"), Symbol::new(&env, "transferred"), proof_id),
+            (issuer, new_issuer)
+        );
+    }
+
+    /// Check whether a proof has been revoked
+    pub fn is_proof_revoked(env: Env, proof_id: u64) -> bool {
+        env.storage().persistent().has(&DataKey::ProofRevoked(proof_id))
+    }
+
+    /// Check whether a proof has been marked expired by the expiry sweep
+    pub fn is_proof_expired(env: Env, proof_id: u64) -> bool {
+        env.storage().persistent().has(&DataKey::ProofExpired(proof_id))
+    }
+
+    /// Check whether a proof is past its own expiry time or the validity period, or has been marked expired by a sweep
+    pub fn is_expired(env: Env, proof_id: u64) -> bool {
+        if Self::is_proof_expired(env.clone(), proof_id) {
+            return true;
+        }
+        
+        match Self::load_proof(env.clone(), proof_id) {
+            Some(proof) => Self::is_past_expiry(env, &proof),
+            None => false,
+        }
+    }
+
+    /// A proof lapses at its own `expires_at` or once the validity period since issuance has run, whichever comes first
+    fn is_past_expiry(env: Env, proof: &Proof) -> bool {
+        let now = env.ledger().timestamp();
+        let validity = Self::get_proof_validity(env);
+        
+        (proof.expires_at != 0 && now > proof.expires_at)
+            || (validity != 0 && proof.timestamp.saturating_add(validity) <= now)
+    }
+
+    /// Set how many seconds a proof stays valid after issuance (0 means proofs never expire)
+    pub fn set_proof_validity(env: Env, admin: Address, seconds: u64) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::ProofValidity, &seconds);
+    }
+
+    /// Get how many seconds a proof stays valid after issuance
+    pub fn get_proof_validity(env: Env) -> u64 {
+        env.storage().instance().get(&DataKey::ProofValidity).unwrap_or(0)
+    }
+
+    /// Mark up to `limit` proofs past their validity period as expired; anyone may run the sweep
+    /// Ids are issued in time order, so the sweep resumes where it stopped and halts at the first live proof
+    pub fn expire_proofs(env: Env, limit: u32) -> u32 {
+        let validity = Self::get_proof_validity(env.clone());
+        if validity == 0 {
+            return 0;
+        }
+        
+        let count: u64 = env.storage().instance().get(&DataKey::ProofCount).unwrap_or(0);
+        let mut cursor: u64 = env.storage().instance().get(&DataKey::ExpiryCursor).unwrap_or(0);
+        let mut breakdown = Self::get_proof_breakdown(env.clone());
+        let now = env.ledger().timestamp();
+        let mut expired = 0u32;
+        
+        while cursor < count && expired < limit {
+            let proof_id = cursor + 1;
+            if let Some(proof) = Self::load_proof(env.clone(), proof_id) {
+                if proof.timestamp.saturating_add(validity) > now {
+                    break;
+                }
+                if Self::mark_expired(env.clone(), &mut breakdown, proof_id, proof) {
+                    expired += 1;
+                }
+            }
+            cursor = proof_id;
+        }
+        
+        env.storage().instance().set(&DataKey::ExpiryCursor, &cursor);
+        env.storage().instance().set(&DataKey::ProofBreakdown, &breakdown);
+        
+        expired
+    }
+
+    /// Scan up to `limit` proof ids from `start_id`, marking the ones past their own expiry or the validity period as expired
+    /// Returns the id to resume from (0 once the last proof has been scanned) and the number swept
+    pub fn sweep_expired_proofs(env: Env, admin: Address, start_id: u64, limit: u32) -> (u64, u32) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        if limit > MAX_EXPIRY_SWEEP_WINDOW {
+            panic!("Sweep window too large");
+        }
+        
+        let count: u64 = env.storage().instance().get(&DataKey::ProofCount).unwrap_or(0);
+        let start_id = start_id.max(1);
+        let end_id = start_id.saturating_add(limit as u64).min(count.saturating_add(1));
+        if start_id >= end_id {
+            return (0, 0);
+        }
+        
+        let mut breakdown = Self::get_proof_breakdown(env.clone());
+        let mut swept = 0u32;
+        
+        for proof_id in start_id..end_id {
+            if let Some(proof) = Self::load_proof(env.clone(), proof_id) {
+                if Self::mark_expired(env.clone(), &mut breakdown, proof_id, proof) {
+                    swept += 1;
+                }
+            }
+        }
+        
+        env.storage().instance().set(&DataKey::ProofBreakdown, &breakdown);
+        
+        let next_cursor = if end_id > count { 0 } else { end_id };
+        (next_cursor, swept)
+    }
+
+    /// Get the number of verified, unverified, revoked and expired proofs
+    pub fn get_proof_breakdown(env: Env) -> ProofBreakdown {
+        if let Some(breakdown) = env.storage().instance().get(&DataKey::ProofBreakdown) {
+            return breakdown;
+        }
+        
+        // Proofs issued before the counters existed are tallied once
+        let count: u64 = env.storage().instance().get(&DataKey::ProofCount).unwrap_or(0);
+        let mut breakdown = ProofBreakdown { verified: 0, unverified: 0, revoked: 0, expired: 0 };
+        for i in 1..=count {
+            if let Some(proof) = Self::load_proof(env.clone(), i) {
+                match proof.status {
+                    ProofStatus::Verified => breakdown.verified += 1,
+                    ProofStatus::Revoked => breakdown.revoked += 1,
+                    ProofStatus::Pending | ProofStatus::Rejected => breakdown.unverified += 1,
+                }
+            }
+        }
+        breakdown
+    }
+
+    fn require_live(env: Env, proof_id: u64) {
+        if Self::is_proof_revoked(env.clone(), proof_id) {
+            panic!("Proof revoked");
+        }
+        if Self::is_expired(env, proof_id) {
+            panic!("Proof expired");
+        }
+    }
+
+    /// Mark a proof expired if it is past its expiry and not already revoked or marked; returns whether it was marked
+    fn mark_expired(env: Env, breakdown: &mut ProofBreakdown, proof_id: u64, proof: Proof) -> bool {
+        if !Self::is_past_expiry(env.clone(), &proof)
+            || Self::is_proof_revoked(env.clone(), proof_id)
+            || Self::is_proof_expired(env.clone(), proof_id)
+        {
+            return false;
+        }
+        
+        Self::remove_live(breakdown, &proof);
+        breakdown.expired += 1;
+        env.storage().persistent().set(&DataKey::ProofExpired(proof_id), &true);
+        Self::extend_persistent_ttl(env.clone(), &DataKey::ProofExpired(proof_id));
+        env.events().publish(
+            (symbol_short!("proof"), symbol_short!("expired"), proof_id),
+            proof.issuer
+        );
+        
+        true
+    }
+
+    /// Take a live proof out of the verified or unverified count; rejected proofs count as unverified
+    fn remove_live(breakdown: &mut ProofBreakdown, proof: &Proof) {
+        if proof.status == ProofStatus::Verified {
+            breakdown.verified -= 1;
+        } else {
+            breakdown.unverified -= 1;
+        }
+    }
+
+    /// Register the ed25519 key a verifier signs attestations with
+    pub fn set_verifier_key(env: Env, verifier: Address, public_key: BytesN<32>) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if verifier != stored_admin && !Self::is_verifier(env.clone(), verifier.clone()) {
+            panic!("Not authorized");
+        }
+        
+        verifier.require_auth();
+        env.storage().instance().set(&DataKey::VerifierKey(verifier), &public_key);
+    }
+
+    /// Get the ed25519 key a verifier signs attestations with
+    pub fn get_verifier_key(env: Env, verifier: Address) -> Option<BytesN<32>> {
+        env.storage().instance().get(&DataKey::VerifierKey(verifier))
+    }
+
+    /// Get the digest a verifier signs to attest a proof: sha256 of `(proof_id, hash, timestamp)` XDR
+    pub fn get_attestation_digest(env: Env, proof_id: u64) -> BytesN<32> {
+        let proof = Self::load_proof(env.clone(), proof_id)
+            .unwrap_or_else(|| panic!("Proof not found"));
+        Self::attestation_digest(env, &proof)
+    }
+
+    /// Check a proof's stored attestation against the key it was signed with, so later key rotations leave it valid
+    /// Returns false when the proof carries no attestation or no key is known for it; a signature that does not match panics
+    pub fn verify_attestation(env: Env, proof_id: u64) -> bool {
+        let proof = Self::load_proof(env.clone(), proof_id)
+            .unwrap_or_else(|| panic!("Proof not found"));
+        
+        if proof.status != ProofStatus::Verified {
+            return false;
+        }
+        let signature = match BytesN::<64>::try_from(proof.verifier_signature.clone()) {
+            Ok(signature) => signature,
+            Err(_) => return false,
+        };
+        
+        // Attestations recorded before keys were kept with them fall back to the attester's current key
+        let public_key = Self::get_attestation_key(env.clone(), proof_id).or_else(|| {
+            Self::get_proof_verifier(env.clone(), proof_id)
+                .and_then(|attester| Self::get_verifier_key(env.clone(), attester))
+        });
+        let public_key = match public_key {
+            Some(public_key) => public_key,
+            None => return false,
+        };
+        
+        let digest = Self::attestation_digest(env.clone(), &proof);
+        env.crypto().ed25519_verify(&public_key, &digest.into(), &signature);
+        
+        true
+    }
+
+    /// Get the key a proof's attestation was signed with, if it is verified
+    pub fn get_attestation_key(env: Env, proof_id: u64) -> Option<BytesN<32>> {
+        env.storage().persistent().get(&DataKey::AttestationKey(proof_id))
+    }
+
+    fn attestation_digest(env: Env, proof: &Proof) -> BytesN<32> {
+        let payload = (proof.id, proof.hash.clone(), proof.timestamp).to_xdr(&env);
+        env.crypto().sha256(&payload)
+    }
+
+    /// Register an address allowed to verify proofs
+    pub fn add_verifier(env: Env, admin: Address, verifier: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        if Self::is_verifier(env.clone(), verifier.clone()) {
+            panic!("Verifier already registered");
+        }
+        
+        let mut verifiers = Self::get_verifiers(env.clone());
+        verifiers.push_back(verifier.clone());
+        capacity::ensure_fits(&env, &verifiers, Self::get_state_capacity(env.clone()));
+        env.storage().instance().set(&DataKey::VerifierList, &verifiers);
+        env.storage().instance().set(&DataKey::Verifier(verifier.clone()), &true);
+        
+        env.events().publish(
+            (symbol_short!("verifier"), symbol_short!("added")),
+            verifier
+        );
+    }
+
+    /// Remove a registered verifier
+    pub fn remove_verifier(env: Env, admin: Address, verifier: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let mut verifiers = Self::get_verifiers(env.clone());
+        let index = verifiers.first_index_of(&verifier)
+            .unwrap_or_else(|| panic!("Verifier not found"));
+        verifiers.remove(index);
+        env.storage().instance().set(&DataKey::VerifierList, &verifiers);
+        env.storage().instance().remove(&DataKey::Verifier(verifier.clone()));
+        
+        env.events().publish(
+            (symbol_short!("verifier"), symbol_short!("removed")),
+            verifier
+        );
+    }
+
+    /// Check if an address is a registered verifier
+    pub fn is_verifier(env: Env, verifier: Address) -> bool {
+        env.storage().instance().has(&DataKey::Verifier(verifier))
+    }
+
+    /// Get all registered verifiers
+    pub fn get_verifiers(env: Env) -> Vec<Address> {
+        env.storage().instance().get(&DataKey::VerifierList).unwrap_or(Vec::new(&env))
+    }
+
+    /// Get the number of registered verifiers
+    pub fn get_verifier_count(env: Env) -> u32 {
+        Self::get_verifiers(env).len()
+    }
+
+    /// Turn the issuer allowlist on or off; while off anyone may issue proofs
+    pub fn set_issuer_allowlist_enabled(env: Env, admin: Address, enabled: bool) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&DataKey::IssuerAllowlistEnabled, &enabled);
+    }
+
+    /// Check whether only allowlisted issuers may issue proofs
+    pub fn is_issuer_allowlist_enabled(env: Env) -> bool {
+        env.storage().instance().get(&DataKey::IssuerAllowlistEnabled).unwrap_or(false)
+    }
+
+    /// Allow an address to issue proofs while the allowlist is enabled
+    pub fn allow_issuer(env: Env, admin: Address, issuer: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().persistent().set(&DataKey::AllowedIssuer(issuer.clone()), &true);
+        
+        env.events().publish(
+            (symbol_short!("issuer"), symbol_short!("allowed")),
+            issuer
+        );
+    }
+
+    /// Take an address off the issuer allowlist
+    pub fn disallow_issuer(env: Env, admin: Address, issuer: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().persistent().remove(&DataKey::AllowedIssuer(issuer.clone()));
+        
+        env.events().publish(
+            (symbol_short!("issuer"), Symbol::new(&env, "disallowed")),
+            issuer
+        );
+    }
+
+    /// Check whether an address may issue proofs right now
+    pub fn is_issuer_allowed(env: Env, issuer: Address) -> bool {
+        !Self::is_issuer_allowlist_enabled(env.clone())
+            || env.storage().persistent().has(&DataKey::AllowedIssuer(issuer))
+    }
+
+    fn require_allowed_issuer(env: Env, issuer: &Address) {
+        if Self::is_denylisted(env.clone(), issuer.clone()) {
+            panic!("Issuer denylisted");
+        }
+        if !Self::is_issuer_allowed(env, issuer.clone()) {
+            panic!("Issuer not allowed");
+        }
+    }
+
+    /// Block an address from issuing proofs, whether or not the allowlist is enabled
+    pub fn add_to_denylist(env: Env, admin: Address, party: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let key = DataKey::Denylisted(party);
+        env.storage().persistent().set(&key, &true);
+        Self::extend_persistent_ttl(env, &key);
+    }
+
+    /// Lift a denylist block
+    pub fn remove_from_denylist(env: Env, admin: Address, party: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().persistent().remove(&DataKey::Denylisted(party));
+    }
+
+    /// Check whether an address is denylisted; each check keeps the entry alive
+    pub fn is_denylisted(env: Env, party: Address) -> bool {
+        let key = DataKey::Denylisted(party);
+        if !env.storage().persistent().has(&key) {
+            return false;
+        }
+        Self::extend_persistent_ttl(env, &key);
+        true
+    }
+
+    /// Get the byte capacity growing state lists may occupy
+    pub fn get_state_capacity(env: Env) -> u32 {
+        env.storage().instance()
+            .get(&DataKey::StateCapacity)
+            .unwrap_or(capacity::DEFAULT_STATE_CAPACITY)
+    }
+
+    /// Grow the state capacity ahead of demand, prepaying rent for the larger entry
+    pub fn realloc_state(env: Env, admin: Address, new_size: u32) -> u32 {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let current = Self::get_state_capacity(env.clone());
+        let new_size = capacity::realloc(&env, current, new_size);
+        env.storage().instance().set(&DataKey::StateCapacity, &new_size);
+        
+        new_size
+    }
+
+    /// Get proof details
+    /// Proofs live in persistent storage and are evicted once their TTL runs out; every read
+    /// bumps the TTL by the configured extension so proofs that are still consulted stay live
+    pub fn get_proof(env: Env, proof_id: u64) -> Proof {
+        let proof = Self::load_proof(env.clone(), proof_id)
+            .unwrap_or_else(|| panic!("Proof not found"));
+        
+        Self::extend_persistent_ttl(env.clone(), &DataKey::Proof(proof_id));
+        Self::extend_proof_entries_ttl(env, proof_id);
+        
+        proof
+    }
+
+    /// Check whether a proof has been verified (false for unknown, revoked or expired ids)
+    pub fn is_proof_verified(env: Env, proof_id: u64) -> bool {
+        if Self::is_proof_revoked(env.clone(), proof_id) || Self::is_expired(env.clone(), proof_id) {
+            return false;
+        }
+        
+        if Self::get_proof_dispute(env.clone(), proof_id).is_some() {
+            return false;
+        }
+        
+        Self::load_proof(env, proof_id)
+            .map(|proof| proof.status == ProofStatus::Verified)
+            .unwrap_or(false)
+    }
+
+    /// Set how many ledgers a proof's TTL is bumped to when it is read (0 disables bumping)
+    pub fn set_proof_ttl_extension(env: Env, admin: Address, amount: u32) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        if amount > MAX_PROOF_TTL_EXTENSION {
+            panic!("TTL extension exceeds maximum");
+        }
+        
+        env.storage().instance().set(&DataKey::ProofTtlExtension, &amount);
+    }
+
+    /// Get how many ledgers a proof's TTL is bumped to when it is read
+    pub fn get_proof_ttl_extension(env: Env) -> u32 {
+        env.storage().instance()
+            .get(&DataKey::ProofTtlExtension)
+            .unwrap_or(DEFAULT_PROOF_TTL_EXTENSION)
+    }
+
+    /// Get an issuer's proofs among the first scan window of ids; page past it with `get_proofs_by_issuer_paged`
+    pub fn get_proofs_by_issuer(env: Env, issuer: Address) -> Vec<Proof> {
+        Self::get_proofs_by_issuer_paged(env, issuer, 1, PROOF_SCAN_WINDOW as u32)
+    }
+
+    /// Get up to `limit` of an issuer's proofs, scanning at most a fixed window of ids from `start` on
+    /// To continue, pass the last id received plus one as `start`, or, when fewer than `limit`
+    /// came back, `start` advanced by the scan window; stop once `start` passes the proof count
+    pub fn get_proofs_by_issuer_paged(env: Env, issuer: Address, start: u64, limit: u32) -> Vec<Proof> {
+        let count: u64 = env.storage().instance().get(&DataKey::ProofCount).unwrap_or(0);
+        let mut proofs = Vec::new(&env);
+        
+        let start = start.max(1);
+        let end = count.min(start.saturating_add(PROOF_SCAN_WINDOW - 1));
+        for i in start..=end {
+            if proofs.len() >= limit {
+                break;
+            }
+            if let Some(proof) = Self::load_proof(env.clone(), i) {
+                if proof.issuer == issuer {
+                    proofs.push_back(proof);
+                }
+            }
+        }
+        
+        proofs
+    }
+
+    /// Get up to `limit` proofs in `status`, scanning at most a fixed window of ids from `start` on
+    /// Paginated the same way as `get_proofs_by_issuer_paged`
+    pub fn get_proofs_by_status(env: Env, status: ProofStatus, start: u64, limit: u32) -> Vec<Proof> {
+        let count: u64 = env.storage().instance().get(&DataKey::ProofCount).unwrap_or(0);
+        let mut proofs = Vec::new(&env);
+        
+        let start = start.max(1);
+        let end = count.min(start.saturating_add(PROOF_SCAN_WINDOW - 1));
+        for i in start..=end {
+            if proofs.len() >= limit {
+                break;
+            }
+            if let Some(proof) = Self::load_proof(env.clone(), i) {
+                if proof.status == status {
+                    proofs.push_back(proof);
+                }
+            }
+        }
+        
+        proofs
+    }
+
+    /// Get up to `n` of the most recent proofs, newest first, skipping deleted ids
+    /// Scans at most a fixed window of ids back from the newest
+    pub fn get_latest_proofs(env: Env, n: u32) -> Vec<Proof> {
+        if n > MAX_LATEST_PROOFS {
+            panic!("Too many proofs requested");
+        }
+        
+        let count: u64 = env.storage().instance().get(&DataKey::ProofCount).unwrap_or(0);
+        let mut proofs = Vec::new(&env);
+        
+        let end = count.saturating_sub(PROOF_SCAN_WINDOW);
+        let mut id = count;
+        while id > end && proofs.len() < n {
+            if let Some(proof) = Self::load_proof(env.clone(), id) {
+                proofs.push_back(proof);
+            }
+            id -= 1;
+        }
+        
+        proofs
+    }
+
+    /// Get proofs issued at or after `since_ts`, oldest first, continuing after id `cursor` (0 to start)
+    /// Examines at most a fixed window of ids each way; returns the proofs and the cursor to resume from,
+    /// which is 0 once every proof has been examined
+    pub fn get_proofs_since(env: Env, since_ts: u64, cursor: u64, limit: u32) -> (Vec<Proof>, u64) {
+        let count: u64 = env.storage().instance().get(&DataKey::ProofCount).unwrap_or(0);
+        let mut proofs = Vec::new(&env);
+        
+        // Ids are issued in ledger time order, so walk back from the newest proof to find
+        // where `since_ts` starts; past the window, fall back to scanning forward from `cursor`
+        let floor = cursor.max(count.saturating_sub(PROOF_SCAN_WINDOW));
+        let mut start = cursor + 1;
+        let mut id = count;
+        while id > floor {
+            if let Some(proof) = Self::load_proof(env.clone(), id) {
+                if proof.timestamp < since_ts {
+                    start = id + 1;
+                    break;
+                }
+            }
+            id -= 1;
+        }
+        
+        let end = count.min(start.saturating_add(PROOF_SCAN_WINDOW - 1));
+        let mut id = start;
+        while id <= end && proofs.len() < limit {
+            if let Some(proof) = Self::load_proof(env.clone(), id) {
+                if proof.timestamp >= since_ts {
+                    proofs.push_back(proof);
+                }
+            }
+            id += 1;
+        }
+        
+        let next = if id > count { 0 } else { id - 1 };
+        (proofs, next)
+    }
+
+    /// Commit a batch of related proofs under one merkle root, returning the batch id and root
+    pub fn issue_proof_batch_committed(env: Env, issuer: Address, leaves: Vec<Bytes>) -> (u64, BytesN<32>) {
+        issuer.require_auth();
+        Self::require_allowed_issuer(env.clone(), &issuer);
+        
+        if leaves.is_empty() {
+            panic!("Empty batch");
+        }
+        if leaves.len() > MAX_BATCH_LEAVES {
+            panic!("Batch too large");
+        }
+        
+        let root = Self::merkle_root(env.clone(), &leaves);
+        
+        let count: u64 = env.storage().instance().get(&DataKey::BatchCount).unwrap_or(0);
+        let batch_id = count.checked_add(1).unwrap_or_else(|| panic!("batch id space exhausted"));
+        
+        let batch = ProofBatch {
+            batch_id,
+            issuer,
+            root: root.clone(),
+            leaf_count: leaves.len(),
+            timestamp: env.ledger().timestamp(),
+        };
+        
+        env.storage().persistent().set(&DataKey::ProofBatch(batch_id), &batch);
+        env.storage().instance().set(&DataKey::BatchCount, &batch_id);
+        
+        (batch_id, root)
+    }
+
+    /// Get a committed proof batch
+    pub fn get_proof_batch(env: Env, batch_id: u64) -> ProofBatch {
+        env.storage().persistent()
+            .get(&DataKey::ProofBatch(batch_id))
+            .unwrap_or_else(|| panic!("Batch not found"))
+    }
+
+    /// Check that `leaf` sits at `index` in a committed batch
+    /// `proof` lists the sibling hashes from the leaf level up, skipping levels where the node has no sibling
+    pub fn verify_leaf_in_batch(env: Env, batch_id: u64, leaf: Bytes, index: u32, proof: Vec<BytesN<32>>) -> bool {
+        let batch = Self::get_proof_batch(env.clone(), batch_id);
+        if index >= batch.leaf_count {
+            return false;
+        }
+        
+        let mut node = Self::leaf_hash(env.clone(), &leaf);
+        let mut position = index;
+        let mut width = batch.leaf_count;
+        let mut used = 0;
+        
+        while width > 1 {
+            let is_right = position % 2 == 1;
+            if is_right || position + 1 < width {
+                let sibling = match proof.get(used) {
+                    Some(sibling) => sibling,
+                    None => return false,
+                };
+                node = if is_right {
+                    Self::node_hash(env.clone(), &sibling, &node)
+                } else {
+                    Self::node_hash(env.clone(), &node, &sibling)
+                };
+                used += 1;
+            }
+            position /= 2;
+            width = (width + 1) / 2;
+        }
+        
+        used == proof.len() && node == batch.root
+    }
+
+    /// Compute the merkle root over the hashes of proofs `from_id..=to_id`, reproducible off-chain
+    /// A deleted proof's leaf is `DELETED_PROOF_LEAF` in place of its hash
+    pub fn compute_registry_root(env: Env, from_id: u64, to_id: u64) -> BytesN<32> {
+        if from_id == 0 || to_id < from_id {
+            panic!("Invalid proof range");
+        }
+        if to_id - from_id >= MAX_REGISTRY_ROOT_RANGE {
+            panic!("Proof range too large");
+        }
+        
+        let mut leaves = Vec::new(&env);
+        for proof_id in from_id..=to_id {
+            let leaf = match Self::load_proof(env.clone(), proof_id) {
+                Some(proof) => proof.hash,
+                None if Self::is_proof_deleted(env.clone(), proof_id) => Bytes::from_slice(&env, DELETED_PROOF_LEAF),
+                None => panic!("Proof not found"),
+            };
+            leaves.push_back(leaf);
+        }
+        
+        Self::merkle_root(env, &leaves)
+    }
+
+    /// Compute and store the registry root over `from_id..=to_id` as the latest published root
+    pub fn publish_registry_root(env: Env, admin: Address, from_id: u64, to_id: u64) -> BytesN<32> {
+        let stored_admin: Address = env.storage().instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let root = Self::compute_registry_root(env.clone(), from_id, to_id);
+        let published = RegistryRoot {
+            root: root.clone(),
+            from_id,
+            to_id,
+            published_at: env.ledger().timestamp(),
+        };
+        env.storage().instance().set(&DataKey::RegistryRoot, &published);
+        
+        env.events().publish(
+            (symbol_short!("registry"), Symbol::new(&env, "root_published")),
+            published
+        );
+        
+        root
+    }
+
+    /// Get the most recently published registry root, if any
+    pub fn get_registry_root(env: Env) -> Option<RegistryRoot> {
+        env.storage().instance().get(&DataKey::RegistryRoot)
+    }
+
+    /// Root of the merkle tree over `leaves`; a node without a sibling is carried up unchanged
+    fn merkle_root(env: Env, leaves: &Vec<Bytes>) -> BytesN<32> {
+        let mut level = Vec::new(&env);
+        for leaf in leaves.iter() {
+            level.push_back(Self::leaf_hash(env.clone(), &leaf));
+        }
+        
+        while level.len() > 1 {
+            let mut next = Vec::new(&env);
+            let mut i = 0;
+            while i < level.len() {
+                let left = level.get(i).unwrap();
+                match level.get(i + 1) {
+                    Some(right) => next.push_back(Self::node_hash(env.clone(), &left, &right)),
+                    None => next.push_back(left),
+                }
+                i += 2;
+            }
+            level = next;
+        }
+        level.get(0).unwrap()
+    }
+
+    fn leaf_hash(env: Env, leaf: &Bytes) -> BytesN<32> {
+        let mut preimage = Bytes::from_array(&env, &[0u8]);
+        preimage.append(leaf);
+        env.crypto().sha256(&preimage)
+    }
+
+    fn node_hash(env: Env, left: &BytesN<32>, right: &BytesN<32>) -> BytesN<32> {
+        let mut preimage = Bytes::from_array(&env, &[1u8]);
+        preimage.append(&left.clone().into());
+        preimage.append(&right.clone().into());
+        env.crypto().sha256(&preimage)
+    }
+
+    /// Status of a proof stored with the old verified flag; revocation was only kept under its own key
+    fn legacy_status(env: &Env, proof_id: u64, verified: bool) -> ProofStatus {
+        if env.storage().persistent().has(&DataKey::ProofRevoked(proof_id)) {
+            ProofStatus::Revoked
+        } else if verified {
+            ProofStatus::Verified
+        } else {
+            ProofStatus::Pending
+        }
+    }
+
+    /// Metadata URI of a proof stored when "" stood for none
+    fn legacy_metadata_uri(uri: String) -> MetadataUri {
+        if uri.len() == 0 {
+            MetadataUri::Unset
+        } else {
+            MetadataUri::Uri(uri)
+        }
+    }
+
+    /// Anchor of a proof stored when an unset chain id and empty hash stood for none
+    fn legacy_anchor(anchor_chain_id: Option<u64>, anchor_block_hash: Bytes) -> ProofAnchor {
+        match anchor_chain_id {
+            Some(chain_id) => ProofAnchor::Block(chain_id, anchor_block_hash),
+            None => ProofAnchor::Unanchored,
+        }
+    }
+
+    /// Load a proof, upgrading entries stored in an older layout
+    fn load_proof(env: Env, proof_id: u64) -> Option<Proof> {
+        // Proofs issued before the move to persistent storage are still in instance storage
+        let key = DataKey::Proof(proof_id);
+        let raw: Val = env.storage().persistent().get(&key)
+            .or_else(|| env.storage().instance().get(&key))?;
+        
+        if let Ok(proof) = Proof::try_from_val(&env, &raw) {
+            return Some(proof);
+        }
+        
+        if let Ok(v8) = ProofV8::try_from_val(&env, &raw) {
+            return Some(Proof {
+                id: v8.id,
+                issuer: v8.issuer,
+                event_data: v8.event_data,
+                timestamp: v8.timestamp,
+                status: v8.status,
+                hash: v8.hash,
+                metadata_uri: Self::legacy_metadata_uri(v8.metadata_uri),
+                verifier_signature: v8.verifier_signature,
+                supersedes: v8.supersedes,
+                parent_id: v8.parent_id,
+                anchor: Self::legacy_anchor(v8.anchor_chain_id, v8.anchor_block_hash),
+                expires_at: v8.expires_at,
+            });
+        }
+        
+        if let Ok(v7) = ProofV7::try_from_val(&env, &raw) {
+            return Some(Proof {
+                id: v7.id,
+                issuer: v7.issuer,
+                event_data: v7.event_data,
+                timestamp: v7.timestamp,
+                status: v7.status,
+                hash: v7.hash,
+                metadata_uri: Self::legacy_metadata_uri(v7.metadata_uri),
+                verifier_signature: v7.verifier_signature,
+                supersedes: v7.supersedes,
+                parent_id: None,
+                anchor: Self::legacy_anchor(v7.anchor_chain_id, v7.anchor_block_hash),
+                expires_at: v7.expires_at,
+            });
+        }
+        
+        if let Ok(v6) = ProofV6::try_from_val(&env, &raw) {
+            return Some(Proof {
+                id: v6.id,
+                issuer: v6.issuer,
+                event_data: v6.event_data,
+                timestamp: v6.timestamp,
+                status: Self::legacy_status(&env, v6.id, v6.verified),
+                hash: v6.hash,
+                metadata_uri: Self::legacy_metadata_uri(v6.metadata_uri),
+                verifier_signature: v6.verifier_signature,
+                supersedes: v6.supersedes,
+                parent_id: None,
+                anchor: Self::legacy_anchor(v6.anchor_chain_id, v6.anchor_block_hash),
+                expires_at: v6.expires_at,
+            });
+        }
+        
+        if let Ok(v5) = ProofV5::try_from_val(&env, &raw) {
+            return Some(Proof {
+                id: v5.id,
+                issuer: v5.issuer,
+                event_data: v5.event_data,
+                timestamp: v5.timestamp,
+                status: Self::legacy_status(&env, v5.id, v5.verified),
+                hash: v5.hash,
+                metadata_uri: Self::legacy_metadata_uri(v5.metadata_uri),
+                verifier_signature: v5.verifier_signature,
+                supersedes: v5.supersedes,
+                parent_id: None,
+                anchor: Self::legacy_anchor(v5.anchor_chain_id, v5.anchor_block_hash),
+                expires_at: 0,
+            });
+        }
+        
+        if let Ok(v4) = ProofV4::try_from_val(&env, &raw) {
+            return Some(Proof {
+                id: v4.id,
+                issuer: v4.issuer,
+                event_data: v4.event_data,
+                timestamp: v4.timestamp,
+                status: Self::legacy_status(&env, v4.id, v4.verified),
+                hash: v4.hash,
+                metadata_uri: Self::legacy_metadata_uri(v4.metadata_uri),
+                verifier_signature: v4.verifier_signature,
+                supersedes: v4.supersedes,
+                parent_id: None,
+                anchor: ProofAnchor::Unanchored,
+                expires_at: 0,
+            });
+        }
+        
+        if let Ok(v3) = ProofV3::try_from_val(&env, &raw) {
+            return Some(Proof {
+                id: v3.id,
+                issuer: v3.issuer,
+                event_data: v3.event_data,
+                timestamp: v3.timestamp,
+                status: Self::legacy_status(&env, v3.id, v3.verified),
+                hash: v3.hash,
+                metadata_uri: Self::legacy_metadata_uri(v3.metadata_uri),
+                verifier_signature: v3.verifier_signature,
+                supersedes: None,
+                parent_id: None,
+                anchor: ProofAnchor::Unanchored,
+                expires_at: 0,
+            });
+        }
+        
+        if let Ok(v2) = ProofV2::try_from_val(&env, &raw) {
+            return Some(Proof {
+                id: v2.id,
+                issuer: v2.issuer,
+                event_data: v2.event_data,
+                timestamp: v2.timestamp,
+                status: Self::legacy_status(&env, v2.id, v2.verified),
+                hash: v2.hash,
+                metadata_uri: Self::legacy_metadata_uri(v2.metadata_uri),
+                verifier_signature: Bytes::new(&env),
+                supersedes: None,
+                parent_id: None,
+                anchor: ProofAnchor::Unanchored,
+                expires_at: 0,
+            });
+        }
+        
+        let v1 = ProofV1::try_from_val(&env, &raw)
+            .unwrap_or_else(|_| panic!("Corrupt proof entry"));
+        Some(Proof {
+            id: v1.id,
+            issuer: v1.issuer,
+            event_data: v1.event_data,
+            timestamp: v1.timestamp,
+            status: Self::legacy_status(&env, v1.id, v1.verified),
+            hash: v1.hash,
+            metadata_uri: MetadataUri::Unset,
+            verifier_signature: Bytes::new(&env),
+            supersedes: None,
+            parent_id: None,
+            anchor: ProofAnchor::Unanchored,
+            expires_at: 0,
+        })
+    }
+
+    /// Write a proof to persistent storage, dropping any instance-storage copy
+    fn save_proof(env: Env, proof: &Proof) {
+        let key = DataKey::Proof(proof.id);
+        env.storage().persistent().set(&key, proof);
+        env.storage().instance().remove(&key);
     }
 
     /// Get the admin address
@@ -1,9 +1,115 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Bytes, Env, String, Vec, Map, U256};
+use soroban_sdk::{contract, contractimpl, contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, Symbol, TryFromVal, Val, Vec, Map, U256};
+
+use crate::chainVerifier::ChainVerifierClient;
+use crate::capacity;
+use crate::fees;
+
+/// Layout version of this contract's stored state
+const STATE_VERSION: u32 = 5;
+/// Reputation a relayer starts with
+const REPUTATION_MAX: u32 = 100;
+/// Reputation an idle relayer decays toward
+const REPUTATION_BASELINE: u32 = 50;
+/// Seconds over which the decay rate is applied
+const REPUTATION_DECAY_PERIOD: u64 = 86_400;
+/// Default reputation points lost per idle period
+const DEFAULT_REPUTATION_DECAY_RATE: u32 = 1;
+/// Upper bound on the configurable decay rate
+const MAX_REPUTATION_DECAY_RATE: u32 = 10;
+/// Extra gas charged for on-chain verification of Proof messages
+const PROOF_VERIFICATION_GAS: u64 = 50_000;
+/// Length of the rolling window the failure rate is measured over
+const FAILURE_WINDOW_SECONDS: u64 = 3_600;
+/// Messages needed in a window before the circuit breaker can trip
+const FAILURE_MIN_SAMPLE: u64 = 10;
+/// Default failure rate (basis points) that trips the circuit breaker
+const DEFAULT_FAILURE_THRESHOLD_BPS: u32 = 5_000;
+/// Gas limit applied to a message type without a configured cap
+const DEFAULT_GAS_CAP: u64 = 500_000;
+/// Upper bound on a message type's fee multiplier (basis points, 10x)
+const MAX_FEE_MULTIPLIER_BPS: u32 = 100_000;
+/// Seconds after delivery during which an execution can be challenged
+const DISPUTE_WINDOW_SECONDS: u64 = 86_400;
+/// Default bond, in the fee token, a challenger puts up and loses if the challenge is rejected
+const DEFAULT_CHALLENGE_BOND: u64 = 1_000;
+/// Default seconds a relayer's stake stays locked after its last relay, long enough to be challenged
+const DEFAULT_STAKE_LOCKUP_SECONDS: u64 = DISPUTE_WINDOW_SECONDS;
+/// Ledgers a per-message persistent entry is kept alive for after each write (about 30 days)
+const PERSISTENT_TTL_LEDGERS: u32 = 518_400;
+/// Default seconds an undelivered message lives before it can be expired
+const DEFAULT_MESSAGE_TTL_SECONDS: u64 = 604_800;
+/// Default extra seconds an in-transit message has before it expires, so its relayer can finish delivery
+const DEFAULT_IN_TRANSIT_GRACE_SECONDS: u64 = 3_600;
+/// Reputation a relayer loses when a challenge against it is upheld
+const SLASH_REPUTATION_PENALTY: u32 = 25;
+/// Share of a relayer's stake (basis points) forfeited when a challenge against it is upheld
+const SLASH_STAKE_BPS: u32 = 1_000;
+/// Most message ids a single status query may look up
+const MAX_STATUS_QUERY: u32 = 100;
+/// Default number of follow-on messages a chain of executions may spawn
+const DEFAULT_MAX_HOPS: u32 = 8;
+/// Delivery result status: the message executed
+pub const DELIVERY_SUCCEEDED: u32 = 0;
+/// Delivery result status: execution ran but was rejected
+pub const DELIVERY_FAILED: u32 = 1;
+/// Delivery result status: execution needed more gas than the message's limit
+pub const DELIVERY_OUT_OF_GAS: u32 = 2;
+/// Delivery result status: a challenge against the reported execution was upheld
+pub const DELIVERY_CHALLENGED: u32 = 3;
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CrossChainMessage {
+    pub message_id: u64,
+    pub source_chain: u32,
+    pub target_chain: u32,
+    pub sender: Address,
+    pub recipient: Address,
+    pub message_type: MessageType,
+    pub payload: Bytes,
+    pub nonce: u64,
+    pub sequence: u64, // per (sender, target_chain)
+    pub signature: Bytes,
+    pub status: MessageStatus,
+    pub created_at: u64,
+    pub processed_at: Option<u64>,
+    pub gas_used: u64,
+    pub gas_limit: u64,
+    pub fee: u64,
+    pub encryption_scheme: EncryptionScheme,
+    pub hop_count: u32, // messages sent in response to another carry its count plus one
+}
+
+/// Message layout stored under state version 3, before the ephemeral key moved into the encryption scheme
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CrossChainMessageV3 {
+    pub message_id: u64,
+    pub source_chain: u32,
+    pub target_chain: u32,
+    pub sender: Address,
+    pub recipient: Address,
+    pub message_type: MessageType,
+    pub payload: Bytes,
+    pub nonce: u64,
+    pub sequence: u64,
+    pub signature: Bytes,
+    pub status: MessageStatus,
+    pub created_at: u64,
+    pub processed_at: Option<u64>,
+    pub gas_used: u64,
+    pub gas_limit: u64,
+    pub fee: u64,
+    pub encryption_scheme: EncryptionSchemeV3,
+    pub ephemeral_pubkey: Bytes, // empty for plaintext
+    pub hop_count: u32,
+}
+
+/// Message layout stored under state version 2, before sequencing, gas limits, encryption and hop counts
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CrossChainMessageV2 {
     pub message_id: u64,
     pub source_chain: u32,
     pub target_chain: u32,
@@ -17,6 +123,26 @@ pub struct CrossChainMessage {
     pub created_at: u64,
     pub processed_at: Option<u64>,
     pub gas_used: u64,
+    pub fee: u64,
+}
+
+/// How a payload is encrypted to its recipient, with the sender's ephemeral key for the scheme
+/// The contract carries this through but never decrypts
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EncryptionScheme {
+    Plaintext,
+    X25519ChaCha20Poly1305(BytesN<32>), // X25519 ephemeral key
+    EciesSecp256k1(BytesN<33>),         // compressed secp256k1 ephemeral key
+}
+
+/// Encryption scheme stored under state version 3, with its ephemeral key held alongside
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EncryptionSchemeV3 {
+    Plaintext,
+    X25519ChaCha20Poly1305,
+    EciesSecp256k1,
 }
 
 #[contracttype]
@@ -26,6 +152,7 @@ pub enum MessageType {
     AssetTransfer,
     AtomicSwap,
     Generic,
+    Proof,
 }
 
 #[contracttype]
@@ -36,6 +163,7 @@ pub enum MessageStatus {
     Delivered,
     Failed,
     Expired,
+    OutOfGas,
 }
 
 #[contracttype]
@@ -48,6 +176,74 @@ pub struct MessageRelayer {
     pub active: bool,
     pub total_messages: u64,
     pub success_rate: u32,
+    pub reputation: u32,
+    pub last_seen: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MessageStats {
+    pub total_messages: u64,
+    pub failed_messages: u64,
+    pub window_start: u64,
+    pub window_messages: u64,
+    pub window_failed: u64,
+}
+
+/// Outcome of delivering a message; `return_data` is the XDR encoding of a typed value:
+/// - `Proof`: `bool`, the verifier's verdict
+/// - `ProofVerification`, `AssetTransfer`, `AtomicSwap`, `Generic`: `BytesN<32>`, sha256 receipt of the delivered payload
+/// - any type with `DELIVERY_OUT_OF_GAS`: `u64`, the execution gas the message would have needed
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeliveryResult {
+    pub message_id: u64,
+    pub message_type: MessageType,
+    pub status_code: u32,
+    pub return_data: Bytes,
+    pub encryption_scheme: EncryptionScheme,
+}
+
+/// Delivery result layout stored under state version 3
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeliveryResultV3 {
+    pub message_id: u64,
+    pub message_type: MessageType,
+    pub status_code: u32,
+    pub return_data: Bytes,
+    pub encryption_scheme: EncryptionSchemeV3,
+    pub ephemeral_pubkey: Bytes,
+}
+
+/// Traffic between one source and target chain pair
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RouteStats {
+    pub messages_sent: u64,
+    pub delivered: u64,
+    pub failed: u64,
+    pub average_latency: u64, // seconds from send to delivery
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ChallengeStatus {
+    Open,
+    Upheld,
+    Rejected,
+}
+
+/// Dispute raised against a delivered message's reported execution
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExecutionChallenge {
+    pub message_id: u64,
+    pub challenger: Address,
+    pub relayer: Address,
+    pub evidence: Bytes,
+    pub opened_at: u64,
+    pub status: ChallengeStatus,
 }
 
 #[contracttype]
@@ -79,6 +275,46 @@ pub enum MessageDataKey {
     RelayerCount,
     QueueCount,
     PendingMessages,
+    InTransitMessages, // message_ids picked up by a relayer and not yet delivered
+    InTransitGrace,
+    MessageTtl,
+    ChainGasPrice(u32),
+    ReputationDecayRate,
+    ProofVerifier,
+    MessageStats,
+    FailureThreshold,
+    Paused,
+    FeeToken,
+    Treasury,
+    SweepableFees, // single balance kept before v5, paid in whatever the fee token was
+    SweepableFeesIn(Address), // fee token -> expired fees and slashed stake awaiting a sweep
+    FeePaid(u64), // message_id -> (fee token, amount the vault received); absent when no fee was taken
+    StateVersion,
+    StateCapacity,
+    MaxPending,
+    NextSequence(Address, u32),    // sender, target_chain
+    SettledSequence(Address, u32), // sender, target_chain
+    SkippedSequence(Address, u32, u64), // sender, target_chain, sequence of a message that expired before its predecessor settled
+    GasCap(MessageType),
+    FeeMultiplier(MessageType), // basis points applied to the gas fee of the type's messages
+    MinGasLimit(u32), // target chain -> smallest gas limit a message to it may carry
+    DeliveredBy(u64),
+    Challenge(u64), // persistent; challenges opened before that were kept in instance storage
+    ChallengeBond,
+    ChallengeBondPaid(u64), // message_id -> (fee token, amount the vault received) from the challenger
+    DeliveryResult(u64),
+    RouteStats(u32, u32), // source_chain, target_chain -> (sent, delivered, failed, total_latency)
+    DeadLetters, // message_ids that failed permanently, oldest first
+    Requeued(u64), // dead letters sent back to the pending queue
+    RequeueCount(u64), // message_id -> times it has been sent back from the dead-letter queue
+    RelayerStake(u64),
+    TotalStaked,
+    MinRelayerStake,
+    StakeLockup,
+    RelayerKey(u64),
+    KeyOwner(BytesN<32>), // relayer a signing key is registered to
+    Denylisted(Address), // parties messages may neither come from nor go to
+    MaxHops,
     Admin,
 }
 
@@ -98,23 +334,200 @@ impl MessagePassing {
         env.storage().instance().set(&MessageDataKey::RelayerCount, &0u64);
         env.storage().instance().set(&MessageDataKey::QueueCount, &0u64);
         env.storage().instance().set(&MessageDataKey::PendingMessages, &Vec::new(&env));
+        env.storage().instance().set(&MessageDataKey::MaxPending, &capacity::DEFAULT_MAX_PENDING);
+        env.storage().instance().set(&MessageDataKey::StateVersion, &STATE_VERSION);
+    }
+
+    /// Get the layout version of stored state (state predating versioning is v1)
+    pub fn get_state_version(env: Env) -> u32 {
+        env.storage().instance().get(&MessageDataKey::StateVersion).unwrap_or(1)
+    }
+
+    /// Upgrade state written by an older contract version to the current layout
+    pub fn migrate(env: Env, admin: Address) -> u32 {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let version = Self::get_state_version(env.clone());
+        if version > STATE_VERSION {
+            panic!("Stored state is newer than this contract");
+        }
+        
+        // v1 -> v2: the version is now tracked explicitly, existing entries are kept as-is
+        // v2 -> v3: messages in the v2 layout are upgraded as they are read and rewritten on their next update
+        // v3 -> v4: messages and delivery results with a separate ephemeral key are upgraded the same way
+        // v4 -> v5: the single sweepable balance was collected in the current fee token and moves to its per-token balance
+        let legacy_sweepable: u64 = env.storage().instance().get(&MessageDataKey::SweepableFees).unwrap_or(0);
+        if legacy_sweepable > 0 {
+            let fee_token = Self::get_fee_token(env.clone())
+                .unwrap_or_else(|| panic!("Fee token not set"));
+            Self::add_sweepable_fees(env.clone(), &fee_token, legacy_sweepable);
+        }
+        env.storage().instance().remove(&MessageDataKey::SweepableFees);
+        env.storage().instance().set(&MessageDataKey::StateVersion, &STATE_VERSION);
+        
+        STATE_VERSION
+    }
+
+    /// Get the byte capacity growing state lists may occupy
+    pub fn get_state_capacity(env: Env) -> u32 {
+        env.storage().instance()
+            .get(&MessageDataKey::StateCapacity)
+            .unwrap_or(capacity::DEFAULT_STATE_CAPACITY)
+    }
+
+    /// Grow the state capacity ahead of demand, prepaying rent for the larger entry
+    pub fn realloc_state(env: Env, admin: Address, new_size: u32) -> u32 {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let current = Self::get_state_capacity(env.clone());
+        let new_size = capacity::realloc(&env, current, new_size);
+        env.storage().instance().set(&MessageDataKey::StateCapacity, &new_size);
+        
+        new_size
     }
 
     /// Send cross-chain message
+    /// `gas_limit` must cover the message's execution, meet the target chain's minimum and stay within its type's cap
+    /// A payload encrypted to the recipient names its scheme, which carries the ephemeral key needed to decrypt it
     pub fn send_message(
         env: Env,
         target_chain: u32,
         recipient: Address,
         message_type: MessageType,
         payload: Bytes,
+        gas_limit: u64,
+        sender: Address,
+        signature: Bytes,
+        encryption_scheme: Option<EncryptionScheme>,
+    ) -> u64 {
+        sender.require_auth();
+        
+        let encryption_scheme = encryption_scheme.unwrap_or(EncryptionScheme::Plaintext);
+        Self::create_message(env, target_chain, recipient, message_type, payload, gas_limit, sender, signature, encryption_scheme, 0)
+    }
+
+    /// Send a plaintext message in response to executing `parent_message_id`, e.g. a confirmation
+    /// Only the parent's recipient may respond, and only once it has been delivered;
+    /// each response is one hop further from the original message and is rejected past the hop limit
+    pub fn send_chained_message(
+        env: Env,
+        parent_message_id: u64,
+        target_chain: u32,
+        recipient: Address,
+        message_type: MessageType,
+        payload: Bytes,
+        gas_limit: u64,
         sender: Address,
         signature: Bytes,
     ) -> u64 {
         sender.require_auth();
         
+        let parent = Self::get_message(env.clone(), parent_message_id);
+        if parent.recipient != sender {
+            panic!("Only the parent's recipient can respond");
+        }
+        if parent.status != MessageStatus::Delivered {
+            panic!("Parent message not delivered");
+        }
+        
+        let hop_count = parent.hop_count + 1;
+        if hop_count > Self::get_max_hops(env.clone()) {
+            panic!("Hop limit exceeded");
+        }
+        
+        Self::create_message(env, target_chain, recipient, message_type, payload, gas_limit, sender, signature, EncryptionScheme::Plaintext, hop_count)
+    }
+
+    /// Set how many hops a chain of responses may run from the message that started it
+    pub fn set_max_hops(env: Env, admin: Address, max_hops: u32) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&MessageDataKey::MaxHops, &max_hops);
+    }
+
+    /// Get how many hops a chain of responses may run
+    pub fn get_max_hops(env: Env) -> u32 {
+        env.storage().instance()
+            .get(&MessageDataKey::MaxHops)
+            .unwrap_or(DEFAULT_MAX_HOPS)
+    }
+
+    /// Store a new pending message, charging its fee; callers have checked the sender's authorization
+    fn create_message(
+        env: Env,
+        target_chain: u32,
+        recipient: Address,
+        message_type: MessageType,
+        payload: Bytes,
+        gas_limit: u64,
+        sender: Address,
+        signature: Bytes,
+        encryption_scheme: EncryptionScheme,
+        hop_count: u32,
+    ) -> u64 {
+        if Self::is_paused(env.clone()) {
+            panic!("Message sending paused");
+        }
+        
+        if Self::is_denylisted(env.clone(), sender.clone()) || Self::is_denylisted(env.clone(), recipient.clone()) {
+            panic!("Party denylisted");
+        }
+        
+        capacity::ensure_below_max_pending(
+            Self::get_pending_messages(env.clone()).len() as u64,
+            Self::get_max_pending(env.clone()),
+        );
+        
         let count: u64 = env.storage().instance().get(&MessageDataKey::MessageCount).unwrap_or(0);
         let message_id = count + 1;
         
+        // An underpriced gas limit is rejected here rather than failing at delivery
+        if gas_limit < Self::get_min_gas_limit(env.clone(), target_chain) {
+            panic!("Gas limit below chain minimum");
+        }
+        if gas_limit > Self::get_gas_cap(env.clone(), message_type.clone()) {
+            panic!("Gas limit exceeds message type cap");
+        }
+        let execution_gas = Self::estimate_execution_gas(env.clone(), message_type.clone(), payload.len());
+        if execution_gas > gas_limit {
+            panic!("Gas limit below estimated execution gas");
+        }
+        
+        // Charge up front for the gas the message will consume end to end
+        let fee = Self::compute_fee(env.clone(), execution_gas, target_chain, message_type.clone());
+        let paid = Self::collect_fee(env.clone(), sender.clone(), fee);
+        if let Some(paid) = paid {
+            env.storage().instance().set(&MessageDataKey::FeePaid(message_id), &paid);
+        }
+        
+        let sequence_key = MessageDataKey::NextSequence(sender.clone(), target_chain);
+        let sequence: u64 = env.storage().instance().get(&sequence_key).unwrap_or(1);
+        env.storage().instance().set(&sequence_key, &(sequence + 1));
+        
         let message = CrossChainMessage {
             message_id,
             source_chain: Self::get_current_chain_id(env.clone()),
@@ -124,13 +537,19 @@ impl MessagePassing {
             message_type: message_type.clone(),
             payload: payload.clone(),
             nonce: count,
+            sequence,
             signature: signature.clone(),
             status: MessageStatus::Pending,
             created_at: env.ledger().timestamp(),
             processed_at: None,
             gas_used: 0,
+            gas_limit,
+            fee,
+            encryption_scheme,
+            hop_count,
         };
         
+        capacity::ensure_fits(&env, &message, Self::get_state_capacity(env.clone()));
         env.storage().instance().set(&MessageDataKey::CrossChainMessage(message_id), &message);
         env.storage().instance().set(&MessageDataKey::MessageCount, &message_id);
         
@@ -139,8 +558,12 @@ impl MessagePassing {
             .get(&MessageDataKey::PendingMessages)
             .unwrap_or(Vec::new(&env));
         pending.push_back(message_id);
+        capacity::ensure_fits(&env, &pending, Self::get_state_capacity(env.clone()));
         env.storage().instance().set(&MessageDataKey::PendingMessages, &pending);
         
+        Self::record_message_sent(env.clone());
+        Self::record_route_sent(env.clone(), &message);
+        
         message_id
     }
 
@@ -148,13 +571,90 @@ impl MessagePassing {
     pub fn process_message(env: Env, message_id: u64, relayer: Address) -> bool {
         relayer.require_auth();
         
+        Self::start_processing(env, message_id, relayer)
+    }
+
+    /// Register the ed25519 key a relayer signs relayed messages with, replacing any earlier key
+    /// `signature` is the key's signature over the key registration payload, proving the relayer holds it;
+    /// a key can belong to only one relayer
+    pub fn set_relayer_key(env: Env, relayer_id: u64, public_key: BytesN<32>, signature: BytesN<64>) {
+        let relayer = Self::get_relayer(env.clone(), relayer_id);
+        relayer.address.require_auth();
+        
+        let owner_key = MessageDataKey::KeyOwner(public_key.clone());
+        if let Some(owner) = env.storage().instance().get::<MessageDataKey, u64>(&owner_key) {
+            if owner != relayer_id {
+                panic!("Key registered to another relayer");
+            }
+        }
+        
+        // Panics if the signature does not match
+        let payload = Self::get_key_registration_payload(env.clone(), relayer_id);
+        env.crypto().ed25519_verify(&public_key, &payload, &signature);
+        
+        if let Some(old_key) = Self::get_relayer_key(env.clone(), relayer_id) {
+            env.storage().instance().remove(&MessageDataKey::KeyOwner(old_key));
+        }
+        env.storage().instance().set(&MessageDataKey::RelayerKey(relayer_id), &public_key);
+        env.storage().instance().set(&owner_key, &relayer_id);
+    }
+
+    /// Get the canonical bytes a key signs to be registered for a relayer
+    pub fn get_key_registration_payload(env: Env, relayer_id: u64) -> Bytes {
+        let relayer = Self::get_relayer(env.clone(), relayer_id);
+        
+        (env.current_contract_address(), relayer_id, relayer.address).to_xdr(&env)
+    }
+
+    /// Get the ed25519 key a relayer signs relayed messages with
+    pub fn get_relayer_key(env: Env, relayer_id: u64) -> Option<BytesN<32>> {
+        env.storage().instance().get(&MessageDataKey::RelayerKey(relayer_id))
+    }
+
+    /// Get the canonical bytes a relayer signs to relay a message
+    /// The payload covers how often the message has been requeued, so a signature is good for one attempt only
+    pub fn get_relay_payload(env: Env, message_id: u64, relayer_id: u64) -> Bytes {
+        let message: CrossChainMessage = Self::load_message(env.clone(), message_id)
+            .unwrap_or_else(|| panic!("Message not found"));
+        let requeue_count: u32 = env.storage().instance()
+            .get(&MessageDataKey::RequeueCount(message_id))
+            .unwrap_or(0);
+        
+        (
+            env.current_contract_address(),
+            relayer_id,
+            message.message_id,
+            message.source_chain,
+            message.target_chain,
+            message.sender,
+            message.recipient,
+            message.payload,
+            message.sequence,
+            requeue_count,
+        ).to_xdr(&env)
+    }
+
+    /// Process a message on the strength of the relayer's ed25519 signature over its relay payload
+    /// Lets anyone submit on a relayer's behalf; the signature rather than the caller identifies the relayer
+    pub fn relay_message(env: Env, message_id: u64, relayer_id: u64, signature: BytesN<64>) -> bool {
+        let relayer = Self::get_relayer(env.clone(), relayer_id);
+        let public_key = Self::get_relayer_key(env.clone(), relayer_id)
+            .unwrap_or_else(|| panic!("Relayer key not set"));
+        
+        // Panics if the signature does not match
+        let payload = Self::get_relay_payload(env.clone(), message_id, relayer_id);
+        env.crypto().ed25519_verify(&public_key, &payload, &signature);
+        
+        Self::start_processing(env, message_id, relayer.address)
+    }
+
+    fn start_processing(env: Env, message_id: u64, relayer: Address) -> bool {
         // Check if relayer is registered
         if !Self::is_relayer_active(env.clone(), relayer.clone()) {
             panic!("Relayer not active");
         }
         
-        let mut message: CrossChainMessage = env.storage().instance()
-            .get(&MessageDataKey::CrossChainMessage(message_id))
+        let mut message: CrossChainMessage = Self::load_message(env.clone(), message_id)
             .unwrap_or_else(|| panic!("Message not found"));
         
         if message.status != MessageStatus::Pending {
@@ -164,12 +664,15 @@ impl MessagePassing {
         // Update message status
         message.status = MessageStatus::InTransit;
         message.processed_at = Some(env.ledger().timestamp());
-        message.gas_used = Self::estimate_message_gas(env.clone(), message.payload.len());
+        message.gas_used = Self::estimate_message_gas(env.clone(), message.payload.len()).min(message.gas_limit);
         
         env.storage().instance().set(&MessageDataKey::CrossChainMessage(message_id), &message);
         
         // Remove from pending messages
         Self::remove_from_pending(env.clone(), message_id);
+        let mut in_transit = Self::get_in_transit_messages(env.clone());
+        in_transit.push_back(message_id);
+        env.storage().instance().set(&MessageDataKey::InTransitMessages, &in_transit);
         
         // Update relayer stats
         Self::update_relayer_stats(env.clone(), relayer.clone(), true);
@@ -181,72 +684,160 @@ impl MessagePassing {
     pub fn deliver_message(env: Env, message_id: u64, delivery_proof: Bytes, relayer: Address) -> bool {
         relayer.require_auth();
         
-        let mut message: CrossChainMessage = env.storage().instance()
-            .get(&MessageDataKey::CrossChainMessage(message_id))
+        let mut message: CrossChainMessage = Self::load_message(env.clone(), message_id)
             .unwrap_or_else(|| panic!("Message not found"));
         
         if message.status != MessageStatus::InTransit {
             panic!("Message not in transit");
         }
         
+        // Only a registered relayer can answer for the delivery if it is challenged
+        if !Self::is_relayer_active(env.clone(), relayer.clone()) {
+            panic!("Relayer not active");
+        }
+        
+        // Messages from a sender to a chain execute in the order they were sent; requeued dead letters already had their turn
+        // and messages sent before sequencing (sequence 0) are unordered
+        if !env.storage().instance().has(&MessageDataKey::Requeued(message_id))
+            && message.sequence != 0
+            && message.sequence != Self::get_settled_sequence(env.clone(), message.sender.clone(), message.target_chain) + 1
+        {
+            panic!("Message out of order: predecessor not yet executed");
+        }
+        
         // Verify delivery proof (simplified)
         if !Self::verify_delivery_proof(env.clone(), delivery_proof.clone()) {
             panic!("Invalid delivery proof");
         }
         
-        message.status = MessageStatus::Delivered;
+        // Every outcome from here on settles the message
+        Self::remove_from_in_transit(env.clone(), message_id);
+        
+        // Messages stored before the send-time gas check, or requeued under a smaller cap, can still run out
+        let execution_gas = Self::estimate_execution_gas(env.clone(), message.message_type.clone(), message.payload.len());
+        if execution_gas > message.gas_limit {
+            Self::fail_out_of_gas(env.clone(), &mut message);
+            Self::store_delivery_result(env.clone(), &message, DELIVERY_OUT_OF_GAS, execution_gas.to_xdr(&env));
+            return false;
+        }
+        
+        // Proof payloads only count as delivered once the verifier accepts them
+        let (executed, return_data) = match message.message_type {
+            MessageType::Proof => {
+                let verified = Self::execute_proof_message(env.clone(), &mut message);
+                (verified, verified.to_xdr(&env))
+            }
+            _ => (true, env.crypto().sha256(&message.payload).to_xdr(&env)),
+        };
+        
+        message.status = if executed { MessageStatus::Delivered } else { MessageStatus::Failed };
         message.processed_at = Some(env.ledger().timestamp());
         
         env.storage().instance().set(&MessageDataKey::CrossChainMessage(message_id), &message);
+        env.storage().instance().set(&MessageDataKey::DeliveredBy(message_id), &relayer);
+        Self::settle_sequence(env.clone(), &message);
         
-        true
+        if !executed {
+            Self::record_message_failed(env.clone());
+            Self::add_dead_letter(env.clone(), message_id);
+        }
+        Self::record_route_outcome(env.clone(), &message, executed);
+        
+        let status_code = if executed { DELIVERY_SUCCEEDED } else { DELIVERY_FAILED };
+        Self::store_delivery_result(env.clone(), &message, status_code, return_data);
+        
+        executed
     }
 
-    /// Register message relayer
-    pub fn register_relayer(
-        env: Env,
-        admin: Address,
-        relayer_address: Address,
-        supported_chains: Vec<u32>,
-        fee_percentage: u32,
-    ) -> u64 {
-        let stored_admin: Address = env.storage().instance()
-            .get(&MessageDataKey::Admin)
-            .unwrap_or_else(|| panic!("Admin not found"));
+    /// Get message traffic between a source and target chain
+    pub fn get_route_stats(env: Env, source_chain: u32, target_chain: u32) -> RouteStats {
+        let (sent, delivered, failed, total_latency): (u64, u64, u64, u64) = env.storage().instance()
+            .get(&MessageDataKey::RouteStats(source_chain, target_chain))
+            .unwrap_or((0, 0, 0, 0));
         
-        if admin != stored_admin {
-            panic!("Not authorized");
+        RouteStats {
+            messages_sent: sent,
+            delivered,
+            failed,
+            average_latency: if delivered == 0 { 0 } else { total_latency / delivered },
         }
+    }
+
+    /// Get the result recorded when a message was delivered
+    pub fn get_delivery_result(env: Env, message_id: u64) -> Option<DeliveryResult> {
+        let raw: Val = env.storage().instance().get(&MessageDataKey::DeliveryResult(message_id))?;
         
-        admin.require_auth();
+        if let Ok(result) = DeliveryResult::try_from_val(&env, &raw) {
+            return Some(result);
+        }
         
-        let count: u64 = env.storage().instance().get(&MessageDataKey::RelayerCount).unwrap_or(0);
-        let relayer_id = count + 1;
+        let v3 = DeliveryResultV3::try_from_val(&env, &raw).unwrap_or_else(|_| panic!("Unknown delivery result layout"));
+        Some(DeliveryResult {
+            message_id: v3.message_id,
+            message_type: v3.message_type,
+            status_code: v3.status_code,
+            return_data: v3.return_data,
+            encryption_scheme: Self::upgrade_v3_scheme(v3.encryption_scheme, v3.ephemeral_pubkey),
+        })
+    }
+
+    /// Dispute a delivered message's execution within the dispute window; anyone may challenge by posting the bond
+    /// The bond is returned if the challenge is upheld and swept to the treasury if it is rejected
+    pub fn challenge_execution(env: Env, message_id: u64, evidence: Bytes, challenger: Address) {
+        challenger.require_auth();
         
-        let relayer = MessageRelayer {
-            relayer_id,
-            address: relayer_address.clone(),
-            supported_chains: supported_chains.clone(),
-            fee_percentage,
-            active: true,
-            total_messages: 0,
-            success_rate: 100,
+        let message: CrossChainMessage = Self::load_message(env.clone(), message_id)
+            .unwrap_or_else(|| panic!("Message not found"));
+        
+        if message.status != MessageStatus::Delivered {
+            panic!("Message not delivered");
+        }
+        
+        let delivered_at = message.processed_at.unwrap_or(0);
+        if env.ledger().timestamp() > delivered_at + DISPUTE_WINDOW_SECONDS {
+            panic!("Dispute window closed");
+        }
+        
+        if Self::get_challenge(env.clone(), message_id).is_some() {
+            panic!("Execution already challenged");
+        }
+        
+        let relayer: Address = env.storage().instance()
+            .get(&MessageDataKey::DeliveredBy(message_id))
+            .unwrap_or_else(|| panic!("Delivering relayer unknown"));
+        
+        let challenge = ExecutionChallenge {
+            message_id,
+            challenger: challenger.clone(),
+            relayer,
+            evidence,
+            opened_at: env.ledger().timestamp(),
+            status: ChallengeStatus::Open,
         };
+        capacity::ensure_fits(&env, &challenge, Self::get_state_capacity(env.clone()));
         
-        env.storage().instance().set(&MessageDataKey::MessageRelayer(relayer_id), &relayer);
-        env.storage().instance().set(&MessageDataKey::RelayerCount, &relayer_id);
+        let bond = Self::get_challenge_bond(env.clone());
+        if bond > 0 {
+            let (fee_token, received) = Self::collect_fee(env.clone(), challenger.clone(), bond)
+                .unwrap_or_else(|| panic!("Fee token not set"));
+            let bond_key = MessageDataKey::ChallengeBondPaid(message_id);
+            env.storage().persistent().set(&bond_key, &(fee_token, received));
+            Self::extend_persistent_ttl(env.clone(), &bond_key);
+        }
         
-        relayer_id
+        let key = MessageDataKey::Challenge(message_id);
+        env.storage().persistent().set(&key, &challenge);
+        Self::extend_persistent_ttl(env.clone(), &key);
+        
+        env.events().publish(
+            (Symbol::new(&env, "execution_challenged"), message_id),
+            challenger
+        );
     }
 
-    /// Create message queue for chain
-    pub fn create_queue(
-        env: Env,
-        admin: Address,
-        chain_id: u32,
-        priority: QueuePriority,
-        max_size: u32,
-    ) -> u64 {
+    /// Rule on an open challenge; upholding it marks the message failed and slashes the relayer
+    /// A rejected challenge is cleared so the execution can be challenged again
+    pub fn resolve_challenge(env: Env, admin: Address, message_id: u64, upheld: bool) {
         let stored_admin: Address = env.storage().instance()
             .get(&MessageDataKey::Admin)
             .unwrap_or_else(|| panic!("Admin not found"));
@@ -257,7 +848,649 @@ impl MessagePassing {
         
         admin.require_auth();
         
-        let count: u64 = env.storage().instance().get(&MessageDataKey::QueueCount).unwrap_or(0);
+        let mut challenge = Self::get_challenge(env.clone(), message_id)
+            .unwrap_or_else(|| panic!("Challenge not found"));
+        
+        if challenge.status != ChallengeStatus::Open {
+            panic!("Challenge already resolved");
+        }
+        
+        if upheld {
+            let mut message: CrossChainMessage = Self::load_message(env.clone(), message_id)
+                .unwrap_or_else(|| panic!("Message not found"));
+            if message.status == MessageStatus::Delivered {
+                Self::record_route_overturned(env.clone(), &message);
+            }
+            message.status = MessageStatus::Failed;
+            env.storage().instance().set(&MessageDataKey::CrossChainMessage(message_id), &message);
+            
+            // The reported result no longer stands
+            if let Some(mut result) = Self::get_delivery_result(env.clone(), message_id) {
+                result.status_code = DELIVERY_CHALLENGED;
+                env.storage().instance().set(&MessageDataKey::DeliveryResult(message_id), &result);
+            }
+            
+            Self::slash_relayer(env.clone(), challenge.relayer.clone());
+            Self::record_message_failed(env.clone());
+            Self::add_dead_letter(env.clone(), message_id);
+            challenge.status = ChallengeStatus::Upheld;
+        } else {
+            challenge.status = ChallengeStatus::Rejected;
+        }
+        
+        // The bond goes back to an upheld challenger; a rejected one forfeits it
+        let bond_key = MessageDataKey::ChallengeBondPaid(message_id);
+        if let Some((fee_token, bond)) = env.storage().persistent().get::<MessageDataKey, (Address, u64)>(&bond_key) {
+            if upheld {
+                fees::release_fee(&env, &fee_token, &challenge.challenger, bond as i128);
+            } else {
+                Self::add_sweepable_fees(env.clone(), &fee_token, bond);
+            }
+            env.storage().persistent().remove(&bond_key);
+        }
+        
+        env.storage().instance().remove(&MessageDataKey::Challenge(message_id));
+        let key = MessageDataKey::Challenge(message_id);
+        if upheld {
+            env.storage().persistent().set(&key, &challenge);
+            Self::extend_persistent_ttl(env.clone(), &key);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "challenge_resolved"), message_id),
+            upheld
+        );
+    }
+
+    /// Get the challenge raised against a message's execution, if any
+    pub fn get_challenge(env: Env, message_id: u64) -> Option<ExecutionChallenge> {
+        let key = MessageDataKey::Challenge(message_id);
+        env.storage().persistent().get(&key)
+            .or_else(|| env.storage().instance().get(&key))
+    }
+
+    /// Set the bond a challenger must post, in the fee token
+    pub fn set_challenge_bond(env: Env, admin: Address, bond: u64) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&MessageDataKey::ChallengeBond, &bond);
+    }
+
+    /// Get the bond a challenger must post, in the fee token
+    pub fn get_challenge_bond(env: Env) -> u64 {
+        env.storage().instance()
+            .get(&MessageDataKey::ChallengeBond)
+            .unwrap_or(DEFAULT_CHALLENGE_BOND)
+    }
+
+    /// Set how many items may be pending at once before new submissions are rejected
+    pub fn set_max_pending(env: Env, admin: Address, max_pending: u32) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        if max_pending == 0 {
+            panic!("Max pending must be positive");
+        }
+        
+        env.storage().instance().set(&MessageDataKey::MaxPending, &max_pending);
+    }
+
+    /// Get how many items may be pending at once
+    pub fn get_max_pending(env: Env) -> u32 {
+        env.storage().instance()
+            .get(&MessageDataKey::MaxPending)
+            .unwrap_or(capacity::DEFAULT_MAX_PENDING)
+    }
+
+    /// Set the gas limit messages of a type are executed under
+    pub fn set_gas_cap(env: Env, admin: Address, message_type: MessageType, gas_cap: u64) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&MessageDataKey::GasCap(message_type), &gas_cap);
+    }
+
+    /// Get the gas limit messages of a type are executed under
+    pub fn get_gas_cap(env: Env, message_type: MessageType) -> u64 {
+        env.storage().instance()
+            .get(&MessageDataKey::GasCap(message_type))
+            .unwrap_or(DEFAULT_GAS_CAP)
+    }
+
+    /// Set the multiplier (basis points) applied to the gas fee of a message type
+    pub fn set_fee_multiplier(env: Env, admin: Address, message_type: MessageType, multiplier_bps: u32) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        if multiplier_bps == 0 || multiplier_bps > MAX_FEE_MULTIPLIER_BPS {
+            panic!("Invalid fee multiplier");
+        }
+        
+        env.storage().instance().set(&MessageDataKey::FeeMultiplier(message_type), &multiplier_bps);
+    }
+
+    /// Get the multiplier (basis points) applied to the gas fee of a message type, 1x by default
+    pub fn get_fee_multiplier(env: Env, message_type: MessageType) -> u32 {
+        env.storage().instance()
+            .get(&MessageDataKey::FeeMultiplier(message_type))
+            .unwrap_or(fees::FEE_DENOMINATOR)
+    }
+
+    /// Set the failure rate (basis points) that trips the circuit breaker
+    pub fn set_failure_threshold(env: Env, admin: Address, threshold_bps: u32) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        if threshold_bps > fees::FEE_DENOMINATOR {
+            panic!("Threshold exceeds 100%");
+        }
+        
+        env.storage().instance().set(&MessageDataKey::FailureThreshold, &threshold_bps);
+    }
+
+    /// Get the failure rate (basis points) that trips the circuit breaker
+    pub fn get_failure_threshold(env: Env) -> u32 {
+        env.storage().instance()
+            .get(&MessageDataKey::FailureThreshold)
+            .unwrap_or(DEFAULT_FAILURE_THRESHOLD_BPS)
+    }
+
+    /// Resume message sending after the circuit breaker tripped
+    pub fn resume_sending(env: Env, admin: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        // Start a fresh window so the old failures don't immediately re-trip
+        let mut stats = Self::get_message_stats(env.clone());
+        stats.window_start = env.ledger().timestamp();
+        stats.window_messages = 0;
+        stats.window_failed = 0;
+        env.storage().instance().set(&MessageDataKey::MessageStats, &stats);
+        env.storage().instance().set(&MessageDataKey::Paused, &false);
+    }
+
+    /// Check whether the circuit breaker has paused message sending
+    pub fn is_paused(env: Env) -> bool {
+        env.storage().instance().get(&MessageDataKey::Paused).unwrap_or(false)
+    }
+
+    /// Get message delivery statistics
+    pub fn get_message_stats(env: Env) -> MessageStats {
+        env.storage().instance()
+            .get(&MessageDataKey::MessageStats)
+            .unwrap_or(MessageStats {
+                total_messages: 0,
+                failed_messages: 0,
+                window_start: env.ledger().timestamp(),
+                window_messages: 0,
+                window_failed: 0,
+            })
+    }
+
+    /// Set the chain verifier contract that executes Proof messages
+    pub fn set_proof_verifier(env: Env, admin: Address, verifier: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&MessageDataKey::ProofVerifier, &verifier);
+    }
+
+    /// Get the chain verifier contract that executes Proof messages
+    pub fn get_proof_verifier(env: Env) -> Option<Address> {
+        env.storage().instance().get(&MessageDataKey::ProofVerifier)
+    }
+
+    /// Register message relayer
+    pub fn register_relayer(
+        env: Env,
+        admin: Address,
+        relayer_address: Address,
+        supported_chains: Vec<u32>,
+        fee_percentage: u32,
+    ) -> u64 {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let count: u64 = env.storage().instance().get(&MessageDataKey::RelayerCount).unwrap_or(0);
+        let relayer_id = count + 1;
+        
+        let relayer = MessageRelayer {
+            relayer_id,
+            address: relayer_address.clone(),
+            supported_chains: supported_chains.clone(),
+            fee_percentage,
+            active: true,
+            total_messages: 0,
+            success_rate: 100,
+            reputation: REPUTATION_MAX,
+            last_seen: env.ledger().timestamp(),
+        };
+        
+        capacity::ensure_fits(&env, &relayer, Self::get_state_capacity(env.clone()));
+        env.storage().instance().set(&MessageDataKey::MessageRelayer(relayer_id), &relayer);
+        env.storage().instance().set(&MessageDataKey::RelayerCount, &relayer_id);
+        
+        relayer_id
+    }
+
+    /// Set the stake a relayer must hold to process messages
+    pub fn set_min_relayer_stake(env: Env, admin: Address, amount: u64) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&MessageDataKey::MinRelayerStake, &amount);
+    }
+
+    /// Get the stake a relayer must hold to process messages
+    pub fn get_min_relayer_stake(env: Env) -> u64 {
+        env.storage().instance().get(&MessageDataKey::MinRelayerStake).unwrap_or(0)
+    }
+
+    /// Deposit fee tokens as a relayer's refundable stake
+    pub fn stake_relayer(env: Env, relayer_id: u64, amount: u64) -> u64 {
+        let relayer = Self::get_relayer(env.clone(), relayer_id);
+        relayer.address.require_auth();
+        
+        let fee_token = Self::get_fee_token(env.clone())
+            .unwrap_or_else(|| panic!("Fee token not set"));
+        
+        // Credit what the vault received, not what was sent
+        let received = fees::collect_fee(&env, &fee_token, &relayer.address, amount as i128) as u64;
+        let stake = Self::get_relayer_stake(env.clone(), relayer_id)
+            .checked_add(received)
+            .unwrap_or_else(|| panic!("Stake overflow"));
+        Self::set_relayer_stake(env.clone(), relayer_id, stake);
+        
+        stake
+    }
+
+    /// Set how long a relayer's stake stays locked after registration or its last relay
+    pub fn set_stake_lockup(env: Env, admin: Address, seconds: u64) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&MessageDataKey::StakeLockup, &seconds);
+    }
+
+    /// Get how long a relayer's stake stays locked after registration or its last relay
+    pub fn get_stake_lockup(env: Env) -> u64 {
+        env.storage().instance().get(&MessageDataKey::StakeLockup).unwrap_or(DEFAULT_STAKE_LOCKUP_SECONDS)
+    }
+
+    /// Get when a relayer's stake can next be withdrawn; each relay pushes this back
+    pub fn get_stake_unlocks_at(env: Env, relayer_id: u64) -> u64 {
+        let relayer = Self::get_relayer(env.clone(), relayer_id);
+        relayer.last_seen.saturating_add(Self::get_stake_lockup(env))
+    }
+
+    /// Withdraw part of a relayer's stake; dropping below the minimum deactivates it
+    /// Rejected while the stake is locked, so it stays slashable until recent relays can be challenged
+    pub fn withdraw_relayer_stake(env: Env, relayer_id: u64, amount: u64) -> u64 {
+        let mut relayer = Self::get_relayer(env.clone(), relayer_id);
+        relayer.address.require_auth();
+        
+        if env.ledger().timestamp() < Self::get_stake_unlocks_at(env.clone(), relayer_id) {
+            panic!("Stake locked");
+        }
+        
+        let fee_token = Self::get_fee_token(env.clone())
+            .unwrap_or_else(|| panic!("Fee token not set"));
+        
+        let stake = Self::get_relayer_stake(env.clone(), relayer_id)
+            .checked_sub(amount)
+            .unwrap_or_else(|| panic!("Insufficient stake"));
+        Self::set_relayer_stake(env.clone(), relayer_id, stake);
+        
+        if stake < Self::get_min_relayer_stake(env.clone()) && relayer.active {
+            relayer.active = false;
+            env.storage().instance().set(&MessageDataKey::MessageRelayer(relayer_id), &relayer);
+        }
+        
+        fees::release_fee(&env, &fee_token, &relayer.address, amount as i128);
+        
+        stake
+    }
+
+    /// Get a relayer's current stake
+    pub fn get_relayer_stake(env: Env, relayer_id: u64) -> u64 {
+        env.storage().instance()
+            .get(&MessageDataKey::RelayerStake(relayer_id))
+            .unwrap_or(0)
+    }
+
+    /// Get (total staked, active relayer count, minimum stake) across all relayers
+    pub fn get_relayer_economics(env: Env) -> (u64, u64, u64) {
+        let total_staked: u64 = env.storage().instance().get(&MessageDataKey::TotalStaked).unwrap_or(0);
+        let count: u64 = env.storage().instance().get(&MessageDataKey::RelayerCount).unwrap_or(0);
+        
+        let mut active_count = 0u64;
+        for i in 1..=count {
+            if let Some(relayer_info) = env.storage().instance().get::<MessageDataKey, MessageRelayer>(&MessageDataKey::MessageRelayer(i)) {
+                if relayer_info.active && Self::meets_min_stake(env.clone(), i) {
+                    active_count += 1;
+                }
+            }
+        }
+        
+        (total_staked, active_count, Self::get_min_relayer_stake(env))
+    }
+
+    /// Set the gas price charged for delivering messages to a chain
+    pub fn set_chain_gas_price(env: Env, admin: Address, chain_id: u32, gas_price: u64) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&MessageDataKey::ChainGasPrice(chain_id), &gas_price);
+    }
+
+    /// Get the gas price charged for delivering messages to a chain
+    pub fn get_chain_gas_price(env: Env, chain_id: u32) -> u64 {
+        env.storage().instance()
+            .get(&MessageDataKey::ChainGasPrice(chain_id))
+            .unwrap_or(0)
+    }
+
+    /// Set the smallest gas limit a message to a chain may be sent with
+    /// The minimum may not exceed any message type's gas cap, or that type could never reach the chain
+    pub fn set_min_gas_limit(env: Env, admin: Address, chain_id: u32, min_gas_limit: u64) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        for message_type in [
+            MessageType::ProofVerification,
+            MessageType::AssetTransfer,
+            MessageType::AtomicSwap,
+            MessageType::Generic,
+            MessageType::Proof,
+        ] {
+            if min_gas_limit > Self::get_gas_cap(env.clone(), message_type) {
+                panic!("Minimum gas limit exceeds a gas cap");
+            }
+        }
+        
+        env.storage().instance().set(&MessageDataKey::MinGasLimit(chain_id), &min_gas_limit);
+    }
+
+    /// Get the smallest gas limit a message to a chain may be sent with (0 means no minimum)
+    pub fn get_min_gas_limit(env: Env, chain_id: u32) -> u64 {
+        env.storage().instance()
+            .get(&MessageDataKey::MinGasLimit(chain_id))
+            .unwrap_or(0)
+    }
+
+    /// Check whether a chain has been configured with a gas price
+    pub fn is_chain_supported(env: Env, chain_id: u32) -> bool {
+        env.storage().instance().has(&MessageDataKey::ChainGasPrice(chain_id))
+    }
+
+    /// Block an address from sending or receiving messages
+    pub fn add_to_denylist(env: Env, admin: Address, party: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let key = MessageDataKey::Denylisted(party);
+        env.storage().persistent().set(&key, &true);
+        Self::extend_persistent_ttl(env, &key);
+    }
+
+    /// Lift a denylist block
+    pub fn remove_from_denylist(env: Env, admin: Address, party: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().persistent().remove(&MessageDataKey::Denylisted(party));
+    }
+
+    /// Check whether an address is denylisted; each check keeps the entry alive
+    pub fn is_denylisted(env: Env, party: Address) -> bool {
+        let key = MessageDataKey::Denylisted(party);
+        if !env.storage().persistent().has(&key) {
+            return false;
+        }
+        Self::extend_persistent_ttl(env, &key);
+        true
+    }
+
+    /// Set the token fees are collected in
+    pub fn set_fee_token(env: Env, admin: Address, fee_token: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&MessageDataKey::FeeToken, &fee_token);
+    }
+
+    /// Get the token fees are collected in
+    pub fn get_fee_token(env: Env) -> Option<Address> {
+        env.storage().instance().get(&MessageDataKey::FeeToken)
+    }
+
+    /// Get the fee vault collected fees are sent to
+    pub fn get_fee_vault(env: Env) -> Address {
+        fees::fee_vault(&env)
+    }
+
+    /// Set the treasury that swept fees are sent to
+    pub fn set_treasury(env: Env, admin: Address, treasury: Address) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&MessageDataKey::Treasury, &treasury);
+    }
+
+    /// Get the treasury that swept fees are sent to
+    pub fn get_treasury(env: Env) -> Option<Address> {
+        env.storage().instance().get(&MessageDataKey::Treasury)
+    }
+
+    /// Get fees from expired messages awaiting a sweep in `fee_token`
+    pub fn get_sweepable_fees(env: Env, fee_token: Address) -> u64 {
+        env.storage().instance().get(&MessageDataKey::SweepableFeesIn(fee_token)).unwrap_or(0)
+    }
+
+    /// Sweep fees from expired messages paid in `fee_token` out of the fee vault into the treasury
+    pub fn sweep_expired_fees(env: Env, admin: Address, fee_token: Address) -> u64 {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let treasury = Self::get_treasury(env.clone())
+            .unwrap_or_else(|| panic!("Treasury not set"));
+        
+        let amount = Self::get_sweepable_fees(env.clone(), fee_token.clone());
+        env.storage().instance().remove(&MessageDataKey::SweepableFeesIn(fee_token.clone()));
+        fees::release_fee(&env, &fee_token, &treasury, amount as i128);
+        
+        amount
+    }
+
+    /// Get the last settled sequence number for a sender to a target chain
+    pub fn get_settled_sequence(env: Env, sender: Address, target_chain: u32) -> u64 {
+        env.storage().instance()
+            .get(&MessageDataKey::SettledSequence(sender, target_chain))
+            .unwrap_or(0)
+    }
+
+    /// Get the fee a message of `message_type` consuming `gas` would be charged on `chain_id`
+    pub fn get_fee_estimate(env: Env, gas: u64, chain_id: u32, message_type: MessageType) -> u64 {
+        Self::compute_fee(env, gas, chain_id, message_type)
+    }
+
+    /// Set reputation points an idle relayer loses per day
+    pub fn set_reputation_decay_rate(env: Env, admin: Address, decay_rate: u32) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        if decay_rate > MAX_REPUTATION_DECAY_RATE {
+            panic!("Decay rate too high");
+        }
+        
+        env.storage().instance().set(&MessageDataKey::ReputationDecayRate, &decay_rate);
+    }
+
+    /// Get reputation points an idle relayer loses per day
+    pub fn get_reputation_decay_rate(env: Env) -> u32 {
+        env.storage().instance()
+            .get(&MessageDataKey::ReputationDecayRate)
+            .unwrap_or(DEFAULT_REPUTATION_DECAY_RATE)
+    }
+
+    /// Apply idle decay to a relayer's reputation (maintenance function)
+    pub fn decay_relayer_reputation(env: Env, relayer_id: u64) -> u32 {
+        let mut relayer = Self::get_relayer(env.clone(), relayer_id);
+        
+        Self::apply_reputation_decay(env.clone(), &mut relayer);
+        env.storage().instance().set(&MessageDataKey::MessageRelayer(relayer_id), &relayer);
+        
+        relayer.reputation
+    }
+
+    /// Create message queue for chain
+    pub fn create_queue(
+        env: Env,
+        admin: Address,
+        chain_id: u32,
+        priority: QueuePriority,
+        max_size: u32,
+    ) -> u64 {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let count: u64 = env.storage().instance().get(&MessageDataKey::QueueCount).unwrap_or(0);
         let queue_id = count + 1;
         
         let queue = MessageQueue {
@@ -277,11 +1510,25 @@ impl MessagePassing {
 
     /// Get message details
     pub fn get_message(env: Env, message_id: u64) -> CrossChainMessage {
-        env.storage().instance()
-            .get(&MessageDataKey::CrossChainMessage(message_id))
+        Self::load_message(env.clone(), message_id)
             .unwrap_or_else(|| panic!("Message not found"))
     }
 
+    /// Look up several messages' statuses at once; unknown ids come back as `None`
+    pub fn get_message_statuses(env: Env, message_ids: Vec<u64>) -> Vec<(u64, Option<MessageStatus>)> {
+        if message_ids.len() > MAX_STATUS_QUERY {
+            panic!("Too many message ids");
+        }
+        
+        let mut statuses = Vec::new(&env);
+        for message_id in message_ids.iter() {
+            let message: Option<CrossChainMessage> = Self::load_message(env.clone(), message_id);
+            statuses.push_back((message_id, message.map(|message| message.status)));
+        }
+        
+        statuses
+    }
+
     /// Get relayer details
     pub fn get_relayer(env: Env, relayer_id: u64) -> MessageRelayer {
         env.storage().instance()
@@ -296,6 +1543,53 @@ impl MessagePassing {
             .unwrap_or_else(|| panic!("Queue not found"))
     }
 
+    /// Get up to `limit` permanently failed messages, oldest first
+    pub fn get_dead_letters(env: Env, limit: u32) -> Vec<CrossChainMessage> {
+        let mut messages = Vec::new(&env);
+        for message_id in Self::get_dead_letter_ids(env.clone()).iter().take(limit as usize) {
+            messages.push_back(Self::get_message(env.clone(), message_id));
+        }
+        messages
+    }
+
+    /// Move a dead letter back to the pending queue for another delivery attempt
+    /// The message is given the current gas cap for its type so an out-of-gas failure can succeed
+    pub fn retry_dead_letter(env: Env, admin: Address, message_id: u64) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        let mut dead_letters = Self::get_dead_letter_ids(env.clone());
+        let index = dead_letters.first_index_of(message_id)
+            .unwrap_or_else(|| panic!("Message not in dead-letter queue"));
+        dead_letters.remove(index);
+        env.storage().instance().set(&MessageDataKey::DeadLetters, &dead_letters);
+        
+        let mut message = Self::get_message(env.clone(), message_id);
+        message.status = MessageStatus::Pending;
+        message.processed_at = None;
+        message.gas_used = 0;
+        message.gas_limit = Self::get_gas_cap(env.clone(), message.message_type.clone());
+        env.storage().instance().set(&MessageDataKey::CrossChainMessage(message_id), &message);
+        env.storage().instance().set(&MessageDataKey::Requeued(message_id), &true);
+        let requeue_count: u32 = env.storage().instance()
+            .get(&MessageDataKey::RequeueCount(message_id))
+            .unwrap_or(0);
+        env.storage().instance().set(&MessageDataKey::RequeueCount(message_id), &(requeue_count + 1));
+        
+        let mut pending = Self::get_pending_messages(env.clone());
+        capacity::ensure_below_max_pending(pending.len() as u64, Self::get_max_pending(env.clone()));
+        pending.push_back(message_id);
+        capacity::ensure_fits(&env, &pending, Self::get_state_capacity(env.clone()));
+        env.storage().instance().set(&MessageDataKey::PendingMessages, &pending);
+    }
+
     /// Get pending messages
     pub fn get_pending_messages(env: Env) -> Vec<u64> {
         env.storage().instance()
@@ -303,13 +1597,29 @@ impl MessagePassing {
             .unwrap_or(Vec::new(&env))
     }
 
+    /// Get the oldest pending message as `(message_id, created_at)`, a cheap gauge of backlog age
+    /// Requeued messages keep their original creation time, so the whole pending list is scanned
+    pub fn get_oldest_pending(env: Env) -> Option<(u64, u64)> {
+        let mut oldest: Option<(u64, u64)> = None;
+        
+        for message_id in Self::get_pending_messages(env.clone()).iter() {
+            if let Some(message) = Self::load_message(env.clone(), message_id) {
+                if oldest.map_or(true, |(_, created_at)| message.created_at < created_at) {
+                    oldest = Some((message_id, message.created_at));
+                }
+            }
+        }
+        
+        oldest
+    }
+
     /// Get messages for recipient
     pub fn get_messages_for_recipient(env: Env, recipient: Address) -> Vec<CrossChainMessage> {
         let count: u64 = env.storage().instance().get(&MessageDataKey::MessageCount).unwrap_or(0);
         let mut messages = Vec::new(&env);
         
         for i in 1..=count {
-            if let Some(message) = env.storage().instance().get::<MessageDataKey, CrossChainMessage>(&MessageDataKey::CrossChainMessage(i)) {
+            if let Some(message) = Self::load_message(env.clone(), i) {
                 if message.recipient == recipient {
                     messages.push_back(message);
                 }
@@ -325,7 +1635,7 @@ impl MessagePassing {
         let mut messages = Vec::new(&env);
         
         for i in 1..=count {
-            if let Some(message) = env.storage().instance().get::<MessageDataKey, CrossChainMessage>(&MessageDataKey::CrossChainMessage(i)) {
+            if let Some(message) = Self::load_message(env.clone(), i) {
                 if message.message_type == message_type {
                     messages.push_back(message);
                 }
@@ -338,7 +1648,7 @@ impl MessagePassing {
     /// Batch send messages
     pub fn batch_send_messages(
         env: Env,
-        messages: Vec<(u32, Address, MessageType, Bytes, Bytes)>, // (target_chain, recipient, message_type, payload, signature)
+        messages: Vec<(u32, Address, MessageType, Bytes, u64, Bytes)>, // (target_chain, recipient, message_type, payload, gas_limit, signature)
         sender: Address,
     ) -> Vec<u64> {
         sender.require_auth();
@@ -346,7 +1656,7 @@ impl MessagePassing {
         let mut message_ids = Vec::new(&env);
         
         for i in 0..messages.len() {
-            let (target_chain, recipient, message_type, payload, signature) = messages.get(i).unwrap();
+            let (target_chain, recipient, message_type, payload, gas_limit, signature) = messages.get(i).unwrap();
             
             let message_id = Self::send_message(
                 env.clone(),
@@ -354,8 +1664,10 @@ impl MessagePassing {
                 recipient.clone(),
                 message_type.clone(),
                 payload.clone(),
+                gas_limit,
                 sender.clone(),
                 signature.clone(),
+                None,
             );
             
             message_ids.push_back(message_id);
@@ -364,23 +1676,21 @@ impl MessagePassing {
         message_ids
     }
 
-    /// Expire old pending messages
-    pub fn expire_pending_messages(env: Env, timeout_seconds: u64) -> Vec<u64> {
+    /// Expire pending messages older than the stored message TTL
+    /// Messages a relayer has already picked up get the in-transit grace on top of the TTL
+    pub fn expire_pending_messages(env: Env) -> Vec<u64> {
+        let timeout_seconds = Self::get_message_ttl(env.clone());
         let pending = Self::get_pending_messages(env.clone());
         let current_time = env.ledger().timestamp();
         let mut expired = Vec::new(&env);
         
         for i in 0..pending.len() {
             let message_id = pending.get(i).unwrap();
-            let message: CrossChainMessage = env.storage().instance()
-                .get(&MessageDataKey::CrossChainMessage(*message_id))
+            let message: CrossChainMessage = Self::load_message(env.clone(), *message_id)
                 .unwrap();
             
             if current_time > message.created_at + timeout_seconds {
-                let mut updated_message = message;
-                updated_message.status = MessageStatus::Expired;
-                
-                env.storage().instance().set(&MessageDataKey::CrossChainMessage(*message_id), &updated_message);
+                Self::expire_message(env.clone(), message);
                 expired.push_back(*message_id);
             }
         }
@@ -390,9 +1700,77 @@ impl MessagePassing {
             Self::remove_from_pending(env.clone(), *expired.get(i).unwrap());
         }
         
+        let in_transit_timeout = timeout_seconds.saturating_add(Self::get_in_transit_grace(env.clone()));
+        let mut still_in_transit = Vec::new(&env);
+        for message_id in Self::get_in_transit_messages(env.clone()).iter() {
+            let message = Self::get_message(env.clone(), message_id);
+            if current_time > message.created_at.saturating_add(in_transit_timeout) {
+                Self::expire_message(env.clone(), message);
+                expired.push_back(message_id);
+            } else {
+                still_in_transit.push_back(message_id);
+            }
+        }
+        env.storage().instance().set(&MessageDataKey::InTransitMessages, &still_in_transit);
+        
         expired
     }
 
+    /// Set how many seconds an undelivered message lives before it can be expired
+    pub fn set_message_ttl(env: Env, admin: Address, seconds: u64) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        if seconds == 0 {
+            panic!("Message TTL must be positive");
+        }
+        
+        env.storage().instance().set(&MessageDataKey::MessageTtl, &seconds);
+    }
+
+    /// Get how many seconds an undelivered message lives before it can be expired
+    pub fn get_message_ttl(env: Env) -> u64 {
+        env.storage().instance()
+            .get(&MessageDataKey::MessageTtl)
+            .unwrap_or(DEFAULT_MESSAGE_TTL_SECONDS)
+    }
+
+    /// Set the extra seconds an in-transit message has before it expires
+    pub fn set_in_transit_grace(env: Env, admin: Address, seconds: u64) {
+        let stored_admin: Address = env.storage().instance()
+            .get(&MessageDataKey::Admin)
+            .unwrap_or_else(|| panic!("Admin not found"));
+        
+        if admin != stored_admin {
+            panic!("Not authorized");
+        }
+        
+        admin.require_auth();
+        
+        env.storage().instance().set(&MessageDataKey::InTransitGrace, &seconds);
+    }
+
+    /// Get the extra seconds an in-transit message has before it expires
+    pub fn get_in_transit_grace(env: Env) -> u64 {
+        env.storage().instance()
+            .get(&MessageDataKey::InTransitGrace)
+            .unwrap_or(DEFAULT_IN_TRANSIT_GRACE_SECONDS)
+    }
+
+    /// Get messages picked up by a relayer and not yet delivered
+    pub fn get_in_transit_messages(env: Env) -> Vec<u64> {
+        env.storage().instance()
+            .get(&MessageDataKey::InTransitMessages)
+            .unwrap_or(Vec::new(&env))
+    }
+
     /// Helper functions
     fn get_current_chain_id(env: Env) -> u32 {
         // Simplified - in practice, this would get the actual chain ID
@@ -404,6 +1782,277 @@ impl MessagePassing {
         21000u64 + (payload_size as u64 * 100)
     }
 
+    fn estimate_execution_gas(env: Env, message_type: MessageType, payload_size: u32) -> u64 {
+        let gas = Self::estimate_message_gas(env, payload_size);
+        match message_type {
+            MessageType::Proof => gas + PROOF_VERIFICATION_GAS,
+            _ => gas,
+        }
+    }
+
+    /// Fail a message whose execution would exceed its gas limit, refunding the gas it never used
+    fn fail_out_of_gas(env: Env, message: &mut CrossChainMessage) {
+        let charged = message.fee;
+        message.fee = Self::compute_fee(env.clone(), message.gas_used, message.target_chain, message.message_type.clone()).min(charged);
+        message.status = MessageStatus::OutOfGas;
+        message.processed_at = Some(env.ledger().timestamp());
+        
+        env.storage().instance().set(&MessageDataKey::CrossChainMessage(message.message_id), message);
+        Self::settle_sequence(env.clone(), message);
+        Self::record_message_failed(env.clone());
+        Self::record_route_outcome(env.clone(), message, false);
+        Self::add_dead_letter(env.clone(), message.message_id);
+        
+        // Refund only from what was actually collected, in the token it was paid in
+        let paid_key = MessageDataKey::FeePaid(message.message_id);
+        let mut refund = 0;
+        if let Some((fee_token, paid)) = env.storage().instance().get::<MessageDataKey, (Address, u64)>(&paid_key) {
+            refund = paid.saturating_sub(message.fee);
+            fees::release_fee(&env, &fee_token, &message.sender, refund as i128);
+            env.storage().instance().set(&paid_key, &(fee_token, paid - refund));
+        }
+        
+        env.events().publish(
+            (Symbol::new(&env, "out_of_gas"), message.message_id),
+            (message.gas_limit, refund)
+        );
+    }
+
+    fn record_route_sent(env: Env, message: &CrossChainMessage) {
+        let key = MessageDataKey::RouteStats(message.source_chain, message.target_chain);
+        let (sent, delivered, failed, total_latency): (u64, u64, u64, u64) = env.storage().instance()
+            .get(&key)
+            .unwrap_or((0, 0, 0, 0));
+        env.storage().instance().set(&key, &(sent + 1, delivered, failed, total_latency));
+    }
+
+    fn record_route_outcome(env: Env, message: &CrossChainMessage, delivered_ok: bool) {
+        let key = MessageDataKey::RouteStats(message.source_chain, message.target_chain);
+        let (sent, delivered, failed, total_latency): (u64, u64, u64, u64) = env.storage().instance()
+            .get(&key)
+            .unwrap_or((0, 0, 0, 0));
+        
+        let stats = if delivered_ok {
+            let latency = env.ledger().timestamp().saturating_sub(message.created_at);
+            (sent, delivered + 1, failed, total_latency + latency)
+        } else {
+            (sent, delivered, failed + 1, total_latency)
+        };
+        env.storage().instance().set(&key, &stats);
+    }
+
+    /// Move a delivery that turned out to have failed from the route's delivered count to its failed count
+    fn record_route_overturned(env: Env, message: &CrossChainMessage) {
+        let key = MessageDataKey::RouteStats(message.source_chain, message.target_chain);
+        let (sent, delivered, failed, total_latency): (u64, u64, u64, u64) = env.storage().instance()
+            .get(&key)
+            .unwrap_or((0, 0, 0, 0));
+        
+        let latency = message.processed_at.unwrap_or(message.created_at).saturating_sub(message.created_at);
+        env.storage().instance().set(
+            &key,
+            &(sent, delivered.saturating_sub(1), failed + 1, total_latency.saturating_sub(latency)),
+        );
+    }
+
+    fn store_delivery_result(env: Env, message: &CrossChainMessage, status_code: u32, return_data: Bytes) {
+        let result = DeliveryResult {
+            message_id: message.message_id,
+            message_type: message.message_type.clone(),
+            status_code,
+            return_data,
+            encryption_scheme: message.encryption_scheme.clone(),
+        };
+        env.storage().instance().set(&MessageDataKey::DeliveryResult(message.message_id), &result);
+    }
+
+    /// Fold a version 3 scheme and the ephemeral key stored beside it into one `EncryptionScheme`
+    fn upgrade_v3_scheme(encryption_scheme: EncryptionSchemeV3, ephemeral_pubkey: Bytes) -> EncryptionScheme {
+        match encryption_scheme {
+            EncryptionSchemeV3::Plaintext => EncryptionScheme::Plaintext,
+            EncryptionSchemeV3::X25519ChaCha20Poly1305 => EncryptionScheme::X25519ChaCha20Poly1305(
+                BytesN::try_from(ephemeral_pubkey).unwrap_or_else(|_| panic!("Corrupt ephemeral key")),
+            ),
+            EncryptionSchemeV3::EciesSecp256k1 => EncryptionScheme::EciesSecp256k1(
+                BytesN::try_from(ephemeral_pubkey).unwrap_or_else(|_| panic!("Corrupt ephemeral key")),
+            ),
+        }
+    }
+
+    fn execute_proof_message(env: Env, message: &mut CrossChainMessage) -> bool {
+        let verifier_contract = Self::get_proof_verifier(env.clone())
+            .unwrap_or_else(|| panic!("Proof verifier not set"));
+        
+        message.gas_used += PROOF_VERIFICATION_GAS;
+        
+        // This contract acts as the verifier, so it must be trusted by the verifier contract
+        let verification_hash: Bytes = env.crypto().sha256(&message.payload).into();
+        ChainVerifierClient::new(&env, &verifier_contract).verify_proof_on_chain(
+            &message.message_id,
+            &message.source_chain,
+            &message.payload,
+            &env.current_contract_address(),
+            &verification_hash,
+        )
+    }
+
+    fn record_message_sent(env: Env) {
+        let mut stats = Self::current_window_stats(env.clone());
+        stats.total_messages += 1;
+        stats.window_messages += 1;
+        env.storage().instance().set(&MessageDataKey::MessageStats, &stats);
+    }
+
+    fn record_message_failed(env: Env) {
+        let mut stats = Self::current_window_stats(env.clone());
+        stats.failed_messages += 1;
+        stats.window_failed += 1;
+        env.storage().instance().set(&MessageDataKey::MessageStats, &stats);
+        
+        if Self::is_paused(env.clone()) || stats.window_messages < FAILURE_MIN_SAMPLE {
+            return;
+        }
+        
+        let threshold = Self::get_failure_threshold(env.clone()) as u64;
+        if stats.window_failed * fees::FEE_DENOMINATOR as u64 > threshold * stats.window_messages {
+            env.storage().instance().set(&MessageDataKey::Paused, &true);
+            env.events().publish(
+                (Symbol::new(&env, "circuit_breaker_tripped"),),
+                (stats.window_failed, stats.window_messages)
+            );
+        }
+    }
+
+    fn current_window_stats(env: Env) -> MessageStats {
+        let now = env.ledger().timestamp();
+        let mut stats = Self::get_message_stats(env);
+        
+        if now >= stats.window_start + FAILURE_WINDOW_SECONDS {
+            stats.window_start = now;
+            stats.window_messages = 0;
+            stats.window_failed = 0;
+        }
+        
+        stats
+    }
+
+    /// Load a message, upgrading entries stored in an older layout
+    fn load_message(env: Env, message_id: u64) -> Option<CrossChainMessage> {
+        let raw: Val = env.storage().instance().get(&MessageDataKey::CrossChainMessage(message_id))?;
+        
+        if let Ok(message) = CrossChainMessage::try_from_val(&env, &raw) {
+            return Some(message);
+        }
+        
+        if let Ok(v3) = CrossChainMessageV3::try_from_val(&env, &raw) {
+            return Some(CrossChainMessage {
+                message_id: v3.message_id,
+                source_chain: v3.source_chain,
+                target_chain: v3.target_chain,
+                sender: v3.sender,
+                recipient: v3.recipient,
+                message_type: v3.message_type,
+                payload: v3.payload,
+                nonce: v3.nonce,
+                sequence: v3.sequence,
+                signature: v3.signature,
+                status: v3.status,
+                created_at: v3.created_at,
+                processed_at: v3.processed_at,
+                gas_used: v3.gas_used,
+                gas_limit: v3.gas_limit,
+                fee: v3.fee,
+                encryption_scheme: Self::upgrade_v3_scheme(v3.encryption_scheme, v3.ephemeral_pubkey),
+                hop_count: v3.hop_count,
+            });
+        }
+        
+        let v2 = CrossChainMessageV2::try_from_val(&env, &raw).unwrap_or_else(|_| panic!("Unknown message layout"));
+        // v2 messages were never sequenced, so they take sequence 0 and bypass ordering; their limit is today's cap for the type
+        Some(CrossChainMessage {
+            message_id: v2.message_id,
+            source_chain: v2.source_chain,
+            target_chain: v2.target_chain,
+            sender: v2.sender,
+            recipient: v2.recipient,
+            message_type: v2.message_type.clone(),
+            payload: v2.payload,
+            nonce: v2.nonce,
+            sequence: 0,
+            signature: v2.signature,
+            status: v2.status,
+            created_at: v2.created_at,
+            processed_at: v2.processed_at,
+            gas_used: v2.gas_used,
+            gas_limit: Self::get_gas_cap(env.clone(), v2.message_type),
+            fee: v2.fee,
+            encryption_scheme: EncryptionScheme::Plaintext,
+            hop_count: 0,
+        })
+    }
+
+    fn settle_sequence(env: Env, message: &CrossChainMessage) {
+        // A requeued dead letter settling late must not move the sequence back
+        if message.sequence <= Self::get_settled_sequence(env.clone(), message.sender.clone(), message.target_chain) {
+            return;
+        }
+        
+        // Successors that expired while this message was still outstanding settle along with it
+        let mut sequence = message.sequence;
+        loop {
+            let skipped = MessageDataKey::SkippedSequence(message.sender.clone(), message.target_chain, sequence + 1);
+            if !env.storage().persistent().has(&skipped) {
+                break;
+            }
+            env.storage().persistent().remove(&skipped);
+            sequence += 1;
+        }
+        
+        env.storage().instance().set(
+            &MessageDataKey::SettledSequence(message.sender.clone(), message.target_chain),
+            &sequence,
+        );
+    }
+
+    /// Bump a persistent entry's TTL so it outlives the messages it tracks
+    fn extend_persistent_ttl(env: Env, key: &MessageDataKey) {
+        env.storage().persistent().extend_ttl(key, PERSISTENT_TTL_LEDGERS, PERSISTENT_TTL_LEDGERS);
+    }
+
+    fn add_dead_letter(env: Env, message_id: u64) {
+        let mut dead_letters = Self::get_dead_letter_ids(env.clone());
+        dead_letters.push_back(message_id);
+        capacity::ensure_fits(&env, &dead_letters, Self::get_state_capacity(env.clone()));
+        env.storage().instance().set(&MessageDataKey::DeadLetters, &dead_letters);
+    }
+
+    fn get_dead_letter_ids(env: Env) -> Vec<u64> {
+        env.storage().instance()
+            .get(&MessageDataKey::DeadLetters)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    fn add_sweepable_fees(env: Env, fee_token: &Address, amount: u64) {
+        let sweepable = Self::get_sweepable_fees(env.clone(), fee_token.clone());
+        env.storage().instance().set(&MessageDataKey::SweepableFeesIn(fee_token.clone()), &(sweepable + amount));
+    }
+
+    /// Returns the fee token and what the vault received, or `None` when nothing was taken
+    fn collect_fee(env: Env, payer: Address, fee: u64) -> Option<(Address, u64)> {
+        let fee_token = Self::get_fee_token(env.clone())?;
+        let received = fees::collect_fee(&env, &fee_token, &payer, fee as i128) as u64;
+        Some((fee_token, received))
+    }
+
+    fn compute_fee(env: Env, gas: u64, chain_id: u32, message_type: MessageType) -> u64 {
+        let gas_price = Self::get_chain_gas_price(env.clone(), chain_id);
+        let multiplier = Self::get_fee_multiplier(env, message_type);
+        fees::gas_fee(gas, gas_price)
+            .checked_mul(multiplier as u64)
+            .unwrap_or_else(|| panic!("Fee overflow"))
+            / fees::FEE_DENOMINATOR as u64
+    }
+
     fn verify_delivery_proof(env: Env, proof: Bytes) -> bool {
         // Simplified proof verification
         proof.len() > 0
@@ -414,7 +2063,7 @@ impl MessagePassing {
         
         for i in 1..=count {
             if let Some(relayer_info) = env.storage().instance().get::<MessageDataKey, MessageRelayer>(&MessageDataKey::MessageRelayer(i)) {
-                if relayer_info.address == relayer && relayer_info.active {
+                if relayer_info.address == relayer && relayer_info.active && Self::meets_min_stake(env.clone(), i) {
                     return true;
                 }
             }
@@ -429,9 +2078,12 @@ impl MessagePassing {
         for i in 1..=count {
             if let Some(mut relayer_info) = env.storage().instance().get::<MessageDataKey, MessageRelayer>(&MessageDataKey::MessageRelayer(i)) {
                 if relayer_info.address == relayer {
+                    Self::apply_reputation_decay(env.clone(), &mut relayer_info);
+                    relayer_info.last_seen = env.ledger().timestamp();
                     relayer_info.total_messages += 1;
                     if success {
                         // Maintain success rate (simplified)
+                        relayer_info.reputation = (relayer_info.reputation + 1).min(REPUTATION_MAX);
                     }
                     env.storage().instance().set(&MessageDataKey::MessageRelayer(i), &relayer_info);
                     break;
@@ -440,6 +2092,98 @@ impl MessagePassing {
         }
     }
 
+    fn slash_relayer(env: Env, relayer: Address) {
+        let count: u64 = env.storage().instance().get(&MessageDataKey::RelayerCount).unwrap_or(0);
+        
+        for i in 1..=count {
+            if let Some(mut relayer_info) = env.storage().instance().get::<MessageDataKey, MessageRelayer>(&MessageDataKey::MessageRelayer(i)) {
+                if relayer_info.address == relayer {
+                    relayer_info.reputation = relayer_info.reputation.saturating_sub(SLASH_REPUTATION_PENALTY);
+                    env.storage().instance().set(&MessageDataKey::MessageRelayer(i), &relayer_info);
+                    
+                    // Forfeited stake stays in the vault and is swept to the treasury with expired fees in the staked token
+                    let stake = Self::get_relayer_stake(env.clone(), i);
+                    let slashed = stake
+                        .checked_mul(SLASH_STAKE_BPS as u64)
+                        .unwrap_or_else(|| panic!("Stake overflow"))
+                        / fees::FEE_DENOMINATOR as u64;
+                    if slashed > 0 {
+                        let fee_token = Self::get_fee_token(env.clone())
+                            .unwrap_or_else(|| panic!("Fee token not set"));
+                        Self::set_relayer_stake(env.clone(), i, stake - slashed);
+                        Self::add_sweepable_fees(env.clone(), &fee_token, slashed);
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Set a relayer's stake, keeping the running total in step
+    fn set_relayer_stake(env: Env, relayer_id: u64, stake: u64) {
+        let previous = Self::get_relayer_stake(env.clone(), relayer_id);
+        let total: u64 = env.storage().instance().get(&MessageDataKey::TotalStaked).unwrap_or(0);
+        let total = if stake >= previous {
+            total.checked_add(stake - previous)
+        } else {
+            total.checked_sub(previous - stake)
+        }
+        .unwrap_or_else(|| panic!("Stake total out of range"));
+        
+        env.storage().instance().set(&MessageDataKey::RelayerStake(relayer_id), &stake);
+        env.storage().instance().set(&MessageDataKey::TotalStaked, &total);
+    }
+
+    fn meets_min_stake(env: Env, relayer_id: u64) -> bool {
+        Self::get_relayer_stake(env.clone(), relayer_id) >= Self::get_min_relayer_stake(env)
+    }
+
+    fn apply_reputation_decay(env: Env, relayer: &mut MessageRelayer) {
+        let now = env.ledger().timestamp();
+        let idle_periods = now.saturating_sub(relayer.last_seen) / REPUTATION_DECAY_PERIOD;
+        
+        // Decay only down to the baseline, never below it
+        if relayer.reputation > REPUTATION_BASELINE {
+            let decay = idle_periods.saturating_mul(Self::get_reputation_decay_rate(env.clone()) as u64);
+            let headroom = (relayer.reputation - REPUTATION_BASELINE) as u64;
+            relayer.reputation -= decay.min(headroom) as u32;
+        }
+        
+        // Only consume whole periods so partial idle time still counts later
+        relayer.last_seen += idle_periods * REPUTATION_DECAY_PERIOD;
+    }
+
+    /// Expiry is not a delivery failure, so it never counts towards the circuit breaker
+    fn expire_message(env: Env, mut message: CrossChainMessage) {
+        message.status = MessageStatus::Expired;
+        
+        // Fees of undelivered messages are not refunded; whatever was collected becomes sweepable in the token it was paid in
+        let paid: Option<(Address, u64)> = env.storage().instance().get(&MessageDataKey::FeePaid(message.message_id));
+        if let Some((fee_token, amount)) = paid {
+            Self::add_sweepable_fees(env.clone(), &fee_token, amount);
+        }
+        
+        // Let successors of an expired message through; one expiring ahead of a predecessor is skipped once that settles
+        let settled = Self::get_settled_sequence(env.clone(), message.sender.clone(), message.target_chain);
+        if message.sequence == settled + 1 {
+            Self::settle_sequence(env.clone(), &message);
+        } else if message.sequence > settled + 1 {
+            let skipped = MessageDataKey::SkippedSequence(message.sender.clone(), message.target_chain, message.sequence);
+            env.storage().persistent().set(&skipped, &true);
+            Self::extend_persistent_ttl(env.clone(), &skipped);
+        }
+        
+        env.storage().instance().set(&MessageDataKey::CrossChainMessage(message.message_id), &message);
+    }
+
+    fn remove_from_in_transit(env: Env, message_id: u64) {
+        let mut in_transit = Self::get_in_transit_messages(env.clone());
+        if let Some(index) = in_transit.first_index_of(message_id) {
+            in_transit.remove(index);
+            env.storage().instance().set(&MessageDataKey::InTransitMessages, &in_transit);
+        }
+    }
+
     fn remove_from_pending(env: Env, message_id: u64) {
         let mut pending: Vec<u64> = env.storage().instance()
             .get(&MessageDataKey::PendingMessages)
@@ -0,0 +1,1428 @@
+#![cfg(test)]
+use ed25519_dalek::{Signer, SigningKey};
+use soroban_sdk::{testutils::{Address as _, Ledger as _}, token, xdr::FromXdr, Address, Bytes, BytesN, Env, String, Vec};
+use crate::capacity;
+use crate::chainVerifier::{ChainVerifier, ChainVerifierClient, VerificationRule};
+use crate::messagePassing::{ChallengeStatus, CrossChainMessage, CrossChainMessageV2, CrossChainMessageV3, DeliveryResult, DeliveryResultV3, EncryptionScheme, EncryptionSchemeV3, MessageDataKey, MessagePassing, MessagePassingClient, MessageStatus, MessageType, RouteStats, DELIVERY_CHALLENGED, DELIVERY_OUT_OF_GAS, DELIVERY_SUCCEEDED};
+
+/// Gas limit test messages carry, the default per-type cap
+const GAS_LIMIT: u64 = 500_000;
+
+fn setup(env: &Env) -> (MessagePassingClient, Address) {
+    let contract_id = env.register_contract(None, MessagePassing);
+    let client = MessagePassingClient::new(env, &contract_id);
+    
+    let admin = Address::generate(env);
+    client.initialize(&admin);
+    
+    (client, admin)
+}
+
+// Shrink a stored message's gas limit, as for one sent before the send-time gas check
+fn lower_gas_limit(env: &Env, client: &MessagePassingClient, message_id: u64, gas_limit: u64) {
+    env.as_contract(&client.address, || {
+        let key = MessageDataKey::CrossChainMessage(message_id);
+        let mut message: CrossChainMessage = env.storage().instance().get(&key).unwrap();
+        message.gas_limit = gas_limit;
+        env.storage().instance().set(&key, &message);
+    });
+}
+
+#[test]
+fn test_fee_estimate_matches_charged_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let relayer = Address::generate(&env);
+    let mut chains = Vec::new(&env);
+    chains.push_back(137u32);
+    client.register_relayer(&admin, &relayer, &chains, &1);
+    client.set_chain_gas_price(&admin, &137, &25);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let message_id = client.send_message(
+        &137,
+        &recipient,
+        &MessageType::Generic,
+        &payload,
+        &GAS_LIMIT,
+        &sender,
+        &Bytes::from_slice(&env, b"signature"),
+        &None,
+    );
+    client.process_message(&message_id, &relayer);
+    
+    let message = client.get_message(&message_id);
+    assert_eq!(message.fee, message.gas_used * 25);
+    assert_eq!(client.get_fee_estimate(&message.gas_used, &137, &MessageType::Generic), message.fee);
+}
+
+#[test]
+fn test_idle_relayer_reputation_decays() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let relayer = Address::generate(&env);
+    let mut chains = Vec::new(&env);
+    chains.push_back(137u32);
+    let relayer_id = client.register_relayer(&admin, &relayer, &chains, &1);
+    client.set_reputation_decay_rate(&admin, &5);
+    assert_eq!(client.get_relayer(&relayer_id).reputation, 100);
+    
+    // Three idle days
+    env.ledger().with_mut(|li| li.timestamp += 3 * 86_400);
+    assert_eq!(client.decay_relayer_reputation(&relayer_id), 85);
+    
+    // Long idleness bottoms out at the baseline
+    env.ledger().with_mut(|li| li.timestamp += 365 * 86_400);
+    assert_eq!(client.decay_relayer_reputation(&relayer_id), 50);
+}
+
+#[test]
+fn test_proof_message_execution_triggers_verification() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let verifier_id = env.register_contract(None, ChainVerifier);
+    let verifier = ChainVerifierClient::new(&env, &verifier_id);
+    verifier.initialize(&admin);
+    verifier.add_trusted_verifier(&admin, &client.address);
+    verifier.add_verification_rule(&admin, &VerificationRule {
+        rule_id: 0,
+        chain_id: 1,
+        min_confirmations: 1,
+        gas_limit: 1_000_000,
+        verification_method: String::from_str(&env, "merkle"),
+        active: true,
+    });
+    client.set_proof_verifier(&admin, &verifier_id);
+    
+    let relayer = Address::generate(&env);
+    let mut chains = Vec::new(&env);
+    chains.push_back(137u32);
+    client.register_relayer(&admin, &relayer, &chains, &1);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"chain proof");
+    let message_id = client.send_message(
+        &137,
+        &recipient,
+        &MessageType::Proof,
+        &payload,
+        &GAS_LIMIT,
+        &sender,
+        &Bytes::from_slice(&env, b"signature"),
+        &None,
+    );
+    client.process_message(&message_id, &relayer);
+    let gas_before = client.get_message(&message_id).gas_used;
+    
+    assert!(client.deliver_message(&message_id, &Bytes::from_slice(&env, b"delivered"), &relayer));
+    
+    let message = client.get_message(&message_id);
+    assert_eq!(message.status, MessageStatus::Delivered);
+    assert!(message.gas_used > gas_before);
+    
+    let result = verifier.get_verification_result(&message_id, &1).unwrap();
+    assert!(result.verified);
+    assert_eq!(result.verifier, client.address);
+}
+
+#[test]
+fn test_failure_spike_trips_circuit_breaker() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    client.set_failure_threshold(&admin, &5_000);
+    
+    let relayer = Address::generate(&env);
+    client.register_relayer(&admin, &relayer, &Vec::from_array(&env, [137u32]), &1);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    let delivery_proof = Bytes::from_slice(&env, b"delivered");
+    
+    // Every message runs out of gas on delivery
+    for _ in 0..10 {
+        assert!(!client.is_paused());
+        let message_id = client.send_message(&137, &recipient, &MessageType::Proof, &payload, &GAS_LIMIT, &sender, &signature, &None);
+        lower_gas_limit(&env, &client, message_id, 30_000);
+        client.process_message(&message_id, &relayer);
+        client.deliver_message(&message_id, &delivery_proof, &relayer);
+    }
+    
+    assert!(client.is_paused());
+    let stats = client.get_message_stats();
+    assert_eq!(stats.total_messages, 10);
+    assert_eq!(stats.failed_messages, 10);
+    assert!(client
+        .try_send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None)
+        .is_err());
+    
+    client.resume_sending(&admin);
+    assert!(!client.is_paused());
+    client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+}
+
+#[test]
+fn test_expiry_follows_stored_ttl_and_spares_circuit_breaker() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    client.set_failure_threshold(&admin, &5_000);
+    
+    let outsider = Address::generate(&env);
+    assert!(client.try_set_message_ttl(&outsider, &1).is_err());
+    assert!(client.try_set_message_ttl(&admin, &0).is_err());
+    client.set_message_ttl(&admin, &1_000);
+    assert_eq!(client.get_message_ttl(), 1_000);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    for _ in 0..10 {
+        client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    }
+    
+    // Nothing expires before the TTL, whoever asks
+    env.ledger().with_mut(|li| li.timestamp += 500);
+    assert!(client.expire_pending_messages().is_empty());
+    
+    env.ledger().with_mut(|li| li.timestamp += 501);
+    assert_eq!(client.expire_pending_messages().len(), 10);
+    
+    // Expired messages are not delivery failures
+    assert!(!client.is_paused());
+    assert_eq!(client.get_message_stats().failed_messages, 0);
+    client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+}
+
+#[test]
+fn test_fees_land_in_contract_fee_vault() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let token_admin = Address::generate(&env);
+    let fee_token = env.register_stellar_asset_contract(token_admin);
+    client.set_fee_token(&admin, &fee_token);
+    client.set_chain_gas_price(&admin, &137, &2);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &fee_token).mint(&sender, &1_000_000);
+    
+    // The contract custodies its own fees
+    let vault = client.get_fee_vault();
+    assert_eq!(vault, client.address);
+    
+    let message_id = client.send_message(
+        &137,
+        &recipient,
+        &MessageType::Generic,
+        &Bytes::from_slice(&env, b"payload"),
+        &GAS_LIMIT,
+        &sender,
+        &Bytes::from_slice(&env, b"signature"),
+        &None,
+    );
+    
+    let fee = client.get_message(&message_id).fee;
+    assert!(fee > 0);
+    assert_eq!(token::Client::new(&env, &fee_token).balance(&vault), fee as i128);
+}
+
+#[test]
+fn test_sweep_expired_message_fees_to_treasury() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let token_admin = Address::generate(&env);
+    let fee_token = env.register_stellar_asset_contract(token_admin);
+    let treasury = Address::generate(&env);
+    client.set_fee_token(&admin, &fee_token);
+    client.set_treasury(&admin, &treasury);
+    client.set_chain_gas_price(&admin, &137, &2);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &fee_token).mint(&sender, &1_000_000);
+    
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    let first = client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    let second = client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    let expected = client.get_message(&first).fee + client.get_message(&second).fee;
+    assert_eq!(client.get_sweepable_fees(&fee_token), 0);
+    
+    // Switching the fee token later does not change what the expired fees were paid in
+    let other_token = env.register_stellar_asset_contract(Address::generate(&env));
+    client.set_fee_token(&admin, &other_token);
+    
+    client.set_message_ttl(&admin, &50);
+    env.ledger().with_mut(|li| li.timestamp += 100);
+    client.expire_pending_messages();
+    assert_eq!(client.get_sweepable_fees(&fee_token), expected);
+    assert_eq!(client.get_sweepable_fees(&other_token), 0);
+    
+    assert_eq!(client.sweep_expired_fees(&admin, &other_token), 0);
+    assert_eq!(client.sweep_expired_fees(&admin, &fee_token), expected);
+    assert_eq!(client.get_sweepable_fees(&fee_token), 0);
+    
+    let balances = token::Client::new(&env, &fee_token);
+    assert_eq!(balances.balance(&treasury), expected as i128);
+    assert_eq!(balances.balance(&client.get_fee_vault()), 0);
+}
+
+#[test]
+fn test_migrate_moves_legacy_sweepable_fees_to_fee_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let fee_token = env.register_stellar_asset_contract(Address::generate(&env));
+    client.set_fee_token(&admin, &fee_token);
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&MessageDataKey::SweepableFees, &300u64);
+        env.storage().instance().set(&MessageDataKey::StateVersion, &4u32);
+    });
+    
+    assert_eq!(client.migrate(&admin), 5);
+    assert_eq!(client.get_sweepable_fees(&fee_token), 300);
+    env.as_contract(&client.address, || {
+        assert!(!env.storage().instance().has(&MessageDataKey::SweepableFees));
+    });
+}
+
+#[test]
+fn test_migrate_reads_v2_message_layout() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let relayer = Address::generate(&env);
+    let mut chains = Vec::new(&env);
+    chains.push_back(137u32);
+    client.register_relayer(&admin, &relayer, &chains, &1);
+    
+    // A pending message written by the v2 contract, before sequencing and gas limits
+    let sender = Address::generate(&env);
+    let legacy = CrossChainMessageV2 {
+        message_id: 1,
+        source_chain: 1,
+        target_chain: 137,
+        sender: sender.clone(),
+        recipient: Address::generate(&env),
+        message_type: MessageType::Generic,
+        payload: Bytes::from_slice(&env, b"legacy payload"),
+        nonce: 1,
+        signature: Bytes::from_slice(&env, b"signature"),
+        status: MessageStatus::Pending,
+        created_at: 0,
+        processed_at: None,
+        gas_used: 0,
+        fee: 0,
+    };
+    env.as_contract(&client.address, || {
+        let mut pending = Vec::new(&env);
+        pending.push_back(1u64);
+        env.storage().instance().set(&MessageDataKey::CrossChainMessage(1), &legacy);
+        env.storage().instance().set(&MessageDataKey::MessageCount, &1u64);
+        env.storage().instance().set(&MessageDataKey::PendingMessages, &pending);
+        env.storage().instance().set(&MessageDataKey::StateVersion, &2u32);
+    });
+    
+    assert_eq!(client.migrate(&admin), 5);
+    assert_eq!(client.get_state_version(), 5);
+    
+    let message = client.get_message(&1);
+    assert_eq!(message.sequence, 0);
+    assert_eq!(message.gas_limit, client.get_gas_cap(&MessageType::Generic));
+    assert_eq!(message.encryption_scheme, EncryptionScheme::Plaintext);
+    assert_eq!(message.hop_count, 0);
+    
+    // Unsequenced legacy messages still deliver and leave the sender's sequence untouched
+    client.process_message(&1, &relayer);
+    assert!(client.deliver_message(&1, &Bytes::from_slice(&env, b"delivered"), &relayer));
+    assert_eq!(client.get_message(&1).status, MessageStatus::Delivered);
+    assert_eq!(client.get_settled_sequence(&sender, &137), 0);
+}
+
+#[test]
+fn test_migrate_folds_v3_ephemeral_key_into_scheme() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    // A delivered message and its result written by the v3 contract, with the key beside the scheme
+    let ephemeral_pubkey = BytesN::from_array(&env, &[9u8; 32]);
+    let legacy = CrossChainMessageV3 {
+        message_id: 1,
+        source_chain: 1,
+        target_chain: 137,
+        sender: Address::generate(&env),
+        recipient: Address::generate(&env),
+        message_type: MessageType::Generic,
+        payload: Bytes::from_slice(&env, b"sealed to recipient"),
+        nonce: 0,
+        sequence: 1,
+        signature: Bytes::from_slice(&env, b"signature"),
+        status: MessageStatus::Delivered,
+        created_at: 0,
+        processed_at: Some(0),
+        gas_used: 22_900,
+        gas_limit: 500_000,
+        fee: 0,
+        encryption_scheme: EncryptionSchemeV3::X25519ChaCha20Poly1305,
+        ephemeral_pubkey: ephemeral_pubkey.clone().into(),
+        hop_count: 0,
+    };
+    let legacy_result = DeliveryResultV3 {
+        message_id: 1,
+        message_type: MessageType::Generic,
+        status_code: DELIVERY_SUCCEEDED,
+        return_data: Bytes::new(&env),
+        encryption_scheme: EncryptionSchemeV3::X25519ChaCha20Poly1305,
+        ephemeral_pubkey: ephemeral_pubkey.clone().into(),
+    };
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&MessageDataKey::CrossChainMessage(1), &legacy);
+        env.storage().instance().set(&MessageDataKey::DeliveryResult(1), &legacy_result);
+        env.storage().instance().set(&MessageDataKey::MessageCount, &1u64);
+        env.storage().instance().set(&MessageDataKey::StateVersion, &3u32);
+    });
+    
+    assert_eq!(client.migrate(&admin), 5);
+    
+    let scheme = EncryptionScheme::X25519ChaCha20Poly1305(ephemeral_pubkey);
+    let message = client.get_message(&1);
+    assert_eq!(message.encryption_scheme, scheme);
+    assert_eq!(message.sequence, 1);
+    let result: DeliveryResult = client.get_delivery_result(&1).unwrap();
+    assert_eq!(result.encryption_scheme, scheme);
+    assert_eq!(result.status_code, DELIVERY_SUCCEEDED);
+}
+
+#[test]
+fn test_messages_execute_in_sequence() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let relayer = Address::generate(&env);
+    let mut chains = Vec::new(&env);
+    chains.push_back(137u32);
+    client.register_relayer(&admin, &relayer, &chains, &1);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    let delivery_proof = Bytes::from_slice(&env, b"delivered");
+    
+    let first = client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    let second = client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    let third = client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    assert_eq!(client.get_message(&first).sequence, 1);
+    assert_eq!(client.get_message(&third).sequence, 3);
+    
+    // Another target chain has its own sequence
+    let other = client.send_message(&56, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    assert_eq!(client.get_message(&other).sequence, 1);
+    
+    for id in [first, second, third] {
+        client.process_message(&id, &relayer);
+    }
+    
+    assert!(client.try_deliver_message(&second, &delivery_proof, &relayer).is_err());
+    
+    assert!(client.deliver_message(&first, &delivery_proof, &relayer));
+    assert!(client.deliver_message(&second, &delivery_proof, &relayer));
+    assert!(client.deliver_message(&third, &delivery_proof, &relayer));
+    assert_eq!(client.get_settled_sequence(&sender, &137), 3);
+}
+
+/// Store a pending queue that leaves no room for another message id
+fn fill_pending_queue(env: &Env, client: &MessagePassingClient) -> Vec<u64> {
+    let capacity = client.get_state_capacity();
+    let mut pending = Vec::new(env);
+    let mut next_id = 1_000u64;
+    loop {
+        let mut grown = pending.clone();
+        grown.push_back(next_id);
+        if capacity::serialized_size(env, &grown) > capacity {
+            break;
+        }
+        pending = grown;
+        next_id += 1;
+    }
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&MessageDataKey::PendingMessages, &pending);
+    });
+    pending
+}
+
+#[test]
+fn test_pending_queue_at_capacity_rejects_cleanly() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let pending = fill_pending_queue(&env, &client);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let result = client.try_send_message(
+        &137,
+        &recipient,
+        &MessageType::Generic,
+        &Bytes::from_slice(&env, b"payload"),
+        &GAS_LIMIT,
+        &sender,
+        &Bytes::from_slice(&env, b"signature"),
+        &None,
+    );
+    assert!(result.is_err());
+    
+    // The rejected write leaves the stored queue and counters untouched
+    assert_eq!(client.get_pending_messages(), pending);
+    assert_eq!(client.get_message_count(), 0);
+}
+
+#[test]
+fn test_realloc_state_unblocks_writes() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let pending = fill_pending_queue(&env, &client);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    assert!(client.try_send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None).is_err());
+    
+    let current = client.get_state_capacity();
+    assert!(client.try_realloc_state(&admin, &current).is_err());
+    assert!(client.try_realloc_state(&admin, &(capacity::MAX_STATE_CAPACITY + 1)).is_err());
+    
+    assert_eq!(client.realloc_state(&admin, &(current * 2)), current * 2);
+    let message_id = client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    assert_eq!(client.get_pending_messages().len(), pending.len() + 1);
+    assert_eq!(client.get_pending_messages().last(), Some(message_id));
+}
+
+#[test]
+fn test_undersized_gas_limit_fails_out_of_gas() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let relayer = Address::generate(&env);
+    let mut chains = Vec::new(&env);
+    chains.push_back(137u32);
+    client.register_relayer(&admin, &relayer, &chains, &1);
+    
+    let token_admin = Address::generate(&env);
+    let fee_token = env.register_stellar_asset_contract(token_admin);
+    client.set_fee_token(&admin, &fee_token);
+    client.set_chain_gas_price(&admin, &137, &2);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    token::StellarAssetClient::new(&env, &fee_token).mint(&sender, &1_000_000);
+    
+    // Enough gas to relay the message but not to verify the proof it carries is refused at send
+    assert!(client.try_send_message(&137, &recipient, &MessageType::Proof, &payload, &30_000, &sender, &signature, &None).is_err());
+    assert_eq!(client.get_message_count(), 0);
+    
+    // A message already stored with that limit still fails out of gas at delivery
+    let message_id = client.send_message(&137, &recipient, &MessageType::Proof, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    lower_gas_limit(&env, &client, message_id, 30_000);
+    
+    client.process_message(&message_id, &relayer);
+    assert!(!client.deliver_message(&message_id, &Bytes::from_slice(&env, b"delivered"), &relayer));
+    
+    let message = client.get_message(&message_id);
+    assert_eq!(message.status, MessageStatus::OutOfGas);
+    assert!(message.gas_used <= message.gas_limit);
+    
+    // Only the relaying gas is kept; the unused verification gas goes back to the sender
+    assert_eq!(message.fee, message.gas_used * 2);
+    let balance = token::Client::new(&env, &fee_token).balance(&sender);
+    assert_eq!(balance, 1_000_000 - message.fee as i128);
+}
+
+#[test]
+fn test_upheld_challenge_slashes_relayer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let relayer = Address::generate(&env);
+    let mut chains = Vec::new(&env);
+    chains.push_back(137u32);
+    let relayer_id = client.register_relayer(&admin, &relayer, &chains, &1);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let message_id = client.send_message(
+        &137,
+        &recipient,
+        &MessageType::Generic,
+        &Bytes::from_slice(&env, b"payload"),
+        &GAS_LIMIT,
+        &sender,
+        &Bytes::from_slice(&env, b"signature"),
+        &None,
+    );
+    client.process_message(&message_id, &relayer);
+    assert!(client.deliver_message(&message_id, &Bytes::from_slice(&env, b"delivered"), &relayer));
+    let reputation = client.get_relayer(&relayer_id).reputation;
+    
+    // Anyone can dispute the reported execution while the window is open, once a bond can be posted
+    let challenger = Address::generate(&env);
+    let evidence = Bytes::from_slice(&env, b"target revert receipt");
+    assert!(client.try_challenge_execution(&message_id, &evidence, &challenger).is_err());
+    
+    let fee_token = env.register_stellar_asset_contract(Address::generate(&env));
+    client.set_fee_token(&admin, &fee_token);
+    token::StellarAssetClient::new(&env, &fee_token).mint(&challenger, &1_000);
+    let balances = token::Client::new(&env, &fee_token);
+    client.challenge_execution(&message_id, &evidence, &challenger);
+    assert_eq!(balances.balance(&challenger), 0);
+    assert!(client.try_challenge_execution(&message_id, &evidence, &challenger).is_err());
+    
+    let challenge = client.get_challenge(&message_id).unwrap();
+    assert_eq!(challenge.relayer, relayer);
+    assert_eq!(challenge.status, ChallengeStatus::Open);
+    
+    client.resolve_challenge(&admin, &message_id, &true);
+    assert_eq!(client.get_challenge(&message_id).unwrap().status, ChallengeStatus::Upheld);
+    assert_eq!(balances.balance(&challenger), 1_000);
+    assert_eq!(client.get_message(&message_id).status, MessageStatus::Failed);
+    assert_eq!(client.get_delivery_result(&message_id).unwrap().status_code, DELIVERY_CHALLENGED);
+    assert_eq!(client.get_relayer(&relayer_id).reputation, reputation - 25);
+    
+    // The route counts the overturned delivery as a failure
+    let stats = client.get_route_stats(&1, &137);
+    assert_eq!((stats.delivered, stats.failed), (0, 1));
+}
+
+#[test]
+fn test_rejected_challenge_forfeits_bond_and_clears() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let fee_token = env.register_stellar_asset_contract(Address::generate(&env));
+    client.set_fee_token(&admin, &fee_token);
+    client.set_challenge_bond(&admin, &300);
+    
+    let relayer = Address::generate(&env);
+    client.register_relayer(&admin, &relayer, &Vec::from_array(&env, [137u32]), &1);
+    
+    let message_id = client.send_message(
+        &137,
+        &Address::generate(&env),
+        &MessageType::Generic,
+        &Bytes::from_slice(&env, b"payload"),
+        &GAS_LIMIT,
+        &Address::generate(&env),
+        &Bytes::from_slice(&env, b"signature"),
+        &None,
+    );
+    client.process_message(&message_id, &relayer);
+    
+    // Deliveries are only accepted from registered relayers
+    let impostor = Address::generate(&env);
+    let delivery_proof = Bytes::from_slice(&env, b"delivered");
+    assert!(client.try_deliver_message(&message_id, &delivery_proof, &impostor).is_err());
+    client.deliver_message(&message_id, &delivery_proof, &relayer);
+    
+    let griefer = Address::generate(&env);
+    let challenger = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &fee_token).mint(&griefer, &300);
+    token::StellarAssetClient::new(&env, &fee_token).mint(&challenger, &300);
+    let evidence = Bytes::from_slice(&env, b"evidence");
+    client.challenge_execution(&message_id, &evidence, &griefer);
+    client.resolve_challenge(&admin, &message_id, &false);
+    
+    // The rejected challenge no longer blocks a later one, and its bond is swept
+    assert!(client.get_challenge(&message_id).is_none());
+    assert_eq!(client.get_sweepable_fees(&fee_token), 300);
+    client.challenge_execution(&message_id, &evidence, &challenger);
+    assert_eq!(client.get_challenge(&message_id).unwrap().challenger, challenger);
+}
+
+#[test]
+fn test_challenge_rejected_after_dispute_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let relayer = Address::generate(&env);
+    let mut chains = Vec::new(&env);
+    chains.push_back(137u32);
+    client.register_relayer(&admin, &relayer, &chains, &1);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let message_id = client.send_message(
+        &137,
+        &recipient,
+        &MessageType::Generic,
+        &Bytes::from_slice(&env, b"payload"),
+        &GAS_LIMIT,
+        &sender,
+        &Bytes::from_slice(&env, b"signature"),
+        &None,
+    );
+    client.process_message(&message_id, &relayer);
+    client.deliver_message(&message_id, &Bytes::from_slice(&env, b"delivered"), &relayer);
+    
+    env.ledger().with_mut(|li| li.timestamp += 86_401);
+    let challenger = Address::generate(&env);
+    let evidence = Bytes::from_slice(&env, b"target revert receipt");
+    assert!(client.try_challenge_execution(&message_id, &evidence, &challenger).is_err());
+}
+
+#[test]
+fn test_delivery_result_decodes_per_message_type() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let verifier_id = env.register_contract(None, ChainVerifier);
+    let verifier = ChainVerifierClient::new(&env, &verifier_id);
+    verifier.initialize(&admin);
+    verifier.add_trusted_verifier(&admin, &client.address);
+    verifier.add_verification_rule(&admin, &VerificationRule {
+        rule_id: 0,
+        chain_id: 1,
+        min_confirmations: 1,
+        gas_limit: 1_000_000,
+        verification_method: String::from_str(&env, "merkle"),
+        active: true,
+    });
+    client.set_proof_verifier(&admin, &verifier_id);
+    
+    let relayer = Address::generate(&env);
+    let mut chains = Vec::new(&env);
+    chains.push_back(137u32);
+    client.register_relayer(&admin, &relayer, &chains, &1);
+    
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    let delivery_proof = Bytes::from_slice(&env, b"delivered");
+    
+    // Each message comes from its own sender so sequencing never holds one back
+    let deliver = |message_type: MessageType| {
+        let sender = Address::generate(&env);
+        let message_id = client.send_message(&137, &recipient, &message_type, &payload, &GAS_LIMIT, &sender, &signature, &None);
+        client.process_message(&message_id, &relayer);
+        client.deliver_message(&message_id, &delivery_proof, &relayer);
+        client.get_delivery_result(&message_id).unwrap()
+    };
+    
+    let receipt = env.crypto().sha256(&payload);
+    for message_type in [MessageType::ProofVerification, MessageType::AssetTransfer, MessageType::AtomicSwap, MessageType::Generic] {
+        let result = deliver(message_type.clone());
+        assert_eq!(result.message_type, message_type);
+        assert_eq!(result.status_code, DELIVERY_SUCCEEDED);
+        assert_eq!(BytesN::<32>::from_xdr(&env, &result.return_data).unwrap(), receipt);
+    }
+    
+    let result = deliver(MessageType::Proof);
+    assert_eq!(result.status_code, DELIVERY_SUCCEEDED);
+    assert!(bool::from_xdr(&env, &result.return_data).unwrap());
+    
+    let message_id = client.send_message(&137, &recipient, &MessageType::Proof, &payload, &GAS_LIMIT, &Address::generate(&env), &signature, &None);
+    lower_gas_limit(&env, &client, message_id, 30_000);
+    client.process_message(&message_id, &relayer);
+    client.deliver_message(&message_id, &delivery_proof, &relayer);
+    let result = client.get_delivery_result(&message_id).unwrap();
+    assert_eq!(result.status_code, DELIVERY_OUT_OF_GAS);
+    assert!(u64::from_xdr(&env, &result.return_data).unwrap() > 30_000);
+}
+
+#[test]
+fn test_route_stats_per_chain_pair() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let relayer = Address::generate(&env);
+    let mut chains = Vec::new(&env);
+    chains.push_back(137u32);
+    chains.push_back(56u32);
+    client.register_relayer(&admin, &relayer, &chains, &1);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    let delivery_proof = Bytes::from_slice(&env, b"delivered");
+    
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let first = client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    let second = client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    client.send_message(&56, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    
+    client.process_message(&first, &relayer);
+    client.process_message(&second, &relayer);
+    env.ledger().with_mut(|li| li.timestamp = 1_010);
+    client.deliver_message(&first, &delivery_proof, &relayer);
+    env.ledger().with_mut(|li| li.timestamp = 1_030);
+    client.deliver_message(&second, &delivery_proof, &relayer);
+    
+    assert_eq!(client.get_route_stats(&1, &137), RouteStats {
+        messages_sent: 3,
+        delivered: 2,
+        failed: 0,
+        average_latency: 20,
+    });
+    assert_eq!(client.get_route_stats(&1, &56), RouteStats {
+        messages_sent: 1,
+        delivered: 0,
+        failed: 0,
+        average_latency: 0,
+    });
+    assert_eq!(client.get_route_stats(&137, &1).messages_sent, 0);
+}
+
+#[test]
+fn test_is_chain_supported() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    assert!(!client.is_chain_supported(&137));
+    client.set_chain_gas_price(&admin, &137, &2);
+    assert!(client.is_chain_supported(&137));
+    assert!(!client.is_chain_supported(&56));
+}
+
+#[test]
+fn test_failed_message_dead_letters_and_requeues() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let relayer = Address::generate(&env);
+    let mut chains = Vec::new(&env);
+    chains.push_back(137u32);
+    client.register_relayer(&admin, &relayer, &chains, &1);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    let delivery_proof = Bytes::from_slice(&env, b"delivered");
+    let failed = client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    
+    // Too little gas to ever execute, so delivery fails for good
+    lower_gas_limit(&env, &client, failed, 20_000);
+    client.process_message(&failed, &relayer);
+    assert!(!client.deliver_message(&failed, &delivery_proof, &relayer));
+    
+    let dead_letters = client.get_dead_letters(&10);
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters.get(0).unwrap().message_id, failed);
+    assert_eq!(dead_letters.get(0).unwrap().status, MessageStatus::OutOfGas);
+    
+    // A successor settles past the failed message before the operator requeues it
+    let next = client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    client.process_message(&next, &relayer);
+    assert!(client.deliver_message(&next, &delivery_proof, &relayer));
+    
+    client.retry_dead_letter(&admin, &failed);
+    assert_eq!(client.get_dead_letters(&10).len(), 0);
+    assert_eq!(client.get_message(&failed).status, MessageStatus::Pending);
+    assert!(client.try_retry_dead_letter(&admin, &failed).is_err());
+    
+    client.process_message(&failed, &relayer);
+    assert!(client.deliver_message(&failed, &delivery_proof, &relayer));
+    assert_eq!(client.get_message(&failed).status, MessageStatus::Delivered);
+    assert_eq!(client.get_settled_sequence(&sender, &137), 2);
+}
+
+#[test]
+fn test_relayer_economics_track_stakes_and_slashing() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let token_admin = Address::generate(&env);
+    let fee_token = env.register_stellar_asset_contract(token_admin);
+    client.set_fee_token(&admin, &fee_token);
+    client.set_min_relayer_stake(&admin, &500);
+    
+    let mut chains = Vec::new(&env);
+    chains.push_back(137u32);
+    let relayer = Address::generate(&env);
+    let other = Address::generate(&env);
+    let relayer_id = client.register_relayer(&admin, &relayer, &chains, &1);
+    let other_id = client.register_relayer(&admin, &other, &chains, &1);
+    token::StellarAssetClient::new(&env, &fee_token).mint(&relayer, &1_000);
+    token::StellarAssetClient::new(&env, &fee_token).mint(&other, &1_000);
+    
+    // Relayers below the minimum stake cannot pick up messages
+    assert_eq!(client.get_relayer_economics(), (0, 0, 500));
+    client.stake_relayer(&relayer_id, &1_000);
+    client.stake_relayer(&other_id, &600);
+    assert_eq!(client.get_relayer_economics(), (1_600, 2, 500));
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let message_id = client.send_message(
+        &137,
+        &recipient,
+        &MessageType::Generic,
+        &Bytes::from_slice(&env, b"payload"),
+        &GAS_LIMIT,
+        &sender,
+        &Bytes::from_slice(&env, b"signature"),
+        &None,
+    );
+    client.process_message(&message_id, &relayer);
+    client.deliver_message(&message_id, &Bytes::from_slice(&env, b"delivered"), &relayer);
+    token::StellarAssetClient::new(&env, &fee_token).mint(&sender, &1_000);
+    client.challenge_execution(&message_id, &Bytes::from_slice(&env, b"evidence"), &sender);
+    client.resolve_challenge(&admin, &message_id, &true);
+    
+    assert_eq!(client.get_relayer_stake(&relayer_id), 900);
+    assert_eq!(client.get_sweepable_fees(&fee_token), 100);
+    assert_eq!(client.get_relayer_economics(), (1_500, 2, 500));
+    
+    // Withdrawing below the minimum refunds the stake and drops the relayer
+    env.ledger().with_mut(|li| li.timestamp = client.get_stake_unlocks_at(&other_id));
+    client.withdraw_relayer_stake(&other_id, &200);
+    assert!(!client.get_relayer(&other_id).active);
+    assert_eq!(token::Client::new(&env, &fee_token).balance(&other), 600);
+    assert_eq!(client.get_relayer_economics(), (1_300, 1, 500));
+}
+
+#[test]
+fn test_relay_message_with_relayer_signature() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let relayer = Address::generate(&env);
+    let mut chains = Vec::new(&env);
+    chains.push_back(137u32);
+    let relayer_id = client.register_relayer(&admin, &relayer, &chains, &1);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    let message_id = client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    let other_id = client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    
+    let key = SigningKey::from_bytes(&[4u8; 32]);
+    let public_key = BytesN::from_array(&env, &key.verifying_key().to_bytes());
+    let sign_with = |key: &SigningKey, payload: Bytes| {
+        let mut bytes = [0u8; 512];
+        let len = payload.len() as usize;
+        payload.copy_into_slice(&mut bytes[..len]);
+        BytesN::from_array(&env, &key.sign(&bytes[..len]).to_bytes())
+    };
+    let sign = |id: u64, relayer_id: u64| sign_with(&key, client.get_relay_payload(&id, &relayer_id));
+    
+    // Relaying needs a registered key
+    assert!(client.try_relay_message(&message_id, &relayer_id, &sign(message_id, relayer_id)).is_err());
+    
+    // Registering a key takes the key's own signature over this relayer's registration payload
+    let other_relayer = Address::generate(&env);
+    let other_relayer_id = client.register_relayer(&admin, &other_relayer, &chains, &1);
+    let impostor = SigningKey::from_bytes(&[5u8; 32]);
+    assert!(client.try_set_relayer_key(&relayer_id, &public_key, &sign_with(&impostor, client.get_key_registration_payload(&relayer_id))).is_err());
+    assert!(client.try_set_relayer_key(&relayer_id, &public_key, &sign_with(&key, client.get_key_registration_payload(&other_relayer_id))).is_err());
+    client.set_relayer_key(&relayer_id, &public_key, &sign_with(&key, client.get_key_registration_payload(&relayer_id)));
+    
+    // Nor can another relayer claim the same key
+    assert!(client.try_set_relayer_key(&other_relayer_id, &public_key, &sign_with(&key, client.get_key_registration_payload(&other_relayer_id))).is_err());
+    
+    // A signature over another message's or another relayer's payload does not authorize this one
+    assert!(client.try_relay_message(&message_id, &relayer_id, &sign(other_id, relayer_id)).is_err());
+    assert!(client.try_relay_message(&message_id, &relayer_id, &sign(message_id, other_relayer_id)).is_err());
+    assert_eq!(client.get_message(&message_id).status, MessageStatus::Pending);
+    
+    // Starved of gas, so the first delivery dead-letters it
+    lower_gas_limit(&env, &client, message_id, 20_000);
+    let first_attempt = sign(message_id, relayer_id);
+    assert!(client.relay_message(&message_id, &relayer_id, &first_attempt));
+    assert_eq!(client.get_message(&message_id).status, MessageStatus::InTransit);
+    assert!(client.try_relay_message(&message_id, &relayer_id, &sign(message_id, relayer_id)).is_err());
+    assert!(!client.deliver_message(&message_id, &Bytes::from_slice(&env, b"delivered"), &relayer));
+    
+    // Once requeued, the first attempt's signature can't be replayed; the relayer signs afresh
+    client.retry_dead_letter(&admin, &message_id);
+    assert!(client.try_relay_message(&message_id, &relayer_id, &first_attempt).is_err());
+    assert!(client.relay_message(&message_id, &relayer_id, &sign(message_id, relayer_id)));
+    assert_eq!(client.get_message(&message_id).status, MessageStatus::InTransit);
+}
+
+#[test]
+fn test_max_pending_rejects_until_queue_drains() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    assert_eq!(client.get_max_pending(), capacity::DEFAULT_MAX_PENDING);
+    assert!(client.try_set_max_pending(&admin, &0).is_err());
+    client.set_max_pending(&admin, &2);
+    
+    let relayer = Address::generate(&env);
+    let mut chains = Vec::new(&env);
+    chains.push_back(137u32);
+    client.register_relayer(&admin, &relayer, &chains, &1);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    let first = client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    assert!(client.try_send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None).is_err());
+    assert_eq!(client.get_message_count(), 2);
+    
+    // Picking up a message frees its slot
+    client.process_message(&first, &relayer);
+    client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    assert_eq!(client.get_pending_messages().len(), 2);
+}
+
+#[test]
+fn test_encryption_metadata_carried_to_delivery_result() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let relayer = Address::generate(&env);
+    let mut chains = Vec::new(&env);
+    chains.push_back(137u32);
+    client.register_relayer(&admin, &relayer, &chains, &1);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let ciphertext = Bytes::from_slice(&env, b"sealed to recipient");
+    let signature = Bytes::from_slice(&env, b"signature");
+    let scheme = EncryptionScheme::X25519ChaCha20Poly1305(BytesN::from_array(&env, &[9u8; 32]));
+    
+    let message_id = client.send_message(&137, &recipient, &MessageType::Generic, &ciphertext, &GAS_LIMIT, &sender, &signature, &Some(scheme.clone()));
+    assert_eq!(client.get_message(&message_id).encryption_scheme, scheme);
+    client.process_message(&message_id, &relayer);
+    assert!(client.deliver_message(&message_id, &Bytes::from_slice(&env, b"delivered"), &relayer));
+    
+    let result = client.get_delivery_result(&message_id).unwrap();
+    assert_eq!(result.encryption_scheme, scheme);
+    
+    // Unencrypted messages say so explicitly
+    let plain = client.send_message(&137, &recipient, &MessageType::Generic, &ciphertext, &GAS_LIMIT, &sender, &signature, &None);
+    assert_eq!(client.get_message(&plain).encryption_scheme, EncryptionScheme::Plaintext);
+}
+
+#[test]
+fn test_oversized_message_rejected_without_writing() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let signature = Bytes::from_slice(&env, b"signature");
+    let capacity = client.get_state_capacity();
+    let payload = Bytes::from_slice(&env, &[7u8; 20_000]);
+    assert!(payload.len() > capacity);
+    client.set_gas_cap(&admin, &MessageType::Generic, &5_000_000);
+    
+    let result = client.try_send_message(&137, &recipient, &MessageType::Generic, &payload, &5_000_000, &sender, &signature, &None);
+    assert!(result.is_err());
+    assert_eq!(client.get_message_count(), 0);
+    assert!(client.get_pending_messages().is_empty());
+    
+    // Growing the state budget lets the same message through intact
+    client.realloc_state(&admin, &(capacity * 2));
+    let message_id = client.send_message(&137, &recipient, &MessageType::Generic, &payload, &5_000_000, &sender, &signature, &None);
+    assert_eq!(client.get_message(&message_id).payload, payload);
+}
+
+
+#[test]
+fn test_gas_limit_below_chain_minimum_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    assert!(client.try_set_min_gas_limit(&sender, &137, &40_000).is_err());
+    client.set_min_gas_limit(&admin, &137, &40_000);
+    assert_eq!(client.get_min_gas_limit(&137), 40_000);
+    
+    // A minimum no message type's cap can meet is refused when configured
+    client.set_gas_cap(&admin, &MessageType::Proof, &30_000);
+    assert!(client.try_set_min_gas_limit(&admin, &56, &40_000).is_err());
+    assert_eq!(client.get_min_gas_limit(&56), 0);
+    
+    // The sender's limit must clear the destination chain's minimum
+    let result = client.try_send_message(&137, &recipient, &MessageType::Generic, &payload, &30_000, &sender, &signature, &None);
+    assert!(result.is_err());
+    assert_eq!(client.get_message_count(), 0);
+    
+    // The minimum is per chain, and an adequate limit goes through as sent
+    let other_chain = client.send_message(&56, &recipient, &MessageType::Generic, &payload, &30_000, &sender, &signature, &None);
+    assert_eq!(client.get_message(&other_chain).gas_limit, 30_000);
+    let message_id = client.send_message(&137, &recipient, &MessageType::Generic, &payload, &40_000, &sender, &signature, &None);
+    assert_eq!(client.get_message(&message_id).gas_limit, 40_000);
+    
+    // A limit below the message's own execution gas is refused on any chain
+    assert!(client.try_send_message(&56, &recipient, &MessageType::Generic, &payload, &21_000, &sender, &signature, &None).is_err());
+    
+    // And no sender may ask for more than the message type's cap
+    client.set_gas_cap(&admin, &MessageType::Generic, &100_000);
+    assert!(client.try_send_message(&137, &recipient, &MessageType::Generic, &payload, &100_001, &sender, &signature, &None).is_err());
+    assert_eq!(client.get_message_count(), 2);
+}
+
+#[test]
+fn test_stake_locked_until_lockup_after_last_relay() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let token_admin = Address::generate(&env);
+    let fee_token = env.register_stellar_asset_contract(token_admin);
+    client.set_fee_token(&admin, &fee_token);
+    assert!(client.try_set_stake_lockup(&Address::generate(&env), &1_000).is_err());
+    client.set_stake_lockup(&admin, &1_000);
+    
+    let mut chains = Vec::new(&env);
+    chains.push_back(137u32);
+    let relayer = Address::generate(&env);
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    let relayer_id = client.register_relayer(&admin, &relayer, &chains, &1);
+    token::StellarAssetClient::new(&env, &fee_token).mint(&relayer, &1_000);
+    client.stake_relayer(&relayer_id, &1_000);
+    assert_eq!(client.get_stake_unlocks_at(&relayer_id), 1_100);
+    
+    // Relaying restarts the lockup
+    env.ledger().with_mut(|li| li.timestamp = 900);
+    let message_id = client.send_message(
+        &137,
+        &Address::generate(&env),
+        &MessageType::Generic,
+        &Bytes::from_slice(&env, b"payload"),
+        &GAS_LIMIT,
+        &Address::generate(&env),
+        &Bytes::from_slice(&env, b"signature"),
+        &None,
+    );
+    client.process_message(&message_id, &relayer);
+    assert_eq!(client.get_stake_unlocks_at(&relayer_id), 1_900);
+    
+    env.ledger().with_mut(|li| li.timestamp = 1_500);
+    assert!(client.try_withdraw_relayer_stake(&relayer_id, &400).is_err());
+    assert_eq!(client.get_relayer_stake(&relayer_id), 1_000);
+    
+    env.ledger().with_mut(|li| li.timestamp = 1_900);
+    assert_eq!(client.withdraw_relayer_stake(&relayer_id, &400), 600);
+    assert_eq!(token::Client::new(&env, &fee_token).balance(&relayer), 400);
+}
+
+#[test]
+fn test_get_message_statuses_in_one_call() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let relayer = Address::generate(&env);
+    let mut chains = Vec::new(&env);
+    chains.push_back(137u32);
+    client.register_relayer(&admin, &relayer, &chains, &1);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    let pending = client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    let delivered = client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    client.process_message(&delivered, &relayer);
+    client.deliver_message(&delivered, &Bytes::from_slice(&env, b"delivered"), &relayer);
+    
+    let statuses = client.get_message_statuses(&Vec::from_array(&env, [pending, delivered, 99]));
+    assert_eq!(statuses, Vec::from_array(&env, [
+        (pending, Some(MessageStatus::Pending)),
+        (delivered, Some(MessageStatus::Delivered)),
+        (99, None),
+    ]));
+    
+    let mut too_many = Vec::new(&env);
+    for id in 0..101u64 {
+        too_many.push_back(id);
+    }
+    assert!(client.try_get_message_statuses(&too_many).is_err());
+}
+
+#[test]
+fn test_fee_multiplier_scales_fee_by_message_type() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    client.set_chain_gas_price(&admin, &137, &25);
+    
+    // Heavier types can be priced above the 1x default
+    assert_eq!(client.get_fee_multiplier(&MessageType::AssetTransfer), 10_000);
+    client.set_fee_multiplier(&admin, &MessageType::AssetTransfer, &20_000);
+    assert!(client.try_set_fee_multiplier(&admin, &MessageType::Generic, &0).is_err());
+    assert!(client.try_set_fee_multiplier(&admin, &MessageType::Generic, &100_001).is_err());
+    assert!(client.try_set_fee_multiplier(&Address::generate(&env), &MessageType::Generic, &20_000).is_err());
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let send = |message_type: MessageType| {
+        let message_id = client.send_message(
+            &137,
+            &recipient,
+            &message_type,
+            &payload,
+            &GAS_LIMIT,
+            &sender,
+            &Bytes::from_slice(&env, b"signature"),
+            &None,
+        );
+        client.get_message(&message_id).fee
+    };
+    
+    let generic_fee = send(MessageType::Generic);
+    let transfer_fee = send(MessageType::AssetTransfer);
+    assert!(generic_fee > 0);
+    assert_eq!(transfer_fee, generic_fee * 2);
+    assert_eq!(client.get_fee_estimate(&1_000, &137, &MessageType::AssetTransfer), 50_000);
+    assert_eq!(client.get_fee_estimate(&1_000, &137, &MessageType::Generic), 25_000);
+}
+
+#[test]
+fn test_in_transit_message_expires_only_after_grace() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    client.set_message_ttl(&admin, &50);
+    client.set_in_transit_grace(&admin, &100);
+    
+    let relayer = Address::generate(&env);
+    client.register_relayer(&admin, &relayer, &Vec::from_array(&env, [137u32]), &1);
+    
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    let send = |sender: &Address| {
+        client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, sender, &signature, &None)
+    };
+    let in_transit = send(&Address::generate(&env));
+    let pending = send(&Address::generate(&env));
+    client.process_message(&in_transit, &relayer);
+    assert_eq!(client.get_in_transit_messages(), Vec::from_array(&env, [in_transit]));
+    
+    // Past the timeout only the message nobody picked up expires
+    env.ledger().with_mut(|li| li.timestamp += 80);
+    assert_eq!(client.expire_pending_messages(), Vec::from_array(&env, [pending]));
+    assert_eq!(client.get_message(&pending).status, MessageStatus::Expired);
+    assert_eq!(client.get_message(&in_transit).status, MessageStatus::InTransit);
+    
+    // The relayer can still deliver within the grace
+    assert!(client.deliver_message(&in_transit, &Bytes::from_slice(&env, b"delivered"), &relayer));
+    assert!(client.get_in_transit_messages().is_empty());
+    
+    // One left in transit past the grace expires too
+    let stalled = send(&Address::generate(&env));
+    client.process_message(&stalled, &relayer);
+    env.ledger().with_mut(|li| li.timestamp += 151);
+    assert_eq!(client.expire_pending_messages(), Vec::from_array(&env, [stalled]));
+    assert_eq!(client.get_message(&stalled).status, MessageStatus::Expired);
+    assert!(client.try_deliver_message(&stalled, &Bytes::from_slice(&env, b"delivered"), &relayer).is_err());
+}
+
+#[test]
+fn test_expired_successor_settles_with_its_predecessor() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    client.set_message_ttl(&admin, &50);
+    client.set_in_transit_grace(&admin, &100);
+    
+    let relayer = Address::generate(&env);
+    client.register_relayer(&admin, &relayer, &Vec::from_array(&env, [137u32]), &1);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    let delivery_proof = Bytes::from_slice(&env, b"delivered");
+    let send = || client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    let first = send();
+    let second = send();
+    client.process_message(&first, &relayer);
+    
+    // The second message expires while the first is still in flight
+    env.ledger().with_mut(|li| li.timestamp += 80);
+    assert_eq!(client.expire_pending_messages(), Vec::from_array(&env, [second]));
+    assert_eq!(client.get_settled_sequence(&sender, &137), 0);
+    
+    // Settling the first carries the sequence past the expired second
+    assert!(client.deliver_message(&first, &delivery_proof, &relayer));
+    assert_eq!(client.get_settled_sequence(&sender, &137), 2);
+    
+    let third = send();
+    client.process_message(&third, &relayer);
+    assert!(client.deliver_message(&third, &delivery_proof, &relayer));
+    assert_eq!(client.get_settled_sequence(&sender, &137), 3);
+}
+
+#[test]
+fn test_denylisted_party_cannot_send_or_receive() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let denied = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    assert!(client.try_add_to_denylist(&sender, &denied).is_err());
+    client.add_to_denylist(&admin, &denied);
+    
+    assert!(client
+        .try_send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &denied, &signature, &None)
+        .is_err());
+    assert!(client
+        .try_send_message(&137, &denied, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None)
+        .is_err());
+    client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    
+    client.remove_from_denylist(&admin, &denied);
+    assert!(!client.is_denylisted(&denied));
+    client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &denied, &signature, &None);
+}
+
+#[test]
+fn test_oldest_pending_message() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    assert_eq!(client.get_oldest_pending(), None);
+    
+    let relayer = Address::generate(&env);
+    client.register_relayer(&admin, &relayer, &Vec::from_array(&env, [137u32]), &1);
+    
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    let send = |sender: &Address| {
+        client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, sender, &signature, &None)
+    };
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let first = send(&Address::generate(&env));
+    env.ledger().with_mut(|li| li.timestamp = 1_010);
+    let second = send(&Address::generate(&env));
+    env.ledger().with_mut(|li| li.timestamp = 1_030);
+    send(&Address::generate(&env));
+    assert_eq!(client.get_oldest_pending(), Some((first, 1_000)));
+    
+    // Once picked up, a message no longer counts toward the backlog
+    client.process_message(&first, &relayer);
+    assert_eq!(client.get_oldest_pending(), Some((second, 1_010)));
+}
+
+#[test]
+fn test_chained_messages_stop_at_hop_limit() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    assert!(client.try_set_max_hops(&Address::generate(&env), &1).is_err());
+    client.set_max_hops(&admin, &1);
+    
+    let relayer = Address::generate(&env);
+    client.register_relayer(&admin, &relayer, &Vec::from_array(&env, [137u32]), &1);
+    
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    let deliver = |message_id: u64| {
+        client.process_message(&message_id, &relayer);
+        client.deliver_message(&message_id, &Bytes::from_slice(&env, b"delivered"), &relayer);
+    };
+    let original = client.send_message(&137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    assert_eq!(client.get_message(&original).hop_count, 0);
+    
+    // Only the recipient of a delivered message can respond to it
+    assert!(client.try_send_chained_message(&original, &137, &sender, &MessageType::Generic, &payload, &GAS_LIMIT, &recipient, &signature).is_err());
+    deliver(original);
+    assert!(client.try_send_chained_message(&original, &137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature).is_err());
+    
+    let reply = client.send_chained_message(&original, &137, &sender, &MessageType::Generic, &payload, &GAS_LIMIT, &recipient, &signature);
+    assert_eq!(client.get_message(&reply).hop_count, 1);
+    
+    // A response to the response would be a second hop
+    deliver(reply);
+    assert!(client.try_send_chained_message(&reply, &137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature).is_err());
+    client.set_max_hops(&admin, &2);
+    let second = client.send_chained_message(&reply, &137, &recipient, &MessageType::Generic, &payload, &GAS_LIMIT, &sender, &signature);
+    assert_eq!(client.get_message(&second).hop_count, 2);
+}
+
+#[test]
+fn test_out_of_gas_refund_limited_to_collected_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    
+    let relayer = Address::generate(&env);
+    client.register_relayer(&admin, &relayer, &Vec::from_array(&env, [137u32]), &1);
+    client.set_chain_gas_price(&admin, &137, &2);
+    
+    // Sent before any fee token exists, so nothing is collected
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let payload = Bytes::from_slice(&env, b"payload");
+    let signature = Bytes::from_slice(&env, b"signature");
+    let message_id = client.send_message(&137, &recipient, &MessageType::Proof, &payload, &GAS_LIMIT, &sender, &signature, &None);
+    lower_gas_limit(&env, &client, message_id, 30_000);
+    
+    let token_admin = Address::generate(&env);
+    let fee_token = env.register_stellar_asset_contract(token_admin);
+    client.set_fee_token(&admin, &fee_token);
+    let payer = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &fee_token).mint(&payer, &1_000_000);
+    client.send_message(&137, &recipient, &MessageType::Proof, &payload, &GAS_LIMIT, &payer, &signature, &None);
+    let balances = token::Client::new(&env, &fee_token);
+    let vault_balance = balances.balance(&client.get_fee_vault());
+    
+    client.process_message(&message_id, &relayer);
+    assert!(!client.deliver_message(&message_id, &Bytes::from_slice(&env, b"delivered"), &relayer));
+    assert_eq!(client.get_message(&message_id).status, MessageStatus::OutOfGas);
+    
+    // Other senders' fees stay in the vault
+    assert_eq!(balances.balance(&sender), 0);
+    assert_eq!(balances.balance(&client.get_fee_vault()), vault_balance);
+}
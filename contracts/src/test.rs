@@ -1,7 +1,50 @@
 #[cfg(test)]
 mod tests {
-    use soroban_sdk::{Address, Bytes, Env};
-    use crate::{VerinodeContract, VerinodeContractClient};
+    use ed25519_dalek::{Signer, SigningKey};
+    use soroban_sdk::{
+        testutils::{Address as _, Events as _, Ledger as _, MockAuth, MockAuthInvoke},
+        symbol_short, token,
+        xdr::{ContractDataDurability, LedgerKey, LedgerKeyContractData},
+        Address, Bytes, BytesN, Env, IntoVal, String, Symbol, TryFromVal, Val, Vec,
+    };
+    use crate::{ChainVerificationRef, DataKey, MetadataUri, Proof, ProofAnchor, ProofStatus, ProofV1, ProofV8, RegistryRoot, VerinodeContract, VerinodeContractClient};
+    use crate::chainVerifier::{ChainVerifier, ChainVerifierClient, VerificationRule};
+
+    /// Ledger sequence through which a stored proof stays live
+    fn proof_live_until(env: &Env, contract_id: &Address, proof_id: u64) -> u32 {
+        entry_live_until(env, contract_id, &DataKey::Proof(proof_id))
+    }
+
+    /// Ledger sequence through which a persistent entry stays live
+    fn entry_live_until(env: &Env, contract_id: &Address, data_key: &DataKey) -> u32 {
+        let key = LedgerKey::ContractData(LedgerKeyContractData {
+            contract: contract_id.try_into().unwrap(),
+            key: data_key.try_into().unwrap(),
+            durability: ContractDataDurability::Persistent,
+        });
+        env.to_snapshot().ledger.ledger_entries.iter()
+            .find(|(entry_key, _)| **entry_key == key)
+            .and_then(|(_, (_, live_until))| *live_until)
+            .unwrap()
+    }
+
+    /// SHA-256 commitment to `event_data`, as issue_proof requires
+    fn event_hash(env: &Env, event_data: &Bytes) -> Bytes {
+        env.crypto().sha256(event_data).into()
+    }
+
+    /// Register `key` for `verifier` and sign the attestation digest of `proof_id`
+    fn sign_attestation(
+        env: &Env,
+        client: &VerinodeContractClient,
+        verifier: &Address,
+        key: &SigningKey,
+        proof_id: u64,
+    ) -> BytesN<64> {
+        client.set_verifier_key(verifier, &BytesN::from_array(env, &key.verifying_key().to_bytes()));
+        let digest = client.get_attestation_digest(&proof_id).to_array();
+        BytesN::from_array(env, &key.sign(&digest).to_bytes())
+    }
 
     #[test]
     fn test_initialize() {
@@ -28,20 +71,21 @@ mod tests {
         
         let issuer = Address::generate(&env);
         let event_data = Bytes::from_slice(&env, b"test event data");
-        let hash = Bytes::from_slice(&env, b"test hash");
+        let hash = event_hash(&env, &event_data);
         
-        let proof_id = client.issue_proof(&issuer, &event_data, &hash);
+        let proof_id = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
         assert_eq!(proof_id, 1);
         
         let proof = client.get_proof(&proof_id);
         assert_eq!(proof.id, proof_id);
         assert_eq!(proof.issuer, issuer);
-        assert!(!proof.verified);
+        assert_eq!(proof.status, ProofStatus::Pending);
     }
 
     #[test]
     fn test_verify_proof() {
         let env = Env::default();
+        env.mock_all_auths();
         let contract_id = env.register_contract(None, VerinodeContract);
         let client = VerinodeContractClient::new(&env, &contract_id);
         
@@ -50,16 +94,19 @@ mod tests {
         
         let issuer = Address::generate(&env);
         let event_data = Bytes::from_slice(&env, b"test event data");
-        let hash = Bytes::from_slice(&env, b"test hash");
+        let hash = event_hash(&env, &event_data);
         
-        let proof_id = client.issue_proof(&issuer, &event_data, &hash);
+        let proof_id = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
         
         // Verify proof
-        let result = client.verify_proof(&admin, &proof_id);
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let signature = sign_attestation(&env, &client, &admin, &key, proof_id);
+        let result = client.verify_proof(&admin, &proof_id, &signature);
         assert!(result);
         
         let proof = client.get_proof(&proof_id);
-        assert!(proof.verified);
+        assert_eq!(proof.status, ProofStatus::Verified);
+        assert_eq!(client.get_proof_verifier(&proof_id), Some(admin));
     }
 
     #[test]
@@ -75,12 +122,12 @@ mod tests {
         let issuer2 = Address::generate(&env);
         
         let event_data = Bytes::from_slice(&env, b"test event data");
-        let hash = Bytes::from_slice(&env, b"test hash");
+        let hash = event_hash(&env, &event_data);
         
         // Issue proofs for both issuers
-        client.issue_proof(&issuer1, &event_data, &hash);
-        client.issue_proof(&issuer2, &event_data, &hash);
-        client.issue_proof(&issuer1, &event_data, &hash);
+        client.issue_proof(&issuer1, &event_data, &hash, &None, &None, &None, &None);
+        client.issue_proof(&issuer2, &event_data, &hash, &None, &None, &None, &None);
+        client.issue_proof(&issuer1, &event_data, &hash, &None, &None, &None, &None);
         
         let proofs_issuer1 = client.get_proofs_by_issuer(&issuer1);
         assert_eq!(proofs_issuer1.len(), 2);
@@ -88,4 +135,1600 @@ mod tests {
         let proofs_issuer2 = client.get_proofs_by_issuer(&issuer2);
         assert_eq!(proofs_issuer2.len(), 1);
     }
+
+    #[test]
+    fn test_get_proofs_since() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let issuer = Address::generate(&env);
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        
+        // Issue proofs at increasing ledger timestamps
+        for ts in [100u64, 200, 300, 300, 400] {
+            env.ledger().with_mut(|li| li.timestamp = ts);
+            client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        }
+        
+        let (recent, next) = client.get_proofs_since(&300, &0, &10);
+        assert_eq!(recent.len(), 3);
+        assert_eq!(recent.get(0).unwrap().id, 3);
+        assert_eq!(recent.get(2).unwrap().id, 5);
+        assert_eq!(next, 0);
+        
+        // Page through with the returned cursor
+        let (first_page, cursor) = client.get_proofs_since(&200, &0, &2);
+        assert_eq!(first_page.len(), 2);
+        assert_eq!(cursor, first_page.get(1).unwrap().id);
+        let (second_page, next) = client.get_proofs_since(&200, &cursor, &2);
+        assert_eq!(second_page.len(), 2);
+        assert_eq!(second_page.get(0).unwrap().id, 4);
+        assert_eq!(second_page.get(1).unwrap().id, 5);
+        assert_eq!(next, 0);
+        
+        assert_eq!(client.get_proofs_since(&500, &0, &10).0.len(), 0);
+        
+        // Deleted ids are skipped without ending the walk early
+        client.delete_proof(&admin, &4);
+        let (recent, _) = client.get_proofs_since(&300, &0, &10);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent.get(1).unwrap().id, 5);
+    }
+
+    #[test]
+    fn test_verifier_list() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        client.initialize(&admin);
+        assert_eq!(client.get_verifier_count(), 0);
+        
+        let verifier1 = Address::generate(&env);
+        let verifier2 = Address::generate(&env);
+        client.add_verifier(&admin, &verifier1);
+        client.add_verifier(&admin, &verifier2);
+        
+        assert_eq!(client.get_verifier_count(), 2);
+        let verifiers = client.get_verifiers();
+        assert!(verifiers.contains(&verifier1));
+        assert!(verifiers.contains(&verifier2));
+        
+        client.remove_verifier(&admin, &verifier1);
+        assert_eq!(client.get_verifier_count(), 1);
+        assert!(!client.is_verifier(&verifier1));
+        assert!(client.is_verifier(&verifier2));
+        assert!(!client.get_verifiers().contains(&verifier1));
+        
+        // A registered verifier can verify proofs
+        let issuer = Address::generate(&env);
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        let proof_id = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        let key = SigningKey::from_bytes(&[2u8; 32]);
+        let signature = sign_attestation(&env, &client, &verifier2, &key, proof_id);
+        assert!(client.verify_proof(&verifier2, &proof_id, &signature));
+        assert!(client.try_verify_proof(&verifier1, &proof_id, &signature).is_err());
+    }
+
+    #[test]
+    fn test_issue_proof_with_metadata_uri() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let issuer = Address::generate(&env);
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        let uri = String::from_str(&env, "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi");
+        
+        let with_uri = client.issue_proof(&issuer, &event_data, &hash, &Some(uri.clone()), &None, &None, &None);
+        let without_uri = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        
+        assert_eq!(client.get_proof(&with_uri).metadata_uri, MetadataUri::Uri(uri));
+        assert_eq!(client.get_proof(&without_uri).metadata_uri, MetadataUri::Unset);
+        
+        let empty = String::from_str(&env, "");
+        assert!(client.try_issue_proof(&issuer, &event_data, &hash, &Some(empty), &None, &None, &None).is_err());
+    }
+
+    #[test]
+    fn test_legacy_proof_reads_without_metadata_uri() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        
+        let issuer = Address::generate(&env);
+        let legacy = ProofV1 {
+            id: 1,
+            issuer: issuer.clone(),
+            event_data: Bytes::from_slice(&env, b"test event data"),
+            timestamp: 0,
+            verified: false,
+            hash: Bytes::from_slice(&env, b"test hash"),
+        };
+        env.as_contract(&contract_id, || {
+            env.storage().instance().set(&DataKey::Proof(1), &legacy);
+            env.storage().instance().set(&DataKey::ProofCount, &1u64);
+        });
+        
+        let proof = client.get_proof(&1);
+        assert_eq!(proof.issuer, issuer);
+        assert_eq!(proof.metadata_uri, MetadataUri::Unset);
+        assert_eq!(proof.verifier_signature.len(), 0);
+        assert_eq!(proof.status, ProofStatus::Pending);
+    }
+
+    #[test]
+    fn test_legacy_empty_metadata_uri_reads_as_unset() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        
+        // Proofs stored with "" standing in for no URI
+        let uri = String::from_str(&env, "ipfs://evidence");
+        let legacy = |id: u64, metadata_uri: String| ProofV8 {
+            id,
+            issuer: Address::generate(&env),
+            event_data: Bytes::from_slice(&env, b"test event data"),
+            timestamp: 0,
+            status: ProofStatus::Pending,
+            hash: Bytes::from_slice(&env, b"test hash"),
+            metadata_uri,
+            verifier_signature: Bytes::new(&env),
+            supersedes: None,
+            parent_id: None,
+            anchor_chain_id: None,
+            anchor_block_hash: Bytes::new(&env),
+            expires_at: 0,
+        };
+        env.as_contract(&contract_id, || {
+            env.storage().persistent().set(&DataKey::Proof(1), &legacy(1, String::from_str(&env, "")));
+            env.storage().persistent().set(&DataKey::Proof(2), &legacy(2, uri.clone()));
+            env.storage().instance().set(&DataKey::ProofCount, &2u64);
+        });
+        
+        assert_eq!(client.get_proof(&1).metadata_uri, MetadataUri::Unset);
+        assert_eq!(client.get_proof(&1).anchor, ProofAnchor::Unanchored);
+        assert_eq!(client.get_proof(&2).metadata_uri, MetadataUri::Uri(uri));
+    }
+
+    #[test]
+    fn test_verify_attestation() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        client.initialize(&admin);
+        let verifier = Address::generate(&env);
+        client.add_verifier(&admin, &verifier);
+        
+        let issuer = Address::generate(&env);
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        let proof_id = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        assert!(!client.verify_attestation(&proof_id));
+        
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let signature = sign_attestation(&env, &client, &verifier, &key, proof_id);
+        client.verify_proof(&verifier, &proof_id, &signature);
+        
+        assert_eq!(client.get_proof(&proof_id).verifier_signature, Bytes::from(signature));
+        assert!(client.verify_attestation(&proof_id));
+        
+        // Rotating the verifier's key leaves earlier attestations checkable under the key that signed them
+        let old_key = BytesN::from_array(&env, &key.verifying_key().to_bytes());
+        let rotated = SigningKey::from_bytes(&[5u8; 32]);
+        client.set_verifier_key(&verifier, &BytesN::from_array(&env, &rotated.verifying_key().to_bytes()));
+        assert_eq!(client.get_attestation_key(&proof_id), Some(old_key));
+        assert!(client.verify_attestation(&proof_id));
+        
+        // A stored attestation that is not a full signature reads as absent rather than panicking
+        let mut proof = client.get_proof(&proof_id);
+        proof.verifier_signature = Bytes::from_slice(&env, b"short");
+        env.as_contract(&contract_id, || {
+            env.storage().persistent().set(&DataKey::Proof(proof_id), &proof);
+        });
+        assert!(!client.verify_attestation(&proof_id));
+    }
+
+    #[test]
+    fn test_forged_attestation_rejected() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        client.initialize(&admin);
+        let verifier = Address::generate(&env);
+        client.add_verifier(&admin, &verifier);
+        
+        let issuer = Address::generate(&env);
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        let proof_id = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        
+        // A signature from a key other than the verifier's is refused outright
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let forger = SigningKey::from_bytes(&[4u8; 32]);
+        client.set_verifier_key(&verifier, &BytesN::from_array(&env, &key.verifying_key().to_bytes()));
+        let digest = client.get_attestation_digest(&proof_id).to_array();
+        let forged = BytesN::from_array(&env, &forger.sign(&digest).to_bytes());
+        assert!(client.try_verify_proof(&verifier, &proof_id, &forged).is_err());
+        assert_eq!(client.get_proof(&proof_id).status, ProofStatus::Pending);
+        
+        // A stored attestation swapped for a forgery no longer checks out
+        let signature = BytesN::from_array(&env, &key.sign(&digest).to_bytes());
+        client.verify_proof(&verifier, &proof_id, &signature);
+        let mut proof = client.get_proof(&proof_id);
+        proof.verifier_signature = forged.into();
+        env.as_contract(&contract_id, || {
+            env.storage().persistent().set(&DataKey::Proof(proof_id), &proof);
+        });
+        assert!(client.try_verify_attestation(&proof_id).is_err());
+    }
+
+    #[test]
+    fn test_get_proof_extends_ttl() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let issuer = Address::generate(&env);
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        let proof_id = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        let issued_until = proof_live_until(&env, &contract_id, proof_id);
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let signature = sign_attestation(&env, &client, &admin, &key, proof_id);
+        client.verify_proof(&admin, &proof_id, &signature);
+        
+        client.set_proof_ttl_extension(&admin, &200_000);
+        client.get_proof(&proof_id);
+        let sequence = env.ledger().sequence();
+        assert_eq!(proof_live_until(&env, &contract_id, proof_id), sequence + 200_000);
+        assert!(sequence + 200_000 > issued_until);
+        
+        // The proof's attestation entries live in persistent storage and are kept alive with it
+        for data_key in [DataKey::ProofAttester(proof_id), DataKey::AttestationKey(proof_id), DataKey::ProofVerifiedAt(proof_id)] {
+            assert_eq!(entry_live_until(&env, &contract_id, &data_key), sequence + 200_000);
+        }
+        
+        // The extension is capped so reads can't pin proofs indefinitely
+        assert!(client.try_set_proof_ttl_extension(&admin, &10_000_000).is_err());
+    }
+
+    #[test]
+    fn test_is_proof_verified() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let issuer = Address::generate(&env);
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        let verified_id = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        let unverified_id = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let signature = sign_attestation(&env, &client, &admin, &key, verified_id);
+        client.verify_proof(&admin, &verified_id, &signature);
+        
+        assert!(client.is_proof_verified(&verified_id));
+        assert!(!client.is_proof_verified(&unverified_id));
+        assert!(!client.is_proof_verified(&99));
+    }
+
+    #[test]
+    fn test_proof_breakdown() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        client.initialize(&admin);
+        client.set_proof_validity(&admin, &1_000);
+        
+        let issuer = Address::generate(&env);
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        
+        env.ledger().with_mut(|li| li.timestamp = 100);
+        let first = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        let second = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        env.ledger().with_mut(|li| li.timestamp = 800);
+        let third = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        let fourth = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        
+        let breakdown = client.get_proof_breakdown();
+        assert_eq!((breakdown.verified, breakdown.unverified, breakdown.revoked, breakdown.expired), (0, 4, 0, 0));
+        
+        for proof_id in [first, third] {
+            let signature = sign_attestation(&env, &client, &admin, &key, proof_id);
+            client.verify_proof(&admin, &proof_id, &signature);
+        }
+        let breakdown = client.get_proof_breakdown();
+        assert_eq!((breakdown.verified, breakdown.unverified, breakdown.revoked, breakdown.expired), (2, 2, 0, 0));
+        
+        client.revoke_proof(&issuer, &third);
+        client.revoke_proof(&admin, &fourth);
+        assert!(client.try_revoke_proof(&issuer, &third).is_err());
+        assert!(!client.is_proof_verified(&third));
+        let breakdown = client.get_proof_breakdown();
+        assert_eq!((breakdown.verified, breakdown.unverified, breakdown.revoked, breakdown.expired), (1, 1, 2, 0));
+        
+        // Only the two proofs issued at t=100 are past their validity at t=1_200
+        env.ledger().with_mut(|li| li.timestamp = 1_200);
+        assert_eq!(client.expire_proofs(&10), 2);
+        assert!(client.is_proof_expired(&first));
+        assert!(client.is_proof_expired(&second));
+        assert!(!client.is_proof_expired(&third));
+        let breakdown = client.get_proof_breakdown();
+        assert_eq!((breakdown.verified, breakdown.unverified, breakdown.revoked, breakdown.expired), (0, 0, 2, 2));
+        
+        // Expired proofs can no longer be verified or revoked
+        let signature = sign_attestation(&env, &client, &admin, &key, second);
+        assert!(client.try_verify_proof(&admin, &second, &signature).is_err());
+        assert!(client.try_revoke_proof(&issuer, &second).is_err());
+    }
+
+    /// Sibling path for `index` in the batch tree, mirroring the contract's hashing rules
+    fn batch_merkle_proof(env: &Env, leaves: &Vec<Bytes>, index: u32) -> Vec<BytesN<32>> {
+        let mut level = Vec::new(env);
+        for leaf in leaves.iter() {
+            let mut preimage = Bytes::from_array(env, &[0u8]);
+            preimage.append(&leaf);
+            level.push_back(env.crypto().sha256(&preimage));
+        }
+        
+        let mut proof = Vec::new(env);
+        let mut position = index;
+        while level.len() > 1 {
+            let sibling = if position % 2 == 1 { Some(position - 1) } else if position + 1 < level.len() { Some(position + 1) } else { None };
+            if let Some(sibling) = sibling {
+                proof.push_back(level.get(sibling).unwrap());
+            }
+            
+            let mut next = Vec::new(env);
+            let mut i = 0;
+            while i < level.len() {
+                let left = level.get(i).unwrap();
+                match level.get(i + 1) {
+                    Some(right) => {
+                        let mut preimage = Bytes::from_array(env, &[1u8]);
+                        preimage.append(&left.into());
+                        preimage.append(&right.into());
+                        next.push_back(env.crypto().sha256(&preimage));
+                    }
+                    None => next.push_back(left),
+                }
+                i += 2;
+            }
+            level = next;
+            position /= 2;
+        }
+        proof
+    }
+
+    #[test]
+    fn test_batch_commitment_proves_leaf_inclusion() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        client.initialize(&admin);
+        
+        // Five readings leave an unpaired node on the first two levels
+        let mut leaves = Vec::new(&env);
+        for reading in [b"reading-0", b"reading-1", b"reading-2", b"reading-3", b"reading-4"] {
+            leaves.push_back(Bytes::from_slice(&env, reading));
+        }
+        let (batch_id, root) = client.issue_proof_batch_committed(&issuer, &leaves);
+        
+        let batch = client.get_proof_batch(&batch_id);
+        assert_eq!(batch.root, root);
+        assert_eq!(batch.leaf_count, 5);
+        assert_eq!(client.get_proof_count(), 0);
+        
+        for index in 0..leaves.len() {
+            let proof = batch_merkle_proof(&env, &leaves, index);
+            assert!(client.verify_leaf_in_batch(&batch_id, &leaves.get(index).unwrap(), &index, &proof));
+        }
+        
+        // A proof only holds for its own leaf and position
+        let proof = batch_merkle_proof(&env, &leaves, 1);
+        assert!(!client.verify_leaf_in_batch(&batch_id, &Bytes::from_slice(&env, b"forged"), &1, &proof));
+        assert!(!client.verify_leaf_in_batch(&batch_id, &leaves.get(1).unwrap(), &0, &proof));
+        assert!(!client.verify_leaf_in_batch(&batch_id, &leaves.get(1).unwrap(), &7, &proof));
+        
+        assert!(client.try_issue_proof_batch_committed(&issuer, &Vec::new(&env)).is_err());
+    }
+
+    /// Topics and data of the most recent contract event
+    fn last_event(env: &Env) -> (Vec<Val>, Val) {
+        let (_, topics, data) = env.events().all().last().unwrap();
+        (topics, data)
+    }
+
+    #[test]
+    fn test_lifecycle_transitions_publish_events() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let new_issuer = Address::generate(&env);
+        let verifier = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let event_data = Bytes::from_slice(&env, b"event data");
+        let hash = event_hash(&env, &event_data);
+        let proof_key = symbol_short!("proof");
+        let verifier_key = symbol_short!("verifier");
+        
+        client.add_verifier(&admin, &verifier);
+        let (topics, data) = last_event(&env);
+        assert_eq!(topics, (verifier_key.clone(), symbol_short!("added")).into_val(&env));
+        assert_eq!(Address::try_from_val(&env, &data).unwrap(), verifier);
+        
+        client.remove_verifier(&admin, &verifier);
+        let (topics, data) = last_event(&env);
+        assert_eq!(topics, (verifier_key, symbol_short!("removed")).into_val(&env));
+        assert_eq!(Address::try_from_val(&env, &data).unwrap(), verifier);
+        
+        let transferred = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        client.transfer_proof(&issuer, &transferred, &new_issuer);
+        let (topics, data) = last_event(&env);
+        assert_eq!(topics, (proof_key.clone(), Symbol::new(&env, "transferred"), transferred).into_val(&env));
+        assert_eq!(<(Address, Address)>::try_from_val(&env, &data).unwrap(), (issuer.clone(), new_issuer.clone()));
+        assert_eq!(client.get_proof(&transferred).issuer, new_issuer);
+        assert!(client.try_transfer_proof(&issuer, &transferred, &issuer).is_err());
+        
+        client.revoke_proof(&new_issuer, &transferred);
+        let (topics, data) = last_event(&env);
+        assert_eq!(topics, (proof_key.clone(), symbol_short!("revoked"), transferred).into_val(&env));
+        assert_eq!(Address::try_from_val(&env, &data).unwrap(), new_issuer);
+        
+        client.set_proof_validity(&admin, &100);
+        let expiring = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        env.ledger().with_mut(|li| li.timestamp = 1_000);
+        assert_eq!(client.expire_proofs(&10), 1);
+        let (topics, data) = last_event(&env);
+        assert_eq!(topics, (proof_key, symbol_short!("expired"), expiring).into_val(&env));
+        assert_eq!(Address::try_from_val(&env, &data).unwrap(), issuer);
+    }
+
+    #[test]
+    fn test_event_data_size_limit() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let issue = |event_data: Bytes| {
+            client.try_issue_proof(&issuer, &event_data, &event_hash(&env, &event_data), &None, &None, &None, &None).is_ok()
+        };
+        assert_eq!(client.get_max_event_data_size(), 2_048);
+        assert!(issue(Bytes::from_array(&env, &[7u8; 2_048])));
+        assert!(!issue(Bytes::from_array(&env, &[7u8; 2_049])));
+        
+        client.set_max_event_data_size(&admin, &16);
+        assert!(issue(Bytes::from_array(&env, &[7u8; 16])));
+        assert!(!issue(Bytes::from_array(&env, &[7u8; 17])));
+        assert!(client.try_set_max_event_data_size(&issuer, &4_096).is_err());
+    }
+
+    #[test]
+    fn test_issue_fee_modes() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let treasury = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let event_data = Bytes::from_slice(&env, b"event data");
+        let hash = event_hash(&env, &event_data);
+        
+        // With no fee set issuance stays free, even without a fee token
+        assert_eq!(client.get_issue_fee(), 0);
+        client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        assert_eq!(client.get_fees_collected(), 0);
+        
+        let fee_token = env.register_stellar_asset_contract(Address::generate(&env));
+        client.set_fee_token(&admin, &fee_token);
+        client.set_treasury(&admin, &treasury);
+        client.set_issue_fee(&admin, &100);
+        
+        // An issuer who cannot pay is turned away
+        assert!(client.try_issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None).is_err());
+        assert_eq!(client.get_proof_count(), 1);
+        
+        token::StellarAssetClient::new(&env, &fee_token).mint(&issuer, &250);
+        client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        
+        let balances = token::Client::new(&env, &fee_token);
+        assert_eq!(balances.balance(&treasury), 200);
+        assert_eq!(balances.balance(&issuer), 50);
+        assert_eq!(client.get_fees_collected(), 200);
+        assert!(client.try_issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None).is_err());
+    }
+
+    #[test]
+    fn test_reissue_proof_supersedes_old() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let key = SigningKey::from_bytes(&[9u8; 32]);
+        client.initialize(&admin);
+        
+        let uri = String::from_str(&env, "ipfs://reading");
+        let wrong = Bytes::from_slice(&env, b"wrong reading");
+        let old = client.issue_proof(&issuer, &wrong, &event_hash(&env, &wrong), &Some(uri.clone()), &None, &None, &None);
+        assert_eq!(client.get_proof(&old).supersedes, None);
+        
+        let corrected = Bytes::from_slice(&env, b"corrected reading");
+        let hash = event_hash(&env, &corrected);
+        assert!(client.try_reissue_proof(&Address::generate(&env), &old, &corrected, &hash).is_err());
+        
+        let new = client.reissue_proof(&issuer, &old, &corrected, &hash);
+        assert!(client.is_proof_revoked(&old));
+        
+        let proof = client.get_proof(&new);
+        assert_eq!(proof.supersedes, Some(old));
+        assert_eq!(proof.event_data, corrected);
+        assert_eq!(proof.metadata_uri, MetadataUri::Uri(uri));
+        
+        let breakdown = client.get_proof_breakdown();
+        assert_eq!((breakdown.unverified, breakdown.revoked), (1, 1));
+        
+        // Revoked and verified proofs cannot be reissued
+        assert!(client.try_reissue_proof(&issuer, &old, &corrected, &hash).is_err());
+        let signature = sign_attestation(&env, &client, &admin, &key, new);
+        client.verify_proof(&admin, &new, &signature);
+        assert!(client.try_reissue_proof(&issuer, &new, &corrected, &hash).is_err());
+    }
+
+    #[test]
+    fn test_sweep_expired_proofs_in_batches() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        client.initialize(&admin);
+        client.set_proof_validity(&admin, &1_000);
+        
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        env.ledger().with_mut(|li| li.timestamp = 100);
+        for _ in 0..4 {
+            client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        }
+        client.revoke_proof(&issuer, &2);
+        env.ledger().with_mut(|li| li.timestamp = 900);
+        let live = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        
+        env.ledger().with_mut(|li| li.timestamp = 1_500);
+        assert!(client.try_sweep_expired_proofs(&issuer, &1, &3).is_err());
+        assert!(client.try_sweep_expired_proofs(&admin, &1, &501).is_err());
+        
+        // Revoked proofs are skipped and each expired proof is swept once
+        assert_eq!(client.sweep_expired_proofs(&admin, &1, &3), (4, 2));
+        assert_eq!(client.sweep_expired_proofs(&admin, &4, &3), (0, 1));
+        assert_eq!(client.sweep_expired_proofs(&admin, &1, &5), (0, 0));
+        assert_eq!(client.expire_proofs(&10), 0);
+        assert!(client.is_proof_expired(&4));
+        assert!(!client.is_proof_expired(&live));
+        
+        let breakdown = client.get_proof_breakdown();
+        assert_eq!((breakdown.verified, breakdown.unverified, breakdown.revoked, breakdown.expired), (0, 1, 1, 3));
+        
+        // A proof's own ttl expires it ahead of the validity period, and the sweep counts it
+        let short_lived = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &Some(10));
+        assert_eq!(client.sweep_expired_proofs(&admin, &short_lived, &1), (0, 0));
+        env.ledger().with_mut(|li| li.timestamp = 1_511);
+        assert!(client.is_expired(&short_lived));
+        assert_eq!(client.sweep_expired_proofs(&admin, &1, &10), (0, 1));
+        assert!(client.is_proof_expired(&short_lived));
+        assert!(!client.is_expired(&live));
+        
+        // Past the validity period a proof is expired even before a sweep marks it
+        env.ledger().with_mut(|li| li.timestamp = 1_900);
+        assert!(client.is_expired(&live));
+        assert!(!client.is_proof_expired(&live));
+        
+        let breakdown = client.get_proof_breakdown();
+        assert_eq!((breakdown.verified, breakdown.unverified, breakdown.revoked, breakdown.expired), (0, 1, 1, 4));
+    }
+
+    #[test]
+    fn test_delegate_verifies_until_expiry_or_revocation() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let verifier = Address::generate(&env);
+        let delegate = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let key = SigningKey::from_bytes(&[5u8; 32]);
+        client.initialize(&admin);
+        client.add_verifier(&admin, &verifier);
+        
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        let first = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        let second = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        let third = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        
+        assert!(client.try_delegate_verification(&delegate, &issuer, &500).is_err());
+        env.ledger().with_mut(|li| li.timestamp = 100);
+        assert!(client.try_delegate_verification(&verifier, &delegate, &100).is_err());
+        client.delegate_verification(&verifier, &delegate, &500);
+        
+        // Only the delegate signs the transaction; the attestation stays the verifier's
+        let signature = sign_attestation(&env, &client, &verifier, &key, first);
+        let delegate_only = |proof_id: u64, signature: &BytesN<64>| {
+            env.mock_auths(&[MockAuth {
+                address: &delegate,
+                invoke: &MockAuthInvoke {
+                    contract: &contract_id,
+                    fn_name: "verify_proof",
+                    args: (verifier.clone(), proof_id, signature.clone()).into_val(&env),
+                    sub_invokes: &[],
+                },
+            }]);
+        };
+        delegate_only(first, &signature);
+        assert!(client.verify_proof(&verifier, &first, &signature));
+        assert!(client.is_proof_verified(&first));
+        
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 500);
+        let signature = sign_attestation(&env, &client, &verifier, &key, second);
+        delegate_only(second, &signature);
+        assert!(client.try_verify_proof(&verifier, &second, &signature).is_err());
+        
+        env.mock_all_auths();
+        client.delegate_verification(&verifier, &delegate, &1_000);
+        client.revoke_delegation(&verifier);
+        assert_eq!(client.get_verification_delegate(&verifier), None);
+        assert!(client.try_revoke_delegation(&verifier).is_err());
+        
+        let signature = sign_attestation(&env, &client, &verifier, &key, third);
+        delegate_only(third, &signature);
+        assert!(client.try_verify_proof(&verifier, &third, &signature).is_err());
+    }
+
+    #[test]
+    fn test_issue_proof_anchored_to_external_block() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        let block_hash = Bytes::from_array(&env, &[0xabu8; 32]);
+        let anchored = client.issue_proof(&issuer, &event_data, &hash, &None, &Some(1), &Some(block_hash.clone()), &None);
+        
+        assert_eq!(client.get_proof(&anchored).anchor, ProofAnchor::Block(1, block_hash.clone()));
+        
+        let unanchored = client.get_proof(&client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None));
+        assert_eq!(unanchored.anchor, ProofAnchor::Unanchored);
+        
+        let short_hash = Bytes::from_array(&env, &[0xabu8; 20]);
+        assert!(client.try_issue_proof(&issuer, &event_data, &hash, &None, &Some(1), &Some(short_hash), &None).is_err());
+        assert!(client.try_issue_proof(&issuer, &event_data, &hash, &None, &Some(1), &None, &None).is_err());
+        assert!(client.try_issue_proof(&issuer, &event_data, &hash, &None, &None, &Some(block_hash), &None).is_err());
+    }
+
+    #[test]
+    fn test_get_proofs_verified_by_attributes_verifiers() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let verifier = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let admin_key = SigningKey::from_bytes(&[6u8; 32]);
+        let verifier_key = SigningKey::from_bytes(&[7u8; 32]);
+        client.initialize(&admin);
+        client.add_verifier(&admin, &verifier);
+        
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        let mut ids = [0u64; 5];
+        for id in ids.iter_mut() {
+            *id = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        }
+        
+        // The log keeps attestation order, not id order
+        for proof_id in [ids[4], ids[0], ids[2]] {
+            let signature = sign_attestation(&env, &client, &verifier, &verifier_key, proof_id);
+            client.verify_proof(&verifier, &proof_id, &signature);
+        }
+        let signature = sign_attestation(&env, &client, &admin, &admin_key, ids[1]);
+        client.verify_proof(&admin, &ids[1], &signature);
+        
+        let verified_ids = |(proofs, _): (Vec<Proof>, u64)| {
+            let mut proof_ids = Vec::new(&env);
+            for proof in proofs.iter() {
+                proof_ids.push_back(proof.id);
+            }
+            proof_ids
+        };
+        assert_eq!(verified_ids(client.get_proofs_verified_by(&verifier, &1, &10)), Vec::from_slice(&env, &[ids[4], ids[0], ids[2]]));
+        assert_eq!(verified_ids(client.get_proofs_verified_by(&admin, &1, &10)), Vec::from_slice(&env, &[ids[1]]));
+        
+        let (page, next) = client.get_proofs_verified_by(&verifier, &1, &2);
+        assert_eq!(verified_ids((page, next)), Vec::from_slice(&env, &[ids[4], ids[0]]));
+        assert_eq!(next, 3);
+        let (page, next) = client.get_proofs_verified_by(&verifier, &next, &2);
+        assert_eq!(verified_ids((page, next)), Vec::from_slice(&env, &[ids[2]]));
+        assert_eq!(next, 0);
+        
+        // Re-attestation moves the proof to its new verifier, and attesting again does not list it twice
+        let signature = sign_attestation(&env, &client, &admin, &admin_key, ids[2]);
+        client.verify_proof(&admin, &ids[2], &signature);
+        client.verify_proof(&admin, &ids[2], &signature);
+        assert_eq!(verified_ids(client.get_proofs_verified_by(&verifier, &1, &10)), Vec::from_slice(&env, &[ids[4], ids[0]]));
+        assert_eq!(verified_ids(client.get_proofs_verified_by(&admin, &1, &10)), Vec::from_slice(&env, &[ids[1], ids[2]]));
+        assert_eq!(client.get_proof_verifier(&ids[2]), Some(admin.clone()));
+        assert_eq!(client.get_proof_verifier(&ids[4]), Some(verifier.clone()));
+        assert_eq!(client.get_proof_verifier(&ids[3]), None);
+        
+        // Only the admin manages the set, and a removed verifier can no longer verify
+        assert!(client.try_remove_verifier(&verifier, &verifier).is_err());
+        let signature = sign_attestation(&env, &client, &verifier, &verifier_key, ids[3]);
+        client.remove_verifier(&admin, &verifier);
+        assert!(client.try_verify_proof(&verifier, &ids[3], &signature).is_err());
+    }
+
+    #[test]
+    fn test_challenge_proof_within_dispute_period() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let verifier = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let challenger = Address::generate(&env);
+        let key = SigningKey::from_bytes(&[6u8; 32]);
+        client.initialize(&admin);
+        client.add_verifier(&admin, &verifier);
+        client.set_dispute_period(&admin, &100);
+        
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        let evidence = Bytes::from_slice(&env, b"counter evidence");
+        let first = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        let second = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        assert!(client.try_challenge_proof(&challenger, &first, &evidence).is_err());
+        
+        env.ledger().with_mut(|li| li.timestamp = 1_000);
+        let signature = sign_attestation(&env, &client, &verifier, &key, first);
+        client.verify_proof(&verifier, &first, &signature);
+        let signature = sign_attestation(&env, &client, &verifier, &key, second);
+        client.verify_proof(&verifier, &second, &signature);
+        assert!(!client.is_verification_final(&first));
+        
+        // Without a bond only verifiers may challenge; with one, anyone who posts it
+        assert!(client.try_challenge_proof(&challenger, &first, &evidence).is_err());
+        let fee_token = env.register_stellar_asset_contract(Address::generate(&env));
+        let treasury = Address::generate(&env);
+        token::StellarAssetClient::new(&env, &fee_token).mint(&challenger, &1_000);
+        assert!(client.try_set_dispute_bond(&admin, &100).is_err());
+        client.set_fee_token(&admin, &fee_token);
+        client.set_treasury(&admin, &treasury);
+        client.set_dispute_bond(&admin, &100);
+        let balances = token::Client::new(&env, &fee_token);
+        
+        // A challenge suspends the verification until the admin rules on it
+        assert!(client.try_challenge_proof(&challenger, &first, &Bytes::new(&env)).is_err());
+        client.challenge_proof(&challenger, &first, &evidence);
+        assert_eq!(balances.balance(&challenger), 900);
+        assert!(client.try_challenge_proof(&challenger, &first, &evidence).is_err());
+        assert_eq!(client.get_proof_dispute(&first).unwrap().challenger, challenger);
+        assert!(!client.is_proof_verified(&first));
+        assert!(client.try_resolve_dispute(&issuer, &first, &true).is_err());
+        
+        client.resolve_dispute(&admin, &first, &true);
+        assert_eq!(balances.balance(&challenger), 1_000);
+        assert_eq!(client.get_proof_dispute(&first), None);
+        assert!(!client.is_proof_verified(&first));
+        assert_eq!(client.get_proof_breakdown().verified, 1);
+        assert!(client.try_resolve_dispute(&admin, &first, &true).is_err());
+        
+        // A rejected challenge leaves the verification in place and forfeits the bond
+        client.challenge_proof(&challenger, &second, &evidence);
+        client.resolve_dispute(&admin, &second, &false);
+        assert!(client.is_proof_verified(&second));
+        assert_eq!(balances.balance(&challenger), 900);
+        assert_eq!(balances.balance(&treasury), 100);
+        
+        env.ledger().with_mut(|li| li.timestamp = 1_101);
+        assert!(client.is_verification_final(&second));
+        assert!(client.try_challenge_proof(&challenger, &second, &evidence).is_err());
+    }
+
+    #[test]
+    fn test_admin_transfer_requires_acceptance() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let new_admin = Address::generate(&env);
+        let typo = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let key = SigningKey::from_bytes(&[8u8; 32]);
+        client.initialize(&admin);
+        
+        assert!(client.try_transfer_admin(&new_admin, &new_admin).is_err());
+        assert!(client.try_accept_admin(&new_admin).is_err());
+        
+        // A mistyped nomination changes nothing and can be replaced
+        client.transfer_admin(&admin, &typo);
+        assert_eq!(client.get_admin(), admin);
+        client.transfer_admin(&admin, &new_admin);
+        assert!(client.try_accept_admin(&typo).is_err());
+        
+        client.accept_admin(&new_admin);
+        assert_eq!(client.get_admin(), new_admin);
+        assert_eq!(client.get_pending_admin(), None);
+        assert!(client.try_transfer_admin(&admin, &admin).is_err());
+        
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        let proof_id = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        assert!(client.try_set_verifier_key(&admin, &BytesN::from_array(&env, &key.verifying_key().to_bytes())).is_err());
+        let signature = sign_attestation(&env, &client, &new_admin, &key, proof_id);
+        assert!(client.try_verify_proof(&admin, &proof_id, &signature).is_err());
+        assert!(client.verify_proof(&new_admin, &proof_id, &signature));
+    }
+
+    #[test]
+    fn test_issue_and_verify_publish_events() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let key = SigningKey::from_bytes(&[9u8; 32]);
+        client.initialize(&admin);
+        
+        let event_data = Bytes::from_slice(&env, b"event data");
+        let hash = event_hash(&env, &event_data);
+        
+        let proof_id = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        let (topics, data) = last_event(&env);
+        assert_eq!(topics, (symbol_short!("issued"), issuer).into_val(&env));
+        assert_eq!(<(u64, Bytes)>::try_from_val(&env, &data).unwrap(), (proof_id, hash.clone()));
+        
+        let signature = sign_attestation(&env, &client, &admin, &key, proof_id);
+        client.verify_proof(&admin, &proof_id, &signature);
+        let (topics, data) = last_event(&env);
+        assert_eq!(topics, (symbol_short!("verified"), admin).into_val(&env));
+        assert_eq!(<(u64, Bytes)>::try_from_val(&env, &data).unwrap(), (proof_id, hash));
+    }
+
+    #[test]
+    fn test_link_proof_to_chain_verification() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let chain_verifier_id = env.register_contract(None, ChainVerifier);
+        let chain_verifier = ChainVerifierClient::new(&env, &chain_verifier_id);
+        let verifier = Address::generate(&env);
+        chain_verifier.initialize(&admin);
+        chain_verifier.add_trusted_verifier(&admin, &verifier);
+        chain_verifier.add_verification_rule(&admin, &VerificationRule {
+            rule_id: 0,
+            chain_id: 1,
+            min_confirmations: 1,
+            gas_limit: 1_000_000,
+            verification_method: String::from_str(&env, "merkle"),
+            active: true,
+        });
+        let event_data = Bytes::from_slice(&env, b"event data");
+        let hash = event_hash(&env, &event_data);
+        chain_verifier.verify_proof_on_chain(&10, &1, &Bytes::from_slice(&env, b"proof data"), &verifier, &hash);
+        chain_verifier.verify_proof_on_chain(&11, &1, &Bytes::new(&env), &verifier, &hash);
+        chain_verifier.verify_proof_on_chain(&13, &1, &Bytes::from_slice(&env, b"proof data"), &verifier, &Bytes::from_slice(&env, b"other hash"));
+        
+        // A second chainVerifier holding the same result is not the one the admin trusts
+        let untrusted_id = env.register_contract(None, ChainVerifier);
+        let untrusted = ChainVerifierClient::new(&env, &untrusted_id);
+        untrusted.initialize(&admin);
+        untrusted.add_trusted_verifier(&admin, &verifier);
+        untrusted.add_verification_rule(&admin, &VerificationRule {
+            rule_id: 0,
+            chain_id: 1,
+            min_confirmations: 1,
+            gas_limit: 1_000_000,
+            verification_method: String::from_str(&env, "merkle"),
+            active: true,
+        });
+        untrusted.verify_proof_on_chain(&10, &1, &Bytes::from_slice(&env, b"proof data"), &verifier, &hash);
+        
+        let proof_id = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        let reference = |chain_verifier: &Address, chain_id: u32, proof_id: u64| ChainVerificationRef {
+            chain_verifier: chain_verifier.clone(),
+            chain_id,
+            proof_id,
+        };
+        
+        // Nothing links until the admin names a chainVerifier
+        assert!(client.try_link_verified_proof(&proof_id, &reference(&chain_verifier_id, 1, 10)).is_err());
+        assert!(client.try_set_chain_verifier(&admin, &contract_id).is_err());
+        client.set_chain_verifier(&admin, &chain_verifier_id);
+        
+        // Malformed, untrusted, failed, mismatched and unknown references are all rejected
+        assert!(client.try_link_verified_proof(&proof_id, &reference(&contract_id, 1, 10)).is_err());
+        assert!(client.try_link_verified_proof(&proof_id, &reference(&untrusted_id, 1, 10)).is_err());
+        assert!(client.try_link_verified_proof(&proof_id, &reference(&chain_verifier_id, 1, 13)).is_err());
+        assert!(client.try_link_verified_proof(&proof_id, &reference(&chain_verifier_id, 0, 10)).is_err());
+        assert!(client.try_link_verified_proof(&proof_id, &reference(&chain_verifier_id, 1, 11)).is_err());
+        assert!(client.try_link_verified_proof(&proof_id, &reference(&chain_verifier_id, 1, 12)).is_err());
+        assert!(client.try_link_verified_proof(&(proof_id + 1), &reference(&chain_verifier_id, 1, 10)).is_err());
+        assert_eq!(client.get_linked_verification(&proof_id), None);
+        
+        client.link_verified_proof(&proof_id, &reference(&chain_verifier_id, 1, 10));
+        assert_eq!(client.get_linked_verification(&proof_id), Some(reference(&chain_verifier_id, 1, 10)));
+        assert!(client.try_link_verified_proof(&proof_id, &reference(&chain_verifier_id, 1, 10)).is_err());
+    }
+
+    #[test]
+    fn test_get_proofs_by_issuer_paged() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let other = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        for i in 0..7 {
+            let owner = if i % 2 == 0 { &issuer } else { &other };
+            client.issue_proof(owner, &event_data, &hash, &None, &None, &None, &None);
+        }
+        
+        let ids = |proofs: Vec<Proof>| {
+            let mut ids = Vec::new(&env);
+            for proof in proofs.iter() {
+                ids.push_back(proof.id);
+            }
+            ids
+        };
+        
+        // Advancing past the last id returned walks every match exactly once
+        let first = client.get_proofs_by_issuer_paged(&issuer, &0, &2);
+        assert_eq!(ids(first), Vec::from_slice(&env, &[1, 3]));
+        let second = client.get_proofs_by_issuer_paged(&issuer, &4, &2);
+        assert_eq!(ids(second), Vec::from_slice(&env, &[5, 7]));
+        assert!(client.get_proofs_by_issuer_paged(&issuer, &8, &2).is_empty());
+        assert!(client.get_proofs_by_issuer_paged(&issuer, &1, &0).is_empty());
+        
+        assert_eq!(ids(client.get_proofs_by_issuer(&other)), Vec::from_slice(&env, &[2, 4, 6]));
+    }
+
+    #[test]
+    fn test_issue_proof_checks_hash_against_event_data() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let event_data = Bytes::from_slice(&env, b"event data");
+        let hash = event_hash(&env, &event_data);
+        let proof_id = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        assert_eq!(client.get_proof(&proof_id).hash, hash);
+        
+        // A hash of other data, or arbitrary bytes, is rejected
+        let other = event_hash(&env, &Bytes::from_slice(&env, b"other data"));
+        assert!(client.try_issue_proof(&issuer, &event_data, &other, &None, &None, &None, &None).is_err());
+        assert!(client.try_issue_proof(&issuer, &event_data, &Bytes::from_slice(&env, b"test hash"), &None, &None, &None, &None).is_err());
+        assert!(client.try_reissue_proof(&issuer, &proof_id, &Bytes::from_slice(&env, b"new data"), &hash).is_err());
+        assert_eq!(client.get_proof_count(), 1);
+    }
+
+    #[test]
+    fn test_proof_ttl_blocks_verification_after_expiry() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let key = SigningKey::from_bytes(&[10u8; 32]);
+        client.initialize(&admin);
+        
+        let event_data = Bytes::from_slice(&env, b"event data");
+        let hash = event_hash(&env, &event_data);
+        env.ledger().with_mut(|li| li.timestamp = 1_000);
+        let verified = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &Some(100));
+        let stale = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &Some(100));
+        let forever = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &Some(0));
+        assert_eq!(client.get_proof(&stale).expires_at, 1_100);
+        assert_eq!(client.get_proof(&forever).expires_at, 0);
+        
+        let signature = sign_attestation(&env, &client, &admin, &key, verified);
+        client.verify_proof(&admin, &verified, &signature);
+        
+        // Expiry is inclusive of its last second
+        env.ledger().with_mut(|li| li.timestamp = 1_100);
+        assert!(!client.is_expired(&stale));
+        env.ledger().with_mut(|li| li.timestamp = 1_101);
+        assert!(client.is_expired(&stale));
+        let signature = sign_attestation(&env, &client, &admin, &key, stale);
+        assert!(client.try_verify_proof(&admin, &stale, &signature).is_err());
+        assert!(!client.is_proof_verified(&verified));
+        
+        env.ledger().with_mut(|li| li.timestamp = u64::MAX);
+        assert!(!client.is_expired(&forever));
+        let signature = sign_attestation(&env, &client, &admin, &key, forever);
+        assert!(client.verify_proof(&admin, &forever, &signature));
+    }
+
+    #[test]
+    fn test_issue_proofs_batch_assigns_sequential_ids() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let single = Bytes::from_slice(&env, b"single event");
+        client.issue_proof(&issuer, &single, &event_hash(&env, &single), &None, &None, &None, &None);
+        
+        let entry = |data: &[u8]| {
+            let event_data = Bytes::from_slice(&env, data);
+            (event_data.clone(), event_hash(&env, &event_data))
+        };
+        let entries = Vec::from_array(&env, [entry(b"first"), entry(b"second"), entry(b"third")]);
+        let ids = client.issue_proofs_batch(&issuer, &entries);
+        assert_eq!(ids, Vec::from_array(&env, [2, 3, 4]));
+        assert_eq!(client.get_proof_count(), 4);
+        assert_eq!(client.get_proof(&3).event_data, Bytes::from_slice(&env, b"second"));
+        assert_eq!(client.get_proof_breakdown().unverified, 4);
+        
+        // One bad entry rejects the whole batch
+        let mut bad = Vec::from_array(&env, [entry(b"fourth")]);
+        bad.push_back((Bytes::from_slice(&env, b"fifth"), Bytes::from_slice(&env, b"wrong hash")));
+        assert!(client.try_issue_proofs_batch(&issuer, &bad).is_err());
+        assert!(client.try_issue_proofs_batch(&issuer, &Vec::new(&env)).is_err());
+        
+        let mut oversized = Vec::new(&env);
+        for _ in 0..51 {
+            oversized.push_back(entry(b"bulk"));
+        }
+        assert!(client.try_issue_proofs_batch(&issuer, &oversized).is_err());
+        assert_eq!(client.get_proof_count(), 4);
+    }
+
+    #[test]
+    fn test_registry_root_matches_reference() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let mut hashes = Vec::new(&env);
+        for data in [b"first".as_slice(), b"second", b"third"] {
+            let event_data = Bytes::from_slice(&env, data);
+            let hash = event_hash(&env, &event_data);
+            client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+            hashes.push_back(hash);
+        }
+        
+        // sha256(0x00 || hash) leaves, sha256(0x01 || left || right) nodes, the odd leaf carried up
+        let leaf = |hash: Bytes| {
+            let mut preimage = Bytes::from_array(&env, &[0u8]);
+            preimage.append(&hash);
+            Bytes::from(env.crypto().sha256(&preimage))
+        };
+        let node = |left: Bytes, right: Bytes| {
+            let mut preimage = Bytes::from_array(&env, &[1u8]);
+            preimage.append(&left);
+            preimage.append(&right);
+            env.crypto().sha256(&preimage)
+        };
+        let pair = Bytes::from(node(leaf(hashes.get(0).unwrap()), leaf(hashes.get(1).unwrap())));
+        let reference = node(pair, leaf(hashes.get(2).unwrap()));
+        
+        assert_eq!(client.compute_registry_root(&1, &3), reference);
+        assert_eq!(Bytes::from(client.compute_registry_root(&2, &2)), leaf(hashes.get(1).unwrap()));
+        assert!(client.try_compute_registry_root(&0, &3).is_err());
+        assert!(client.try_compute_registry_root(&3, &1).is_err());
+        assert!(client.try_compute_registry_root(&1, &4).is_err());
+        
+        assert!(client.try_publish_registry_root(&issuer, &1, &3).is_err());
+        env.ledger().with_mut(|li| li.timestamp = 500);
+        assert_eq!(client.publish_registry_root(&admin, &1, &3), reference);
+        assert_eq!(client.get_registry_root(), Some(RegistryRoot { root: reference, from_id: 1, to_id: 3, published_at: 500 }));
+        
+        // A deleted proof keeps its place under a fixed marker leaf
+        client.delete_proof(&admin, &2);
+        let marker = Bytes::from_slice(&env, b"verinode:deleted-proof");
+        let pair = Bytes::from(node(leaf(hashes.get(0).unwrap()), leaf(marker)));
+        assert_eq!(client.compute_registry_root(&1, &3), node(pair, leaf(hashes.get(2).unwrap())));
+    }
+
+    #[test]
+    fn test_reject_proof() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let issuer = Address::generate(&env);
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        let rejected = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        let verified = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let signature = sign_attestation(&env, &client, &admin, &key, verified);
+        client.verify_proof(&admin, &verified, &signature);
+        
+        // Only the admin rejects, only with a reason, and only pending proofs
+        let reason = String::from_str(&env, "event data does not match source");
+        assert!(client.try_reject_proof(&issuer, &rejected, &reason).is_err());
+        assert!(client.try_reject_proof(&admin, &rejected, &String::from_str(&env, "")).is_err());
+        assert!(client.try_reject_proof(&admin, &verified, &reason).is_err());
+        
+        client.reject_proof(&admin, &rejected, &reason);
+        assert_eq!(client.get_proof(&rejected).status, ProofStatus::Rejected);
+        assert_eq!(client.get_rejection_reason(&rejected), Some(reason.clone()));
+        assert_eq!(client.get_rejection_reason(&verified), None);
+        assert!(!client.is_proof_verified(&rejected));
+        
+        // A rejected proof cannot be verified or rejected again
+        let signature = sign_attestation(&env, &client, &admin, &key, rejected);
+        assert!(client.try_verify_proof(&admin, &rejected, &signature).is_err());
+        assert!(client.try_reject_proof(&admin, &rejected, &reason).is_err());
+        
+        // Revoking sets the status as well as the revoked flag
+        client.revoke_proof(&issuer, &verified);
+        assert_eq!(client.get_proof(&verified).status, ProofStatus::Revoked);
+        let breakdown = client.get_proof_breakdown();
+        assert_eq!((breakdown.verified, breakdown.unverified, breakdown.revoked), (0, 1, 1));
+    }
+
+    #[test]
+    fn test_get_proofs_by_status() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        for _ in 0..6 {
+            client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        }
+        
+        // 1 and 4 verified, 2 rejected, 5 revoked, 3 and 6 left pending
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        for id in [1u64, 4] {
+            let signature = sign_attestation(&env, &client, &admin, &key, id);
+            client.verify_proof(&admin, &id, &signature);
+        }
+        client.reject_proof(&admin, &2, &String::from_str(&env, "mismatched source"));
+        client.revoke_proof(&issuer, &5);
+        
+        let ids = |proofs: Vec<Proof>| {
+            let mut ids = Vec::new(&env);
+            for proof in proofs.iter() {
+                ids.push_back(proof.id);
+            }
+            ids
+        };
+        
+        assert_eq!(ids(client.get_proofs_by_status(&ProofStatus::Verified, &0, &10)), Vec::from_slice(&env, &[1, 4]));
+        assert_eq!(ids(client.get_proofs_by_status(&ProofStatus::Pending, &0, &10)), Vec::from_slice(&env, &[3, 6]));
+        assert_eq!(ids(client.get_proofs_by_status(&ProofStatus::Rejected, &0, &10)), Vec::from_slice(&env, &[2]));
+        assert_eq!(ids(client.get_proofs_by_status(&ProofStatus::Revoked, &0, &10)), Vec::from_slice(&env, &[5]));
+        
+        // Pages continue from the last id returned plus one
+        assert_eq!(ids(client.get_proofs_by_status(&ProofStatus::Pending, &0, &1)), Vec::from_slice(&env, &[3]));
+        assert_eq!(ids(client.get_proofs_by_status(&ProofStatus::Pending, &4, &1)), Vec::from_slice(&env, &[6]));
+        assert!(client.get_proofs_by_status(&ProofStatus::Pending, &7, &10).is_empty());
+    }
+
+    #[test]
+    fn test_issuer_allowlist() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let allowed = Address::generate(&env);
+        let outsider = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        
+        // Open deployments let anyone issue
+        assert!(!client.is_issuer_allowlist_enabled());
+        client.issue_proof(&outsider, &event_data, &hash, &None, &None, &None, &None);
+        
+        assert!(client.try_allow_issuer(&outsider, &outsider).is_err());
+        client.allow_issuer(&admin, &allowed);
+        client.set_issuer_allowlist_enabled(&admin, &true);
+        
+        client.issue_proof(&allowed, &event_data, &hash, &None, &None, &None, &None);
+        assert!(client.try_issue_proof(&outsider, &event_data, &hash, &None, &None, &None, &None).is_err());
+        let entries = Vec::from_array(&env, [(event_data.clone(), hash.clone())]);
+        assert!(client.try_issue_proofs_batch(&outsider, &entries).is_err());
+        
+        client.disallow_issuer(&admin, &allowed);
+        assert!(!client.is_issuer_allowed(&allowed));
+        assert!(client.try_issue_proof(&allowed, &event_data, &hash, &None, &None, &None, &None).is_err());
+        
+        client.set_issuer_allowlist_enabled(&admin, &false);
+        assert!(client.is_issuer_allowed(&outsider));
+    }
+
+    #[test]
+    fn test_proof_id_space_exhaustion_panics() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        // Issuing first stores the breakdown counters so nothing rescans the id range
+        client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        env.as_contract(&contract_id, || {
+            env.storage().instance().set(&DataKey::ProofCount, &(u64::MAX - 1));
+        });
+        
+        // The last id is still issued; a batch that would run past it is refused whole
+        let entries = Vec::from_array(&env, [(event_data.clone(), hash.clone()), (event_data.clone(), hash.clone())]);
+        assert!(client.try_issue_proofs_batch(&issuer, &entries).is_err());
+        assert_eq!(client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None), u64::MAX);
+        
+        let result = client.try_issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        assert!(result.is_err());
+        assert_eq!(client.get_proof_count(), u64::MAX);
+    }
+
+    #[test]
+    fn test_delete_proof() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        let kept = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        let deleted = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let signature = sign_attestation(&env, &client, &admin, &key, deleted);
+        client.verify_proof(&admin, &deleted, &signature);
+        
+        assert!(client.try_delete_proof(&issuer, &deleted).is_err());
+        client.delete_proof(&admin, &deleted);
+        
+        assert!(client.is_proof_deleted(&deleted));
+        assert!(!client.is_proof_deleted(&kept));
+        assert!(client.try_get_proof(&deleted).is_err());
+        assert!(client.try_delete_proof(&admin, &deleted).is_err());
+        assert!(!client.is_proof_verified(&deleted));
+        assert_eq!(client.get_proof_verifier(&deleted), None);
+        
+        // Ids stay allocated and lookups skip the gap
+        assert_eq!(client.get_proof_count(), 2);
+        assert_eq!(client.get_proofs_by_issuer(&issuer).len(), 1);
+        let breakdown = client.get_proof_breakdown();
+        assert_eq!((breakdown.verified, breakdown.unverified), (0, 1));
+        assert_eq!(client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None), 3);
+    }
+
+    #[test]
+    fn test_denylisted_issuer_blocked() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let denied = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        assert!(client.try_add_to_denylist(&issuer, &denied).is_err());
+        client.add_to_denylist(&admin, &denied);
+        
+        // The entry is persistent and kept alive by the proof TTL extension
+        let sequence = env.ledger().sequence();
+        assert_eq!(entry_live_until(&env, &contract_id, &DataKey::Denylisted(denied.clone())), sequence + 120_960);
+        
+        assert!(client.try_issue_proof(&denied, &event_data, &hash, &None, &None, &None, &None).is_err());
+        client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        
+        // The denylist wins over the allowlist
+        client.allow_issuer(&admin, &denied);
+        client.set_issuer_allowlist_enabled(&admin, &true);
+        assert!(client.try_issue_proof(&denied, &event_data, &hash, &None, &None, &None, &None).is_err());
+        
+        client.remove_from_denylist(&admin, &denied);
+        client.issue_proof(&denied, &event_data, &hash, &None, &None, &None, &None);
+    }
+
+    #[test]
+    fn test_linked_proof_chain() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        let root = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        let child = client.issue_linked_proof(&issuer, &event_data, &hash, &root);
+        let grandchild = client.issue_linked_proof(&issuer, &event_data, &hash, &child);
+        
+        assert_eq!(client.get_proof(&root).parent_id, None);
+        assert_eq!(client.get_proof(&grandchild).parent_id, Some(child));
+        let chain = client.get_proof_chain(&grandchild);
+        assert_eq!(chain.len(), 3);
+        assert_eq!(chain.get(0).unwrap().id, grandchild);
+        assert_eq!(chain.get(2).unwrap().id, root);
+        
+        assert!(client.try_issue_linked_proof(&issuer, &event_data, &hash, &99).is_err());
+        assert!(client.try_get_proof_chain(&99).is_err());
+        
+        // Chains stop growing at the depth limit
+        let mut tip = grandchild;
+        for _ in 3..32 {
+            tip = client.issue_linked_proof(&issuer, &event_data, &hash, &tip);
+        }
+        assert_eq!(client.get_proof_chain(&tip).len(), 32);
+        assert!(client.try_issue_linked_proof(&issuer, &event_data, &hash, &tip).is_err());
+    }
+
+    #[test]
+    fn test_verify_proofs_batch() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        let first = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        let second = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        let rejected = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        client.reject_proof(&admin, &rejected, &String::from_str(&env, "bad data"));
+        
+        // Each item carries the admin's attestation, as verify_proof requires; skipped items' signatures are ignored
+        let key = SigningKey::from_bytes(&[8u8; 32]);
+        let ids = Vec::from_array(&env, [first, 99, second, rejected, first]);
+        let unused = BytesN::from_array(&env, &[0u8; 64]);
+        let unsigned = Vec::from_array(&env, [unused.clone(), unused.clone(), unused.clone(), unused.clone(), unused.clone()]);
+        assert!(client.try_verify_proofs_batch(&admin, &ids, &unsigned).is_err());
+        let first_signature = sign_attestation(&env, &client, &admin, &key, first);
+        let second_signature = sign_attestation(&env, &client, &admin, &key, second);
+        let signatures = Vec::from_array(&env, [first_signature.clone(), unused.clone(), second_signature.clone(), unused.clone(), unused.clone()]);
+        
+        // Mismatched lists and swapped signatures are rejected outright
+        assert!(client.try_verify_proofs_batch(&issuer, &ids, &signatures).is_err());
+        assert!(client.try_verify_proofs_batch(&admin, &ids, &Vec::from_array(&env, [first_signature.clone()])).is_err());
+        let swapped = Vec::from_array(&env, [second_signature, unused.clone(), first_signature, unused.clone(), unused]);
+        assert!(client.try_verify_proofs_batch(&admin, &ids, &swapped).is_err());
+        assert!(!client.is_proof_verified(&first));
+        
+        let results = client.verify_proofs_batch(&admin, &ids, &signatures);
+        assert_eq!(results, Vec::from_array(&env, [true, false, true, false, false]));
+        
+        assert!(client.is_proof_verified(&first));
+        assert!(client.is_proof_verified(&second));
+        assert_eq!(client.get_proof_verifier(&first), Some(admin.clone()));
+        assert!(client.verify_attestation(&first));
+        assert!(client.verify_attestation(&second));
+        let breakdown = client.get_proof_breakdown();
+        assert_eq!((breakdown.verified, breakdown.unverified), (2, 1));
+        
+        let mut too_many = Vec::new(&env);
+        for id in 0..51u64 {
+            too_many.push_back(id);
+        }
+        assert!(client.try_verify_proofs_batch(&admin, &too_many, &Vec::new(&env)).is_err());
+    }
+
+    #[test]
+    fn test_get_latest_proofs() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        client.initialize(&admin);
+        assert!(client.get_latest_proofs(&10).is_empty());
+        
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        for _ in 0..4 {
+            client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        }
+        client.delete_proof(&admin, &3);
+        
+        // Fewer proofs than asked for: all live ones, newest first
+        let latest = client.get_latest_proofs(&10);
+        assert_eq!(latest.len(), 3);
+        assert_eq!(latest.get(0).unwrap().id, 4);
+        assert_eq!(latest.get(1).unwrap().id, 2);
+        assert_eq!(latest.get(2).unwrap().id, 1);
+        assert_eq!(client.get_latest_proofs(&2).len(), 2);
+        
+        assert!(client.try_get_latest_proofs(&101).is_err());
+    }
+
+    #[test]
+    fn test_amend_pending_proof() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        client.initialize(&admin);
+        
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        let corrected = Bytes::from_slice(&env, b"corrected event data");
+        let corrected_hash = event_hash(&env, &corrected);
+        let proof_id = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        
+        assert!(client.try_amend_proof(&admin, &proof_id, &corrected, &corrected_hash).is_err());
+        assert!(client.try_amend_proof(&issuer, &proof_id, &corrected, &hash).is_err());
+        client.amend_proof(&issuer, &proof_id, &corrected, &corrected_hash);
+        
+        let (topics, data) = last_event(&env);
+        assert_eq!(topics, (symbol_short!("proof"), symbol_short!("amended"), proof_id).into_val(&env));
+        assert_eq!(<(Bytes, Bytes)>::try_from_val(&env, &data).unwrap(), (hash.clone(), corrected_hash.clone()));
+        let proof = client.get_proof(&proof_id);
+        assert_eq!((proof.event_data, proof.hash), (corrected, corrected_hash));
+        
+        // Issuers blocked since issuing cannot amend
+        client.add_to_denylist(&admin, &issuer);
+        assert!(client.try_amend_proof(&issuer, &proof_id, &event_data, &hash).is_err());
+        client.remove_from_denylist(&admin, &issuer);
+        
+        // Expired proofs cannot be brought back by amending them
+        let expiring = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &Some(10));
+        env.ledger().with_mut(|li| li.timestamp += 11);
+        assert!(client.try_amend_proof(&issuer, &expiring, &event_data, &hash).is_err());
+        
+        // Verified and revoked proofs are settled
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let signature = sign_attestation(&env, &client, &admin, &key, proof_id);
+        client.verify_proof(&admin, &proof_id, &signature);
+        assert!(client.try_amend_proof(&issuer, &proof_id, &event_data, &hash).is_err());
+        
+        let revoked = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        client.revoke_proof(&issuer, &revoked);
+        assert!(client.try_amend_proof(&issuer, &revoked, &event_data, &hash).is_err());
+    }
+
+    #[test]
+    fn test_unresolved_dispute_lapses() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, VerinodeContract);
+        let client = VerinodeContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let verifier = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let key = SigningKey::from_bytes(&[6u8; 32]);
+        client.initialize(&admin);
+        client.add_verifier(&admin, &verifier);
+        
+        let event_data = Bytes::from_slice(&env, b"test event data");
+        let hash = event_hash(&env, &event_data);
+        let proof_id = client.issue_proof(&issuer, &event_data, &hash, &None, &None, &None, &None);
+        let signature = sign_attestation(&env, &client, &verifier, &key, proof_id);
+        client.verify_proof(&verifier, &proof_id, &signature);
+        
+        client.challenge_proof(&verifier, &proof_id, &Bytes::from_slice(&env, b"counter evidence"));
+        assert!(!client.is_proof_verified(&proof_id));
+        assert!(client.try_close_lapsed_dispute(&proof_id).is_err());
+        
+        // Left unresolved for the whole resolution period, the dispute stops suspending the proof
+        env.ledger().with_mut(|li| li.timestamp = 604_801);
+        assert_eq!(client.get_proof_dispute(&proof_id), None);
+        assert!(client.is_proof_verified(&proof_id));
+        assert!(client.try_resolve_dispute(&admin, &proof_id, &true).is_err());
+        
+        client.close_lapsed_dispute(&proof_id);
+        assert!(client.try_close_lapsed_dispute(&proof_id).is_err());
+        assert!(client.is_verification_final(&proof_id));
+    }
 }